@@ -0,0 +1,17 @@
+//! Run with `cargo +nightly fuzz run decode_polyline` from the repository root.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use osrm_interface::polyline::decode_polyline;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(encoded) = std::str::from_utf8(data) else {
+        return;
+    };
+    for precision in [5, 6] {
+        if let Ok(points) = decode_polyline(encoded, precision) {
+            // Every vertex requires at least two bytes
+            assert!(points.len() <= encoded.len() / 2);
+        }
+    }
+});
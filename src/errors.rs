@@ -4,6 +4,8 @@
 
 use thiserror::Error;
 
+use crate::osrm_response_types::ResponseCode;
+
 /// Errors specifically from calling a service using the native
 /// OSRM engine.
 #[derive(Error, Debug)]
@@ -29,6 +31,22 @@ pub enum RemoteOsrmError {
     // Failed to parse OSRM response.
     #[error("Failed to parse OSRM response: {0}")]
     JsonParse(Box<dyn std::error::Error + Send + Sync>),
+    /// The endpoint responded with a redirect to the contained location,
+    /// which is not followed. This usually indicates a misconfigured proxy
+    /// or endpoint url.
+    #[error("Unexpected redirect to: {0}")]
+    UnexpectedRedirect(String),
+    /// Resolving or connecting to the endpoint took longer than the
+    /// [`connect_timeout`](crate::remote::OsrmEngine::connect_timeout).
+    #[error("Timed out connecting to the endpoint")]
+    ConnectTimeout,
+    /// Waiting for or reading the response took longer than the
+    /// [`read_timeout`](crate::remote::OsrmEngine::read_timeout).
+    #[error("Timed out reading the response from the endpoint")]
+    ReadTimeout,
+    /// Any other phase of the request timed out, eg. sending it.
+    #[error("Request to the endpoint timed out: {0}")]
+    Timeout(String),
     /// Other error from the request.
     #[error("Endpoint error: {0}")]
     EndpointError(String),
@@ -43,8 +61,80 @@ pub enum OsrmError {
     /// Request produced an empty response.
     #[error("Request produced an empty response: {0}")]
     EmptyResponse(String),
+    /// Failed to parse a response independently of an engine, eg. with
+    /// [`RouteResponse::from_json`](crate::route::RouteResponse::from_json).
+    #[error("Failed to parse OSRM response: {0}")]
+    JsonParse(Box<dyn std::error::Error + Send + Sync>),
+    /// OSRM responded with a code other than `"Ok"`, eg. `"NoRoute"` or
+    /// `"InvalidQuery"`, along with its message.
+    ///
+    /// For `"NoSegment"`, `failed_coordinate_index` is the index of the input
+    /// coordinate which could not be snapped, when OSRM reports it in the
+    /// message.
+    ///
+    /// The engines return this rather than a response for any code other than
+    /// `"Ok"`. See [`response_code`](Self::response_code) to match on the code.
+    #[error("OSRM responded with {code}: {message}")]
+    Service {
+        code: String,
+        message: String,
+        failed_coordinate_index: Option<usize>,
+    },
     #[error("Error from the native backend: {0}")]
-    Native(NativeOsrmError),
+    Native(#[from] NativeOsrmError),
     #[error("Error from the remote backend: {0}")]
-    Remote(RemoteOsrmError),
+    Remote(#[from] RemoteOsrmError),
+}
+
+impl OsrmError {
+    /// The code of a [`Service`](Self::Service) error as a [`ResponseCode`].
+    /// `None` for every other error.
+    pub fn response_code(&self) -> Option<ResponseCode> {
+        match self {
+            Self::Service { code, .. } => Some(ResponseCode::from(code.as_str())),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "remote")]
+impl From<ureq::Error> for RemoteOsrmError {
+    fn from(e: ureq::Error) -> Self {
+        use ureq::Timeout;
+        match e {
+            ureq::Error::Timeout(Timeout::Resolve | Timeout::Connect) => Self::ConnectTimeout,
+            ureq::Error::Timeout(Timeout::RecvResponse | Timeout::RecvBody) => Self::ReadTimeout,
+            ureq::Error::Timeout(timeout) => Self::Timeout(timeout.to_string()),
+            e => Self::EndpointError(e.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "remote")]
+impl From<serde_json::Error> for RemoteOsrmError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::JsonParse(Box::new(e))
+    }
+}
+
+/// Maps to [`RemoteOsrmError`], see its `From<ureq::Error>` implementation:
+/// timeouts map to [`ConnectTimeout`](RemoteOsrmError::ConnectTimeout),
+/// [`ReadTimeout`](RemoteOsrmError::ReadTimeout) or
+/// [`Timeout`](RemoteOsrmError::Timeout), and all other errors to
+/// [`EndpointError`](RemoteOsrmError::EndpointError).
+#[cfg(feature = "remote")]
+impl From<ureq::Error> for OsrmError {
+    fn from(e: ureq::Error) -> Self {
+        Self::Remote(e.into())
+    }
+}
+
+/// Maps to [`OsrmError::JsonParse`], as for parsing with `from_json`. The
+/// engines wrap their parse errors explicitly, eg. in
+/// [`RemoteOsrmError::JsonParse`].
+#[cfg(feature = "serde")]
+impl From<serde_json::Error> for OsrmError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::JsonParse(Box::new(e))
+    }
 }
@@ -5,9 +5,10 @@
 //! ## Engines
 //!
 //! The osrm engine is an instance which allows calling of the various services provided by osrm.
-//! Three engines are provided. Their API's differ only in initialisation. Otherwise they are identical.
+//! Four engines are provided. Their API's differ only in initialisation, and the services of the wasm engine being
+//! `async`. Otherwise they are identical.
 //!
-//! To minimise compile times and binary sizes, the native and remote engines are disabled by default
+//! To minimise compile times and binary sizes, the native, remote and wasm engines are disabled by default
 //! and are gated by feature flags.
 //!
 //! - mock: For development convenience when the backend is otherwise unavailable.
@@ -17,6 +18,8 @@
 //! - native (`feature="native"`): Call by natively interfacing into an installed version of osrm-backend
 //!   through a C++ wrapper. **REQUIRES A LOCAL INSTALLATION OF `osrm-backend` AND LOCAL VERSIONS OF
 //!   APPROPRIATELY EXTRACTED MAP DATA.**
+//! - wasm (`feature="wasm"`): Call the web API from WebAssembly, eg. the browser, through `fetch`. The services
+//!   are `async fn`s, otherwise as for the remote engine.
 //!
 //! For more information about initialising the engines and their requirements, see their module pages.
 //!
@@ -55,9 +58,19 @@
 //! - `feature="native"`: Enable the native engine - will not compile without the ability to link a compiled version of
 //!   osrm-backend.
 //! - `feature="remote"`: Enable the remote engine for routing through the HTTP web API.
+//! - `feature="async"`: Enable `remote::AsyncOsrmEngine`, an async wrapper of the remote engine sending
+//!   requests from a pool of worker threads. Enables the remote feature flag also.
+//! - `feature="wasm"`: Enable the wasm engine, sending requests through a fetch binding supplied by the frontend.
+//!   Enables the serde feature flag also.
+//! - `feature="log"`: Log non-fatal warnings through the `log` crate, eg. table excludes which do not match the
+//!   profile of the remote engine, or approaches built without bearings or radiuses outside of strict mode.
 //! - `feature="serde"`: Add [`serde::Serialize`] and [`serde::Deserialize`] to all types. Response types require `Deserialize`
 //!   when using the remote and native engines anyway, so the remote and native feature flags will enable this flag also.
 //!
+//! With no features enabled, the crate is only the core: points, bearings, request builders and their urls, response
+//! types, polyline decoding and the mock engine. It depends on nothing but `thiserror` and `itertools`, with no HTTP
+//! client or FFI, so builds for targets without either, eg. a WASM frontend, along with the wasm feature.
+//!
 //! ## Example usage
 //!
 //! First initialise the appropriate engine. Then construct your requests with
@@ -83,6 +96,26 @@
 //!     .expect("Failed to route request");
 //! ```
 //!
+//! ## Sharing an engine between threads
+//!
+//! All three engines are `Send + Sync` and their services take `&self`, so a
+//! single engine can be shared between threads, eg. the handlers of a web
+//! server, by keeping it in a `static` or an [`Arc`](std::sync::Arc). Clones
+//! of the remote and native engines share their connections and the loaded
+//! map data respectively, so cloning is cheap, but unnecessary.
+//!
+//! ```
+//! use std::sync::LazyLock;
+//! use osrm_interface::{Point, mock::OsrmEngine};
+//!
+//! static ENGINE: LazyLock<OsrmEngine> = LazyLock::new(OsrmEngine::new);
+//!
+//! let from = Point::new(48.040437, 10.316550).expect("Invalid point");
+//! let to = Point::new(49.006101, 9.052887).expect("Invalid point");
+//! let handle = std::thread::spawn(move || ENGINE.simple_route(from, to));
+//! handle.join().unwrap().expect("Failed to route");
+//! ```
+//!
 //! ## A note on snapping
 //!
 //! The nearest service provides snapping of `Points` to nodes on the map. All
@@ -97,6 +130,8 @@
 //! snapping will return the closest node in the German map (likely on the
 //! south-western side). This is also true when points near country borders are
 //! snapped using map data which does not contain both countries.
+//! [`OsrmService::is_on_map`](services::OsrmService::is_on_map) guards
+//! against this by rejecting points which snap further than a threshold.
 //!
 //! ## Serialisation/Deserialisation
 //!
@@ -109,14 +144,18 @@
 //!
 //! The main [osrm-backend documentation](<https://github.com/Project-OSRM/osrm-backend/wiki>) is located on the osrm-backend
 //! Github. The HTTP API of `osrm-routed` is a good source of information.
-#![cfg_attr(all(doc), feature(doc_cfg))]
+#![cfg_attr(docsrs, feature(doc_cfg))]
 
 pub mod services;
 pub use services::{r#match, nearest, route, table, trip};
 
 pub mod errors;
 pub mod osrm_response_types;
+pub mod polyline;
 pub mod request_types;
+#[cfg(feature = "serde")]
+mod serde_ops;
+mod snap_cache;
 mod str_ops;
 
 #[allow(unused_imports)]
@@ -128,8 +167,21 @@ pub mod native;
 #[cfg(feature = "remote")]
 pub mod remote;
 
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
 pub mod mock;
 
+// The engines are documented as shareable between threads
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<mock::OsrmEngine>();
+    #[cfg(feature = "remote")]
+    assert_send_sync::<remote::OsrmEngine>();
+    #[cfg(feature = "native")]
+    assert_send_sync::<native::OsrmEngine>();
+};
+
 /// The algorithm used in the pre-processing pipeline to
 /// generate the .osrm map files.
 ///
@@ -160,6 +212,42 @@ impl Algorithm {
     }
 }
 
+/// The profile with which the underlying map data was extracted.
+///
+/// The profile is placed in the URL. In many cases, it is ignored.
+/// See the `remote` module for more information about `Profile`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Profile {
+    Car,
+    Bike,
+    Foot,
+}
+impl Profile {
+    /// Formats the variant as a lowercase &str. The form expected
+    /// by `osrm-routed`.
+    ///
+    /// eg. `"bike"` or `"car"` or `"foot"`
+    pub fn url_form(self) -> &'static str {
+        match self {
+            Self::Bike => "bike",
+            Self::Car => "car",
+            Self::Foot => "foot",
+        }
+    }
+
+    /// Whether `exclude` is meaningful for the default version of the profile.
+    ///
+    /// Car excludes apply to the car profile, bicycle excludes to the bike
+    /// profile and the foot profile has no excludes. Custom profiles may differ.
+    pub fn supports_exclude(self, exclude: &request_types::Exclude) -> bool {
+        matches!(
+            (self, exclude),
+            (Self::Car, request_types::Exclude::Car(_))
+                | (Self::Bike, request_types::Exclude::Bicycle(_))
+        )
+    }
+}
+
 /// A (latitude, longitude pair). The basic coordinate type to pass to OSRM.
 ///
 /// Constructing with `new` will check -90 <= latitude <= 90 and
@@ -167,6 +255,11 @@ impl Algorithm {
 ///
 /// [`new_unchecked`](Self::new_unchecked) is also provided.
 ///
+/// **Note:** [`new`](Self::new) takes latitude first, whereas OSRM and GeoJSON
+/// order coordinates longitude first. When working with longitude first
+/// coordinates, use [`from_lng_lat`](Self::from_lng_lat) rather than swapping
+/// the arguments by hand.
+///
 /// Implements [`serde::Deserialize`] and
 /// [`serde::Serialize`] if `feature="serde"` is set.
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
@@ -191,6 +284,14 @@ impl Point {
         })
     }
 
+    /// Construct from a longitude first pair, the order used by OSRM and
+    /// GeoJSON. Equivalent to `Point::new(latitude, longitude)`.
+    ///
+    /// Returns `None` if the coordinates are out of range, as for [`new`](Self::new).
+    pub fn from_lng_lat(longitude: f64, latitude: f64) -> Option<Self> {
+        Self::new(latitude, longitude)
+    }
+
     /// Init without checking latitude and longitude.
     ///
     /// OSRM will reject points
@@ -210,4 +311,142 @@ impl Point {
     pub fn longitude(&self) -> f64 {
         self.longitude
     }
+
+    /// Returns the point as a `(latitude, longitude)` tuple, the same order
+    /// as [`new`](Self::new).
+    ///
+    /// See [`as_lon_lat`](Self::as_lon_lat) for the order used by OSRM.
+    pub fn as_tuple(&self) -> (f64, f64) {
+        (self.latitude, self.longitude)
+    }
+
+    /// Returns the point as a `(longitude, latitude)` tuple, the order
+    /// used by OSRM in urls and responses.
+    pub fn as_lon_lat(&self) -> (f64, f64) {
+        (self.longitude, self.latitude)
+    }
+
+    /// The great-circle (crow-fly) distance to `other` in meters, calculated
+    /// with the haversine formula.
+    pub fn haversine_distance(&self, other: &Point) -> f64 {
+        const EARTH_RADIUS_M: f64 = 6_371_008.8;
+
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let d_lat = lat2 - lat1;
+        let d_lon = (other.longitude - self.longitude).to_radians();
+
+        let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_M * a.sqrt().min(1.0).asin()
+    }
+}
+
+/// Formats the point as OSRM expects it in urls, longitude first with six
+/// decimal places (~0.1m), eg. `"13.400000,52.500000"`.
+///
+/// Note the order is the opposite of [`Point::new`].
+impl std::fmt::Display for Point {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.6},{:.6}", self.longitude, self.latitude)
+    }
+}
+
+/// Parses a `"latitude,longitude"` string, eg. `"52.5, 13.4"`, as read from
+/// CSV or config files. Whitespace around each value is ignored.
+///
+/// Note the order is the same as [`Point::new`] and the opposite of the
+/// url form produced by the [`Display`](std::fmt::Display) impl, so a point
+/// does not round trip through `to_string` and `parse`.
+///
+/// ```
+/// use osrm_interface::{Point, PointParseError};
+///
+/// let point: Point = "52.5, 13.4".parse().expect("Invalid point");
+/// assert_eq!(point.as_tuple(), (52.5, 13.4));
+/// assert!(matches!(
+///     "13.4".parse::<Point>(),
+///     Err(PointParseError::Malformed(_))
+/// ));
+/// ```
+impl std::str::FromStr for Point {
+    type Err = PointParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || PointParseError::Malformed(s.to_string());
+        let (latitude, longitude) = s.split_once(',').ok_or_else(malformed)?;
+        let latitude: f64 = latitude.trim().parse().map_err(|_| malformed())?;
+        let longitude: f64 = longitude.trim().parse().map_err(|_| malformed())?;
+        Self::new(latitude, longitude).ok_or(PointParseError::OutOfRange {
+            latitude,
+            longitude,
+        })
+    }
+}
+
+/// Errors from parsing a [`Point`] from a string.
+#[derive(thiserror::Error, Clone, Debug, PartialEq)]
+pub enum PointParseError {
+    /// The string is not two numbers separated by a comma.
+    #[error("Invalid point {0:?}; expected \"latitude,longitude\", eg. \"52.5,13.4\"")]
+    Malformed(String),
+    /// The latitude or longitude is outside of the valid range, see
+    /// [`Point::new`].
+    #[error(
+        "Point ({latitude}, {longitude}) is out of range; latitude must be within [-90, 90] and longitude within [-180, 180]"
+    )]
+    OutOfRange { latitude: f64, longitude: f64 },
+}
+
+/// An axis-aligned box of latitudes and longitudes, eg. the extent of a route.
+///
+/// Boxes crossing the antimeridian are not supported.
+///
+/// Implements [`serde::Deserialize`] and
+/// [`serde::Serialize`] if `feature="serde"` is set.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox {
+    south_west: Point,
+    north_east: Point,
+}
+
+impl BoundingBox {
+    /// The smallest box containing all the given points.
+    ///
+    /// Returns `None` if there are no points.
+    pub fn from_points(points: impl IntoIterator<Item = Point>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        Some(points.fold(
+            Self {
+                south_west: first,
+                north_east: first,
+            },
+            |bbox, p| Self {
+                south_west: Point::new_unchecked(
+                    bbox.south_west.latitude.min(p.latitude),
+                    bbox.south_west.longitude.min(p.longitude),
+                ),
+                north_east: Point::new_unchecked(
+                    bbox.north_east.latitude.max(p.latitude),
+                    bbox.north_east.longitude.max(p.longitude),
+                ),
+            },
+        ))
+    }
+
+    /// The corner with the minimum latitude and longitude.
+    pub fn south_west(&self) -> Point {
+        self.south_west
+    }
+
+    /// The corner with the maximum latitude and longitude.
+    pub fn north_east(&self) -> Point {
+        self.north_east
+    }
+
+    /// Whether the point lies within the box, inclusive of the edges.
+    pub fn contains(&self, point: &Point) -> bool {
+        (self.south_west.latitude..=self.north_east.latitude).contains(&point.latitude)
+            && (self.south_west.longitude..=self.north_east.longitude).contains(&point.longitude)
+    }
 }
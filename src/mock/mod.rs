@@ -2,4 +2,4 @@
 //! fabricated data for the sake of development when the backend is unavailable.
 
 mod osrm_engine;
-pub use osrm_engine::OsrmEngine;
+pub use osrm_engine::{DEFAULT_ASSUMED_SPEED_MPS, OsrmEngine};
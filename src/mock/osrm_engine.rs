@@ -1,17 +1,37 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use itertools::Itertools;
+
 use crate::Point;
 use crate::errors::OsrmError;
 use crate::r#match::{MatchRequest, MatchResponse};
 use crate::nearest::{NearestRequest, NearestResponse};
-use crate::osrm_response_types::{MatchRoute, MatchWaypoint, Route, TripWaypoint, Waypoint};
+use crate::osrm_response_types::{
+    Annotation, GeoJsonLineString, Geometry, MatchRoute, MatchWaypoint, Route, RouteLeg, RouteStep,
+    TripWaypoint, Waypoint,
+};
+use crate::request_types::{Annotations, GeometryType, OverviewZoom};
 use crate::route::{RouteRequest, RouteResponse, SimpleRouteResponse};
+use crate::services::OsrmService;
+use crate::snap_cache::SnapCache;
 use crate::table::{TableAnnotation, TableRequest, TableResponse};
 use crate::trip::{TripRequest, TripResponse};
 
+/// The default speed, in meters per second, assumed when estimating a
+/// duration from a crow-fly distance. Roughly 50 km/h.
+pub const DEFAULT_ASSUMED_SPEED_MPS: f64 = 13.9;
+
 /// The engine for calling into the mocked osrm-backend.
 ///
 /// The mock engine returns data of the appropriate type,
 /// but all data is fabricated.
-pub struct OsrmEngine {}
+pub struct OsrmEngine {
+    assumed_speed_mps: f64,
+    unroutable_points: Vec<Point>,
+    snap_distance_m: f64,
+    snap_cache: Option<SnapCache>,
+    call_count: AtomicUsize,
+}
 
 impl OsrmEngine {
     /// Initialise the mock engine.
@@ -20,7 +40,88 @@ impl OsrmEngine {
     /// all data is fabricated.
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
-        Self {}
+        Self {
+            assumed_speed_mps: DEFAULT_ASSUMED_SPEED_MPS,
+            unroutable_points: Vec::new(),
+            snap_distance_m: 0.0,
+            snap_cache: None,
+            call_count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Set the speed, in meters per second, used to convert crow-fly distances
+    /// into durations. eg. ~1.4 for pedestrians. Defaults to
+    /// [`DEFAULT_ASSUMED_SPEED_MPS`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `assumed_speed_mps` is not positive and finite.
+    pub fn assumed_speed_mps(mut self, assumed_speed_mps: f64) -> Self {
+        assert!(
+            assumed_speed_mps.is_finite() && assumed_speed_mps > 0.0,
+            "assumed_speed_mps must be positive and finite"
+        );
+        self.assumed_speed_mps = assumed_speed_mps;
+        self
+    }
+
+    /// Set points which cannot be routed to or from. Table cells between one of
+    /// these points and any other point are `None`, mimicking disconnected
+    /// parts of the road network. Routes and trips through them fail with
+    /// [`OsrmError::Service`] `NoRoute` and `NoTrips` respectively, as they
+    /// would from the other engines.
+    pub fn unroutable_points(mut self, points: Vec<Point>) -> Self {
+        self.unroutable_points = points;
+        self
+    }
+
+    /// Set the distance, in meters, from each point to its closest waypoint
+    /// returned by [`nearest`](Self::nearest). Defaults to `0.0`, as if every
+    /// point lay on a road. Further candidates are 10m apart from there.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `snap_distance_m` is negative or not finite.
+    pub fn snap_distance_m(mut self, snap_distance_m: f64) -> Self {
+        assert!(
+            snap_distance_m.is_finite() && snap_distance_m >= 0.0,
+            "snap_distance_m must be non-negative and finite"
+        );
+        self.snap_distance_m = snap_distance_m;
+        self
+    }
+
+    /// Cache the results of [`nearest`](Self::nearest) requests for a single
+    /// waypoint, keeping the `capacity` most recently used. Mirrors the cache
+    /// of the other engines, see [`call_count`](Self::call_count) to observe
+    /// cache hits.
+    pub fn snap_cache(mut self, capacity: usize) -> Self {
+        self.snap_cache = Some(SnapCache::new(capacity));
+        self
+    }
+
+    /// The number of service calls answered by the mock engine. Calls served
+    /// from the snap cache are not counted.
+    pub fn call_count(&self) -> usize {
+        self.call_count.load(Ordering::Relaxed)
+    }
+
+    /// Whether the route from `source` to `destination` should be reported
+    /// as unroutable.
+    fn is_unroutable(&self, source: &Point, destination: &Point) -> bool {
+        source != destination
+            && (self.unroutable_points.contains(source)
+                || self.unroutable_points.contains(destination))
+    }
+
+    /// The error OSRM responds with when a route between the points is
+    /// impossible.
+    fn no_route() -> OsrmError {
+        OsrmError::Service {
+            code: "NoRoute".to_string(),
+            message: "Impossible route between points".to_string(),
+            failed_coordinate_index: None,
+        }
     }
 
     /// Given a set of source and destination `Point`s or `Hint`s, determine the distances
@@ -35,25 +136,26 @@ impl OsrmEngine {
     /// distances are not the shortest distance between two coordinates, but rather the
     /// distances of the fastest routes. Durations are in seconds and distances are in meters.
     pub fn table(&self, table_request: TableRequest) -> Result<TableResponse, OsrmError> {
+        self.call_count.fetch_add(1, Ordering::Relaxed);
         let len_sources = table_request.sources.len();
         let len_destinations = table_request.destinations.len();
 
         // Just lazily create both even if we don't need them
         // because it is just the mocking function
+        let cell = |i: usize, j: usize, value: f64| {
+            let (source, destination) = (&table_request.sources[i], &table_request.destinations[j]);
+            if self.is_unroutable(source, destination) {
+                None
+            } else {
+                Some(if source == destination { 0.0 } else { value })
+            }
+        };
         let durations: Vec<Vec<Option<f64>>> = (0..len_sources)
-            .map(|i| {
-                (0..len_destinations)
-                    .map(|j| Some(if i == j { 0.0 } else { 1.0 }))
-                    .collect()
-            })
+            .map(|i| (0..len_destinations).map(|j| cell(i, j, 1.0)).collect())
             .collect();
 
         let distances: Vec<Vec<Option<f64>>> = (0..len_sources)
-            .map(|i| {
-                (0..len_destinations)
-                    .map(|j| Some(if i == j { 0.0 } else { 2.0 }))
-                    .collect()
-            })
+            .map(|i| (0..len_destinations).map(|j| cell(i, j, 2.0)).collect())
             .collect();
 
         let (durations, distances) = match table_request.annotations {
@@ -71,7 +173,7 @@ impl OsrmEngine {
                     .iter()
                     .map(|p| Waypoint {
                         hint: Some("Mock hint".to_string()),
-                        location: [p.latitude(), p.longitude()],
+                        location: [p.longitude(), p.latitude()],
                         name: "Mock name".to_string(),
                         distance: 0.0,
                     })
@@ -83,7 +185,7 @@ impl OsrmEngine {
                     .iter()
                     .map(|p| Waypoint {
                         hint: Some("Mock hint".to_string()),
-                        location: [p.latitude(), p.longitude()],
+                        location: [p.longitude(), p.latitude()],
                         name: "Mock name".to_string(),
                         distance: 0.0,
                     })
@@ -91,6 +193,8 @@ impl OsrmEngine {
             ),
             durations,
             distances,
+            // Stock backends do not return weights
+            weights: None,
             fallback_speed_cells: None,
         })
     }
@@ -104,27 +208,48 @@ impl OsrmEngine {
     ///
     /// Finds the fastest route between coordinates in the supplied order.
     pub fn route(&self, route_request: &RouteRequest) -> Result<RouteResponse, OsrmError> {
-        let routes: Vec<Route> = route_request
+        self.call_count.fetch_add(1, Ordering::Relaxed);
+        if route_request
             .points
-            .windows(2)
-            .map(|_| Route::default())
-            .collect();
+            .iter()
+            .tuple_windows()
+            .any(|(from, to)| self.is_unroutable(from, to))
+        {
+            return Err(Self::no_route());
+        }
+        // Via points do not start a new leg, nor are they returned as waypoints
+        let waypoint_points: Vec<&Point> = match route_request.waypoints {
+            Some(indices) => indices.iter().map(|&i| &route_request.points[i]).collect(),
+            None => route_request.points.iter().collect(),
+        };
+        let routes = vec![mock_route(
+            waypoint_points.len().saturating_sub(1),
+            route_request.geometry,
+            route_request.overview,
+            route_request.steps,
+            route_request.annotations,
+        )];
 
-        Ok(RouteResponse {
-            code: "Ok".to_string(),
-            routes,
-            waypoints: Some(
-                route_request
-                    .points
+        let waypoints = if route_request.skip_waypoints {
+            None
+        } else {
+            Some(
+                waypoint_points
                     .iter()
                     .map(|p| Waypoint {
                         hint: Some("Mock hint".to_string()),
-                        location: [p.latitude(), p.longitude()],
+                        location: [p.longitude(), p.latitude()],
                         name: "Mock name".to_string(),
                         distance: 0.0,
                     })
                     .collect(),
-            ),
+            )
+        };
+
+        Ok(RouteResponse {
+            code: "Ok".to_string(),
+            routes,
+            waypoints,
         })
     }
 
@@ -134,41 +259,76 @@ impl OsrmEngine {
     ///
     /// See `TripRequest` for all possible options.
     pub fn trip(&self, trip_request: TripRequest) -> Result<TripResponse, OsrmError> {
-        let trips: Vec<Route> = trip_request
+        self.call_count.fetch_add(1, Ordering::Relaxed);
+        if trip_request
             .points
-            .windows(2)
-            .map(|_| Route::default())
-            .collect();
+            .iter()
+            .tuple_combinations()
+            .any(|(a, b)| self.is_unroutable(a, b))
+        {
+            return Err(OsrmError::Service {
+                code: "NoTrips".to_string(),
+                message: "No trip visiting all destinations possible.".to_string(),
+                failed_coordinate_index: None,
+            });
+        }
+        // A single trip visiting the points in the given order
+        let len_points = trip_request.points.len();
+        let trips = vec![mock_route(
+            if trip_request.roundtrip {
+                len_points
+            } else {
+                len_points.saturating_sub(1)
+            },
+            trip_request.geometry,
+            trip_request.overview,
+            trip_request.steps,
+            trip_request.annotations,
+        )];
 
-        Ok(TripResponse {
-            code: "Ok".to_string(),
-            trips,
-            waypoints: Some(
+        let waypoints = if trip_request.skip_waypoints {
+            None
+        } else {
+            Some(
                 trip_request
                     .points
                     .iter()
                     .enumerate()
                     .map(|(i, p)| TripWaypoint {
                         hint: Some("Mock hint".to_string()),
-                        location: [p.latitude(), p.longitude()],
+                        location: [p.longitude(), p.latitude()],
                         name: "Mock name".to_string(),
                         distance: 0.0,
-                        trips_index: i,
+                        trips_index: 0,
                         waypoint_index: i,
                     })
                     .collect(),
-            ),
+            )
+        };
+
+        Ok(TripResponse {
+            code: "Ok".to_string(),
+            trips,
+            waypoints,
         })
     }
 
     /// A massively simplified interface for routing just between two points.
     ///
-    /// Calls OsrmEngine::route with default options.
-    pub fn simple_route(&self, _from: Point, _to: Point) -> Result<SimpleRouteResponse, OsrmError> {
+    /// The mock returns the crow-fly distance between the points and a duration
+    /// derived from [`assumed_speed_mps`](Self::assumed_speed_mps).
+    pub fn simple_route(&self, from: Point, to: Point) -> Result<SimpleRouteResponse, OsrmError> {
+        self.call_count.fetch_add(1, Ordering::Relaxed);
+        if self.is_unroutable(&from, &to) {
+            return Err(Self::no_route());
+        }
+        let distance = from.haversine_distance(&to);
         Ok(SimpleRouteResponse {
             code: "Ok".to_string(),
-            distance: 0.0,
-            duration: 1.0,
+            distance,
+            duration: distance / self.assumed_speed_mps,
+            start_name: Some("Mock name".to_string()),
+            end_name: Some("Mock name".to_string()),
         })
     }
 
@@ -184,16 +344,30 @@ impl OsrmEngine {
     ///
     /// Snaps a coordinate to the street network and returns the nearest n matches.
     pub fn nearest(&self, nearest_request: &NearestRequest) -> Result<NearestResponse, OsrmError> {
+        if let Some(response) = self
+            .snap_cache
+            .as_ref()
+            .and_then(|cache| cache.get(nearest_request))
+        {
+            return Ok(response);
+        }
+        self.call_count.fetch_add(1, Ordering::Relaxed);
         let point = nearest_request.point;
-        Ok(NearestResponse {
+        let response = NearestResponse {
             code: "Ok".to_string(),
-            waypoints: vec![Waypoint {
-                hint: Some("Mock hint".to_string()),
-                location: [point.latitude(), point.longitude()],
-                name: "Mock name".to_string(),
-                distance: 0.0,
-            }],
-        })
+            waypoints: (0..nearest_request.number.max(1))
+                .map(|i| Waypoint {
+                    hint: Some("Mock hint".to_string()),
+                    location: [point.longitude(), point.latitude()],
+                    name: "Mock name".to_string(),
+                    distance: self.snap_distance_m + 10.0 * i as f64,
+                })
+                .collect(),
+        };
+        if let Some(cache) = &self.snap_cache {
+            cache.insert(nearest_request, &response);
+        }
+        Ok(response)
     }
 
     /// Given an ordered set of `Point`s or `Hint`s (and optionally
@@ -211,11 +385,24 @@ impl OsrmEngine {
     /// could not be found. The algorithm might not be able to match all
     /// points. Outliers are removed if they can not be matched successfully.
     pub fn r#match(&self, match_request: &MatchRequest) -> Result<MatchResponse, OsrmError> {
-        let matchings: Vec<MatchRoute> = match_request
-            .points
-            .windows(2)
-            .map(|_| MatchRoute::default())
-            .collect();
+        self.call_count.fetch_add(1, Ordering::Relaxed);
+        // The whole trace is matched without splits
+        let route = mock_route(
+            match_request.points.len().saturating_sub(1),
+            match_request.geometry,
+            match_request.overview,
+            match_request.steps,
+            match_request.annotations,
+        );
+        let matchings = vec![MatchRoute {
+            distance: route.distance,
+            duration: route.duration,
+            geometry: route.geometry,
+            weight: route.weight,
+            weight_name: route.weight_name,
+            legs: route.legs,
+            confidence: 1.0,
+        }];
 
         Ok(MatchResponse {
             code: "Ok".to_string(),
@@ -227,7 +414,7 @@ impl OsrmEngine {
                 .map(|(i, p)| {
                     Some(MatchWaypoint {
                         hint: "Mock hint".to_string(),
-                        location: [p.latitude(), p.longitude()],
+                        location: [p.longitude(), p.latitude()],
                         name: "Mock name".to_string(),
                         distance: 0.0,
                         matchings_index: 0,
@@ -239,3 +426,107 @@ impl OsrmEngine {
         })
     }
 }
+
+/// Fabricate a route with `len_legs` legs which reflects the geometry, overview,
+/// steps and annotations options of the request, so that the presence and format
+/// of those fields match what the real engines would return.
+fn mock_route(
+    len_legs: usize,
+    geometry_type: GeometryType,
+    overview: OverviewZoom,
+    steps: bool,
+    annotations: Annotations,
+) -> Route {
+    let mock_geometry = || match geometry_type {
+        GeometryType::GeoJSON => Geometry::GeoJson(GeoJsonLineString::default()),
+        GeometryType::Polyline => Geometry::Polyline("{lu_IypwpAVrAvAdI".to_string()),
+        // The same vertices at precision 6
+        GeometryType::Polyline6 => Geometry::Polyline("wi_fcBcqtpXnFfYnZzdB".to_string()),
+    };
+
+    let legs: Vec<RouteLeg> = std::iter::repeat_with(RouteLeg::default)
+        .take(len_legs)
+        .map(|leg| RouteLeg {
+            steps: if steps {
+                vec![
+                    RouteStep {
+                        geometry: mock_geometry(),
+                        ..RouteStep::default()
+                    },
+                    RouteStep {
+                        geometry: mock_geometry(),
+                        ..RouteStep::default()
+                    },
+                ]
+            } else {
+                vec![]
+            },
+            annotation: annotations.any().then(|| mock_annotation(annotations)),
+            ..leg
+        })
+        .collect();
+
+    Route {
+        geometry: match overview {
+            OverviewZoom::False => None,
+            OverviewZoom::Simplified | OverviewZoom::Full => Some(mock_geometry()),
+        },
+        distance: legs.iter().map(|leg| leg.distance).sum(),
+        duration: legs.iter().map(|leg| leg.duration).sum(),
+        weight: legs.iter().map(|leg| leg.weight).sum(),
+        legs,
+        ..Route::default()
+    }
+}
+
+/// Fabricate an annotation with only the requested fields populated, as
+/// returned by the real engines.
+fn mock_annotation(annotations: Annotations) -> Annotation {
+    fn requested<T>(requested: bool, values: Vec<T>) -> Vec<T> {
+        if requested { values } else { Vec::new() }
+    }
+
+    let annotation = Annotation::default();
+    // Rounded to one decimal place, as by OSRM
+    let speed = annotation
+        .distance
+        .iter()
+        .zip(&annotation.duration)
+        .map(|(distance, duration)| (10.0 * distance / duration).round() / 10.0)
+        .collect();
+    Annotation {
+        duration: requested(annotations.duration, annotation.duration),
+        nodes: requested(annotations.nodes, annotation.nodes),
+        distance: requested(annotations.distance, annotation.distance),
+        weight: requested(annotations.weight, annotation.weight),
+        datasources: requested(annotations.datasources, annotation.datasources),
+        speed: requested(annotations.speed, speed),
+        metadata: annotation.metadata,
+    }
+}
+
+impl OsrmService for OsrmEngine {
+    fn table(&self, table_request: &TableRequest) -> Result<TableResponse, OsrmError> {
+        OsrmEngine::table(self, table_request.clone())
+    }
+
+    fn route(&self, route_request: &RouteRequest) -> Result<RouteResponse, OsrmError> {
+        OsrmEngine::route(self, route_request)
+    }
+
+    fn trip(&self, trip_request: &TripRequest) -> Result<TripResponse, OsrmError> {
+        OsrmEngine::trip(self, trip_request.clone())
+    }
+
+    fn simple_route(&self, from: Point, to: Point) -> Result<SimpleRouteResponse, OsrmError> {
+        OsrmEngine::simple_route(self, from, to)
+    }
+
+    fn nearest(&self, nearest_request: &NearestRequest) -> Result<NearestResponse, OsrmError> {
+        OsrmEngine::nearest(self, nearest_request)
+    }
+
+    fn r#match(&self, match_request: &MatchRequest) -> Result<MatchResponse, OsrmError> {
+        OsrmEngine::r#match(self, match_request)
+    }
+}
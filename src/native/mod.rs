@@ -32,6 +32,14 @@
 //! [osrm documentation](<https://github.com/Project-OSRM/osrm-backend>) for more
 //! information about extracting the map data. The extraction process defines the
 //! Algorithm that should be passed to [`OsrmEngine::new()`].
+//!
+//! ## Response parsing
+//!
+//! The backend returns responses as JSON, which are parsed with `serde_json`.
+//! Nesting where the response types do not expect it is rejected with a
+//! [`JsonParse`](crate::errors::NativeOsrmError::JsonParse) error at the depth
+//! it occurs, and unknown fields are skipped without recursing, so malformed
+//! responses cannot overflow the stack.
 
 mod osrm_engine;
 use crate::r#match::{MatchGapsBehaviour, MatchRequest};
@@ -48,21 +56,17 @@ use std::f64;
 use std::ffi::{CStr, CString, c_void};
 use std::os::raw::c_char;
 
-const ROUTE_ALTERNATIVES: u8 = 1 << 0;
 const ROUTE_STEPS: u8 = 1 << 1;
-const ROUTE_ANNOTATIONS: u8 = 1 << 2;
 const ROUTE_CONTINUE_STRAIGHT: u8 = 1 << 3;
 const ROUTE_GENERATE_HINTS: u8 = 1 << 4;
 const ROUTE_SKIP_WAYPOINTS: u8 = 1 << 5;
 
 const MATCH_TIDY: u8 = 1 << 0;
 const MATCH_STEPS: u8 = 1 << 1;
-const MATCH_ANNOTATIONS: u8 = 1 << 2;
 const MATCH_GENERATE_HINTS: u8 = 1 << 3;
 const MATCH_SKIP_WAYPOINTS: u8 = 1 << 4;
 
 const TRIP_STEPS: u8 = 1 << 0;
-const TRIP_ANNOTATIONS: u8 = 1 << 1;
 const TRIP_GENERATE_HINTS: u8 = 1 << 2;
 const TRIP_SKIP_WAYPOINTS: u8 = 1 << 3;
 const TRIP_ROUNDTRIP: u8 = 1 << 4;
@@ -129,6 +133,7 @@ unsafe extern "C" {
         source: TripSource,
         destination: TripDestination,
         flags: u8,
+        annotations: u8,
         bearings: *const Bearing,
         num_bearings: usize,
         radiuses: *const f64,
@@ -149,6 +154,10 @@ unsafe extern "C" {
         geometry_type: GeometryType,
         overview_zoom: OverviewZoom,
         flags: u8,
+        annotations: u8,
+        number_of_alternatives: u8,
+        waypoints: *const usize,
+        num_waypoints: usize,
         bearings: *const Bearing,
         num_bearings: usize,
         radiuses: *const f64,
@@ -174,6 +183,7 @@ unsafe extern "C" {
         waypoints: *const usize,
         num_waypoints: usize,
         flags: u8,
+        annotations: u8,
         bearings: *const Bearing,
         num_bearings: usize,
         radiuses: *const f64,
@@ -206,7 +216,10 @@ unsafe extern "C" {
 
 /// Implements [`Debug`] if the `feature="debug"` feature flag
 /// is set.
-#[derive(Clone, Debug)]
+///
+/// Owns the C++ instance, which is destroyed on drop, so must not be cloned.
+/// Share it behind an `Arc` instead.
+#[derive(Debug)]
 pub(crate) struct Osrm {
     instance: *mut c_void,
 }
@@ -251,7 +264,7 @@ impl Osrm {
             Some(rad) => rad.iter().map(|f| f.unwrap_or(f64::INFINITY)).collect(),
             None => vec![f64::INFINITY; num_coords],
         };
-        let hints = match trip_request.hints {
+        let hints = match &trip_request.hints {
             Some(hints) => hints.iter().map(|hint| hint.unwrap_or("").into()).collect(),
             None => Vec::new(),
         };
@@ -272,9 +285,6 @@ impl Osrm {
         if trip_request.steps {
             flags |= TRIP_STEPS;
         }
-        if trip_request.annotations {
-            flags |= TRIP_ANNOTATIONS;
-        }
         if trip_request.generate_hints {
             flags |= TRIP_GENERATE_HINTS;
         }
@@ -295,6 +305,7 @@ impl Osrm {
                 trip_request.source,
                 trip_request.destination,
                 flags,
+                trip_request.annotations.bits(),
                 bearings.as_ptr(),
                 bearings.len(),
                 radiuses.as_ptr(),
@@ -348,7 +359,7 @@ impl Osrm {
             Some(rad) => rad.iter().map(|f| f.unwrap_or(f64::INFINITY)).collect(),
             None => vec![f64::INFINITY; num_coords],
         };
-        let hints = match route_request.hints {
+        let hints = match &route_request.hints {
             Some(hints) => hints.iter().map(|hint| hint.unwrap_or("").into()).collect(),
             None => Vec::new(),
         };
@@ -366,15 +377,9 @@ impl Osrm {
         };
         let snapping = route_request.snapping.unwrap_or(Snapping::Default);
         let mut flags: u8 = 0;
-        if route_request.alternatives {
-            flags |= ROUTE_ALTERNATIVES;
-        }
         if route_request.steps {
             flags |= ROUTE_STEPS;
         }
-        if route_request.annotations {
-            flags |= ROUTE_ANNOTATIONS;
-        }
         if route_request.continue_straight {
             flags |= ROUTE_CONTINUE_STRAIGHT;
         }
@@ -384,6 +389,7 @@ impl Osrm {
         if route_request.skip_waypoints {
             flags |= ROUTE_SKIP_WAYPOINTS;
         }
+        let waypoints = route_request.waypoints.unwrap_or(&[]);
         let result = unsafe {
             osrm_route(
                 self.instance,
@@ -392,6 +398,10 @@ impl Osrm {
                 route_request.geometry,
                 route_request.overview,
                 flags,
+                route_request.annotations.bits(),
+                route_request.alternatives,
+                waypoints.as_ptr(),
+                waypoints.len(),
                 bearings.as_ptr(),
                 bearings.len(),
                 radiuses.as_ptr(),
@@ -440,9 +450,6 @@ impl Osrm {
         if match_request.steps {
             flags |= MATCH_STEPS;
         }
-        if match_request.annotations {
-            flags |= MATCH_ANNOTATIONS;
-        }
         if match_request.generate_hints {
             flags |= MATCH_GENERATE_HINTS
         }
@@ -466,7 +473,7 @@ impl Osrm {
             Some(rad) => rad.iter().map(|f| f.unwrap_or(f64::INFINITY)).collect(),
             None => vec![f64::INFINITY; num_coords],
         };
-        let hints = match match_request.hints {
+        let hints = match &match_request.hints {
             Some(hints) => hints.iter().map(|hint| hint.unwrap_or("").into()).collect(),
             None => Vec::new(),
         };
@@ -496,6 +503,7 @@ impl Osrm {
                 waypoints.as_ptr(),
                 waypoints.len(),
                 flags,
+                match_request.annotations.bits(),
                 bearings.as_ptr(),
                 bearings.len(),
                 radiuses.as_ptr(),
@@ -708,6 +716,9 @@ impl Osrm {
                 .collect(),
             None => Vec::new(),
         };
+        // Always pass a valid pointer so that an unset snapping behaves
+        // identically to Snapping::Default, as it does for the other services
+        let snapping = nearest_request.snapping.unwrap_or(Snapping::Default);
 
         let result = unsafe {
             osrm_nearest(
@@ -729,10 +740,7 @@ impl Osrm {
                     .map_or(std::ptr::null(), |a| a as *const _),
                 excludes.as_ptr(),
                 excludes.len(),
-                nearest_request
-                    .snapping
-                    .as_ref()
-                    .map_or(std::ptr::null(), |s| s as *const _),
+                &snapping as *const _,
             )
         };
 
@@ -764,5 +772,8 @@ impl Drop for Osrm {
     }
 }
 
+// SAFETY: The instance is only destroyed on drop, and `osrm::OSRM` supports
+// concurrent queries through a const reference, as in `osrm-routed`. Errors
+// are reported through a thread local.
 unsafe impl Send for Osrm {}
 unsafe impl Sync for Osrm {}
@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use crate::Algorithm;
 use crate::Point;
 use crate::errors::{NativeOsrmError, OsrmError};
@@ -5,15 +7,20 @@ use crate::r#match::{MatchRequest, MatchResponse};
 use crate::native::Osrm;
 use crate::nearest::{NearestRequest, NearestResponse};
 use crate::route::{RouteRequest, RouteRequestBuilder, RouteResponse, SimpleRouteResponse};
+use crate::services::OsrmService;
+use crate::snap_cache::SnapCache;
 use crate::table::{TableRequest, TableResponse};
 use crate::trip::{TripRequest, TripResponse};
 
 /// The engine for calling into osrm-backend natively.
-
+///
+/// The loaded map data is shared between all clones of the engine, and is
+/// released once the last clone is dropped.
 #[derive(Clone, Debug)]
 #[cfg_attr(docsrs, doc(cfg(feature = "native")))]
 pub struct OsrmEngine {
-    instance: Osrm,
+    instance: Arc<Osrm>,
+    snap_cache: Option<Arc<SnapCache>>,
 }
 
 impl OsrmEngine {
@@ -30,7 +37,23 @@ impl OsrmEngine {
     pub fn new(base_map_path: &str, algorithm: Algorithm) -> Result<Self, OsrmError> {
         let osrm = Osrm::new(base_map_path, algorithm.as_str())
             .map_err(|e| OsrmError::Native(NativeOsrmError::Initialization(e)))?;
-        Ok(OsrmEngine { instance: osrm })
+        Ok(OsrmEngine {
+            instance: Arc::new(osrm),
+            snap_cache: None,
+        })
+    }
+
+    /// Cache the results of [`nearest`](Self::nearest) requests for a single
+    /// waypoint, keeping the `capacity` most recently used. The cache is shared
+    /// between all clones of the engine.
+    ///
+    /// Requests are keyed by their coordinates rounded to 6 decimal places, and
+    /// requests setting any option other than `number=1` are not cached. Cached
+    /// responses are never invalidated, so hints in them become stale if the
+    /// engine is pointed at updated map data.
+    pub fn snap_cache(mut self, capacity: usize) -> Self {
+        self.snap_cache = Some(Arc::new(SnapCache::new(capacity)));
+        self
     }
 
     /// Given a set of source and destination `Point`s or `Hint`s, determine the distances
@@ -45,12 +68,8 @@ impl OsrmEngine {
     /// distances are not the shortest distance between two coordinates, but rather the
     /// distances of the fastest routes. Durations are in seconds and distances are in meters.
     pub fn table(&self, table_request: &TableRequest) -> Result<TableResponse, OsrmError> {
-        let result = self
-            .instance
-            .table(table_request)
-            .map_err(|e| OsrmError::Native(NativeOsrmError::FfiError(e)))?;
-        serde_json::from_str::<TableResponse>(&result)
-            .map_err(|e| OsrmError::Native(NativeOsrmError::JsonParse(Box::new(e))))
+        let result = self.table_raw(table_request)?;
+        TableResponse::from_json(&result).map_err(native_parse_error)
     }
 
     /// Given an ordered set of `Point`s or `Hint`s, route through those points in the
@@ -62,12 +81,8 @@ impl OsrmEngine {
     ///
     /// Finds the fastest route between coordinates in the supplied order.
     pub fn route(&self, route_request: &RouteRequest) -> Result<RouteResponse, OsrmError> {
-        let result = self
-            .instance
-            .route(route_request)
-            .map_err(|e| OsrmError::Native(NativeOsrmError::FfiError(e)))?;
-        serde_json::from_str::<RouteResponse>(&result)
-            .map_err(|e| OsrmError::Native(NativeOsrmError::JsonParse(Box::new(e))))
+        let result = self.route_raw(route_request)?;
+        RouteResponse::from_json(&result).map_err(native_parse_error)
     }
 
     /// Given an _unordered_ set of `Point`s or `Hint`s, uses a greedy heuristic to
@@ -85,12 +100,8 @@ impl OsrmEngine {
     /// approximation. Note that all input coordinates have to be connected
     /// for the trip service to work.
     pub fn trip(&self, trip_request: &TripRequest) -> Result<TripResponse, OsrmError> {
-        let result = self
-            .instance
-            .trip(trip_request)
-            .map_err(|e| OsrmError::Native(NativeOsrmError::FfiError(e)))?;
-        serde_json::from_str::<TripResponse>(&result)
-            .map_err(|e| OsrmError::Native(NativeOsrmError::JsonParse(Box::new(e))))
+        let result = self.trip_raw(trip_request)?;
+        TripResponse::from_json(&result).map_err(native_parse_error)
     }
 
     /// A massively simplified interface for routing just between two points.
@@ -102,12 +113,8 @@ impl OsrmEngine {
             .build()
             .expect("Route request for simple route is empty");
 
-        let result = self
-            .instance
-            .route(&request)
-            .map_err(|e| OsrmError::Native(NativeOsrmError::FfiError(e)))?;
-        let route_response = serde_json::from_str::<RouteResponse>(&result)
-            .map_err(|e| OsrmError::Native(NativeOsrmError::JsonParse(Box::new(e))))?;
+        let result = self.route_raw(&request)?;
+        let route_response = RouteResponse::from_json(&result).map_err(native_parse_error)?;
         if route_response.routes.is_empty() {
             return Err(OsrmError::EmptyResponse(
                 "No route was returned between those 2 points".to_owned(),
@@ -131,6 +138,16 @@ impl OsrmEngine {
                 .iter()
                 .map(|l| l.duration)
                 .sum(),
+            start_name: route_response
+                .waypoints
+                .as_ref()
+                .and_then(|w| w.first())
+                .map(|w| w.name.clone()),
+            end_name: route_response
+                .waypoints
+                .as_ref()
+                .and_then(|w| w.last())
+                .map(|w| w.name.clone()),
         })
     }
 
@@ -146,12 +163,18 @@ impl OsrmEngine {
     ///
     /// Snaps a coordinate to the street network and returns the nearest n matches.
     pub fn nearest(&self, nearest_request: &NearestRequest) -> Result<NearestResponse, OsrmError> {
-        let result = self
-            .instance
-            .nearest(nearest_request)
-            .map_err(|e| OsrmError::Native(NativeOsrmError::FfiError(e)))?;
-        let nearest_response = serde_json::from_str::<NearestResponse>(&result)
-            .map_err(|e| OsrmError::Native(NativeOsrmError::JsonParse(Box::new(e))))?;
+        if let Some(response) = self
+            .snap_cache
+            .as_ref()
+            .and_then(|cache| cache.get(nearest_request))
+        {
+            return Ok(response);
+        }
+        let result = self.nearest_raw(nearest_request)?;
+        let nearest_response = NearestResponse::from_json(&result).map_err(native_parse_error)?;
+        if let Some(cache) = &self.snap_cache {
+            cache.insert(nearest_request, &nearest_response);
+        }
         Ok(nearest_response)
     }
 
@@ -170,13 +193,89 @@ impl OsrmEngine {
     /// could not be found. The algorithm might not be able to match all
     /// points. Outliers are removed if they can not be matched successfully.
     pub fn r#match(&self, match_request: &MatchRequest) -> Result<MatchResponse, OsrmError> {
-        let result = self
-            .instance
-            .r#match(match_request)
-            .map_err(|e| OsrmError::Native(NativeOsrmError::FfiError(e)))?;
-        let response = serde_json::from_str::<MatchResponse>(&result)
-            .map_err(|e| OsrmError::Native(NativeOsrmError::JsonParse(Box::new(e))))?;
+        let result = self.match_raw(match_request)?;
+        let response = MatchResponse::from_json(&result).map_err(native_parse_error)?;
 
         Ok(response)
     }
+
+    /// The unparsed JSON response to [`table`](Self::table). See
+    /// [`route_raw`](Self::route_raw).
+    pub fn table_raw(&self, table_request: &TableRequest) -> Result<String, OsrmError> {
+        self.instance.table(table_request).map_err(ffi_error)
+    }
+
+    /// The unparsed JSON response to [`route`](Self::route).
+    ///
+    /// Useful for debugging responses which fail to parse, eg. from a custom
+    /// build of osrm-backend, or for reading fields which are not modelled by
+    /// the response types.
+    pub fn route_raw(&self, route_request: &RouteRequest) -> Result<String, OsrmError> {
+        self.instance.route(route_request).map_err(ffi_error)
+    }
+
+    /// The unparsed JSON response to [`trip`](Self::trip). See
+    /// [`route_raw`](Self::route_raw).
+    pub fn trip_raw(&self, trip_request: &TripRequest) -> Result<String, OsrmError> {
+        self.instance.trip(trip_request).map_err(ffi_error)
+    }
+
+    /// The unparsed JSON response to [`nearest`](Self::nearest). See
+    /// [`route_raw`](Self::route_raw). The snap cache is not used.
+    pub fn nearest_raw(&self, nearest_request: &NearestRequest) -> Result<String, OsrmError> {
+        self.instance.nearest(nearest_request).map_err(ffi_error)
+    }
+
+    /// The unparsed JSON response to [`r#match`](Self::r#match). See
+    /// [`route_raw`](Self::route_raw).
+    pub fn match_raw(&self, match_request: &MatchRequest) -> Result<String, OsrmError> {
+        self.instance.r#match(match_request).map_err(ffi_error)
+    }
+}
+
+impl OsrmService for OsrmEngine {
+    fn table(&self, table_request: &TableRequest) -> Result<TableResponse, OsrmError> {
+        OsrmEngine::table(self, table_request)
+    }
+
+    fn route(&self, route_request: &RouteRequest) -> Result<RouteResponse, OsrmError> {
+        OsrmEngine::route(self, route_request)
+    }
+
+    fn trip(&self, trip_request: &TripRequest) -> Result<TripResponse, OsrmError> {
+        OsrmEngine::trip(self, trip_request)
+    }
+
+    fn simple_route(&self, from: Point, to: Point) -> Result<SimpleRouteResponse, OsrmError> {
+        OsrmEngine::simple_route(self, from, to)
+    }
+
+    fn nearest(&self, nearest_request: &NearestRequest) -> Result<NearestResponse, OsrmError> {
+        OsrmEngine::nearest(self, nearest_request)
+    }
+
+    fn r#match(&self, match_request: &MatchRequest) -> Result<MatchResponse, OsrmError> {
+        OsrmEngine::r#match(self, match_request)
+    }
+}
+
+/// Report a response which failed to parse as [`NativeOsrmError::JsonParse`],
+/// as the `from_json` constructors of the responses report
+/// [`OsrmError::JsonParse`]. Responses with a code other than `"Ok"` are
+/// left as [`OsrmError::Service`].
+fn native_parse_error(error: OsrmError) -> OsrmError {
+    match error {
+        OsrmError::JsonParse(e) => OsrmError::Native(NativeOsrmError::JsonParse(e)),
+        error => error,
+    }
+}
+
+/// The error for a failed call into OSRM. Errors from OSRM itself, eg.
+/// `NoSegment`, carry the error response, which is reported as
+/// [`OsrmError::Service`].
+fn ffi_error(error: String) -> OsrmError {
+    let service_error = error
+        .strip_prefix("OSRM error: ")
+        .and_then(crate::serde_ops::error_response);
+    service_error.unwrap_or(OsrmError::Native(NativeOsrmError::FfiError(error)))
 }
@@ -3,6 +3,16 @@
 //! Documentation is pulled directly from the osrm-backend documentation in
 //! v6.0.0 where it exists.
 
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+use itertools::Itertools;
+use thiserror::Error;
+
+use crate::polyline::PolylineDecodeError;
+use crate::request_types::GeometryType;
+use crate::{BoundingBox, Point};
+
 /// Represents a route through (potentially multiple) waypoints.
 ///
 /// Route weight information is defined in the map extraction process.
@@ -46,13 +56,323 @@ impl Default for Route {
     }
 }
 
+/// Legs with both a distance (meters) and duration (seconds) within this
+/// tolerance of zero are considered empty. See [`Route::non_empty_legs`].
+pub const EMPTY_LEG_EPSILON: f64 = 1e-6;
+
+impl Route {
+    /// The bounding box of the route geometry. See [`Geometry::bounding_box`].
+    ///
+    /// Returns `None` if the route has no geometry, ie. `overview=false`.
+    pub fn bounding_box(&self, geometry_type: GeometryType) -> Option<BoundingBox> {
+        self.geometry.as_ref()?.bounding_box(geometry_type)
+    }
+
+    /// The name of the weight profile used during the extraction phase.
+    ///
+    /// eg. `"duration"`, `"routability"` or the name of a custom cost.
+    pub fn weight_profile(&self) -> &str {
+        &self.weight_name
+    }
+
+    /// The calculated weight of the route. Equal to the sum of the
+    /// weights of the legs.
+    pub fn total_weight(&self) -> f64 {
+        self.weight
+    }
+
+    /// The distance of the route in meters, summed over the legs. Matches
+    /// `distance` for routes returned by OSRM, up to rounding.
+    ///
+    /// Returns `0.0` when the route has no legs.
+    pub fn total_distance(&self) -> f64 {
+        self.legs.iter().map(|leg| leg.distance).sum()
+    }
+
+    /// The duration of the route in seconds, summed over the legs. Matches
+    /// `duration` for routes returned by OSRM, up to rounding.
+    ///
+    /// Returns `0.0` when the route has no legs.
+    pub fn total_duration(&self) -> f64 {
+        self.legs.iter().map(|leg| leg.duration).sum()
+    }
+
+    /// The legs of the route, skipping any with zero length. These result from
+    /// routing through duplicate or near-duplicate waypoints.
+    ///
+    /// A leg is empty when both `distance` and `duration` are within
+    /// [`EMPTY_LEG_EPSILON`] of zero.
+    pub fn non_empty_legs(&self) -> impl Iterator<Item = &RouteLeg> {
+        self.legs.iter().filter(|leg| !leg.is_empty())
+    }
+
+    /// The arrival time at each waypoint, as UNIX timestamps in seconds, when
+    /// departing the first waypoint at `departure`.
+    ///
+    /// The first entry is `departure` and each subsequent entry adds the
+    /// duration of the corresponding leg. Durations are accumulated before
+    /// rounding to the nearest second, so the final arrival is `departure`
+    /// plus the total leg duration.
+    pub fn arrival_times(&self, departure: u64) -> Vec<u64> {
+        let mut elapsed = 0.0;
+        std::iter::once(departure)
+            .chain(self.legs.iter().map(|leg| {
+                elapsed += leg.duration;
+                departure + elapsed.round() as u64
+            }))
+            .collect()
+    }
+
+    /// Whether the route was weighted by travel time, in which case
+    /// `weight` is equivalent to `duration`.
+    pub fn is_time_based(&self) -> bool {
+        self.weight_name == "duration"
+    }
+
+    /// Every intersection passed along the route, in order, across all legs
+    /// and steps.
+    ///
+    /// Intersections are only returned as part of the steps, so this is empty
+    /// unless the route was requested with `steps=true`.
+    pub fn intersections(&self) -> impl Iterator<Item = &Intersection> {
+        self.legs
+            .iter()
+            .flat_map(|leg| leg.steps.iter())
+            .flat_map(|step| step.intersections.iter())
+    }
+
+    /// Discard the intersections of every step, keeping the maneuvers, names
+    /// and geometries, eg. to reduce the memory used by routes only needed
+    /// as a list of turns.
+    ///
+    /// Intersections are most of the size of the steps. Their memory is
+    /// released, and the data is lost, so helpers reading the intersections,
+    /// eg. [`class_coverage`](Self::class_coverage), find nothing afterwards.
+    pub fn strip_intersections(&mut self) {
+        for step in self.legs.iter_mut().flat_map(|leg| leg.steps.iter_mut()) {
+            step.intersections = Vec::new();
+        }
+    }
+
+    /// The distance, in meters, travelled on roads of each class,
+    /// eg. `"toll"`, `"motorway"` or `"restricted"`.
+    ///
+    /// The distance of each step is attributed to every class of the
+    /// intersection belonging to its maneuver. Distance on roads without
+    /// a class is not included.
+    ///
+    /// Requires the route to have been requested with `steps=true`,
+    /// otherwise the map is empty.
+    pub fn class_coverage(&self) -> HashMap<String, f64> {
+        let mut coverage = HashMap::new();
+        for step in self.legs.iter().flat_map(|leg| leg.steps.iter()) {
+            let classes = step
+                .intersections
+                .first()
+                .and_then(|intersection| intersection.classes.as_ref());
+            for class in classes.into_iter().flatten() {
+                *coverage.entry(class.clone()).or_insert(0.0) += step.distance;
+            }
+        }
+        coverage
+    }
+
+    /// The distance, in meters, travelled on roads of the given class, eg.
+    /// how much of the route is on toll roads with `"toll"`.
+    ///
+    /// Attributed as in [`class_coverage`](Self::class_coverage), so the
+    /// route need not have been requested with the class excluded or even
+    /// annotated. Requires the route to have been requested with
+    /// `steps=true`, otherwise the distance is zero.
+    pub fn distance_on_class(&self, class: &str) -> f64 {
+        self.legs
+            .iter()
+            .flat_map(|leg| leg.steps.iter())
+            .filter(|step| {
+                step.intersections
+                    .first()
+                    .and_then(|intersection| intersection.classes.as_ref())
+                    .is_some_and(|classes| classes.iter().any(|c| c == class))
+            })
+            .map(|step| step.distance)
+            .sum()
+    }
+
+    /// The number of turn maneuvers along the route.
+    ///
+    /// Every step is a maneuver. Steps whose maneuver type is one of
+    /// [`NON_TURN_MANEUVERS`] (`depart`, `arrive`, `continue` and `new name`)
+    /// are not counted, all other types, eg. `turn`, `fork` or `roundabout`,
+    /// are.
+    ///
+    /// Requires the route to have been requested with `steps=true`,
+    /// otherwise the count is zero.
+    pub fn turn_count(&self) -> usize {
+        self.legs
+            .iter()
+            .flat_map(|leg| leg.steps.iter())
+            .filter(|step| !NON_TURN_MANEUVERS.contains(&step.maneuver.r#type.as_str()))
+            .count()
+    }
+
+    /// The number of steps of each maneuver type, eg. `"turn"` or `"merge"`.
+    ///
+    /// Requires the route to have been requested with `steps=true`,
+    /// otherwise the map is empty.
+    pub fn maneuver_counts(&self) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for step in self.legs.iter().flat_map(|leg| leg.steps.iter()) {
+            *counts.entry(step.maneuver.r#type.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// The lanes to be in for each maneuver with lane data, in route order,
+    /// eg. for a lane guidance display ahead of the upcoming turn.
+    ///
+    /// Lanes are read from the first intersection of each step, the one at
+    /// its maneuver. Steps without lane data there are skipped, as are lanes
+    /// at intersections passed without a maneuver.
+    ///
+    /// Requires the route to have been requested with `steps=true`,
+    /// otherwise no guidance is found.
+    pub fn lane_guidance(&self) -> Vec<LaneGuidance> {
+        self.legs
+            .iter()
+            .enumerate()
+            .flat_map(|(leg_index, leg)| {
+                leg.steps
+                    .iter()
+                    .enumerate()
+                    .map(move |(step_index, step)| (leg_index, step_index, step))
+            })
+            .filter_map(|(leg_index, step_index, step)| {
+                let lanes = step.intersections.first()?.lanes.as_ref()?;
+                if lanes.is_empty() {
+                    return None;
+                }
+                Some(LaneGuidance {
+                    leg_index,
+                    step_index,
+                    valid_lanes: lanes
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, lane)| lane.valid)
+                        .map(|(i, _)| i)
+                        .collect(),
+                    lanes: lanes.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// The indices of the legs whose [average speed](RouteLeg::average_speed)
+    /// is outside `min_mps..=max_mps`, eg. to flag bad map data or traffic
+    /// artifacts.
+    ///
+    /// Legs without an average speed, ie. with zero duration, are only flagged
+    /// if they have a distance, as covering a distance in no time is
+    /// implausible. [Empty](RouteLeg::is_empty) legs are not flagged.
+    pub fn anomalous_legs(&self, min_mps: f64, max_mps: f64) -> Vec<usize> {
+        self.legs
+            .iter()
+            .enumerate()
+            .filter(|(_, leg)| match leg.average_speed() {
+                Some(speed) => !(min_mps..=max_mps).contains(&speed),
+                None => !leg.is_empty(),
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// The approximate locations where the driving side changes between
+    /// consecutive steps, eg. crossing between left and right hand traffic
+    /// countries.
+    ///
+    /// This is a heuristic, a cheap proxy for some border crossings. Borders
+    /// between countries driving on the same side are not detected, and the
+    /// location is that of the first maneuver on the new side rather than the
+    /// border itself.
+    ///
+    /// Requires the route to have been requested with `steps=true`,
+    /// otherwise no changes are found.
+    pub fn driving_side_changes(&self) -> Vec<Point> {
+        self.legs
+            .iter()
+            .flat_map(|leg| leg.steps.iter())
+            .tuple_windows()
+            .filter(|(previous, step)| previous.driving_side != step.driving_side)
+            .map(|(_, step)| step.maneuver.as_point())
+            .collect()
+    }
+
+    /// The interpolated position `elapsed_secs` seconds after departing, eg.
+    /// to animate a vehicle moving along the route.
+    ///
+    /// With steps, the step containing `elapsed_secs` is found by accumulating
+    /// step durations, and the position is interpolated along the geometry of
+    /// that step, assuming a constant speed within the step. Without steps, the
+    /// overview geometry is used, assuming a constant speed along the whole
+    /// route, so request `steps=true` for accurate positions.
+    ///
+    /// Pass the `geometry_type` the request was built with, as for
+    /// [`Geometry::decode`].
+    ///
+    /// Returns `None` if `elapsed_secs` is negative or exceeds the duration of
+    /// the route, if there are neither steps nor an overview geometry, or if
+    /// the geometry cannot be decoded with `geometry_type`.
+    pub fn position_at_time(
+        &self,
+        elapsed_secs: f64,
+        geometry_type: GeometryType,
+    ) -> Option<Point> {
+        if !(0.0..=self.duration).contains(&elapsed_secs) {
+            return None;
+        }
+        let mut steps = self.legs.iter().flat_map(|leg| leg.steps.iter()).peekable();
+        if steps.peek().is_none() {
+            let fraction = if self.duration > 0.0 {
+                elapsed_secs / self.duration
+            } else {
+                0.0
+            };
+            return self
+                .geometry
+                .as_ref()?
+                .point_at_fraction(fraction, geometry_type);
+        }
+
+        let mut start = 0.0;
+        let mut last = None;
+        for step in steps {
+            if elapsed_secs <= start + step.duration && step.duration > 0.0 {
+                return step
+                    .geometry
+                    .point_at_fraction((elapsed_secs - start) / step.duration, geometry_type);
+            }
+            start += step.duration;
+            last = Some(step);
+        }
+        // Rounding of the step durations, or only zero duration steps
+        last?.geometry.point_at_fraction(1.0, geometry_type)
+    }
+}
+
+/// Maneuver types which do not count as a turn. See [`Route::turn_count`].
+pub const NON_TURN_MANEUVERS: [&str; 4] = ["depart", "arrive", "continue", "new name"];
+
 /// Represents the geometry of a route or route step, either as a compact
 /// polyline string or as a structured GeoJSON LineString.
 ///
+/// Every geometry in a response, the overview of each route as well as the
+/// geometry of each step, is encoded as set by the `geometries` option of the
+/// request. So all polylines of a response share its precision, see
+/// [`decode`](Self::decode).
+///
 /// Implements [`serde::Deserialize`] and
 /// [`serde::Serialize`] if `feature="serde"` is set.
 #[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(untagged))]
 pub enum Geometry {
     /// Encoded polyline string (precision 5 or 6 depending on request)
     Polyline(String),
@@ -60,6 +380,115 @@ pub enum Geometry {
     GeoJson(GeoJsonLineString),
 }
 
+impl Geometry {
+    /// The bounding box of the vertices of the geometry.
+    ///
+    /// Pass the `geometry_type` the request was built with, as for
+    /// [`decode`](Self::decode).
+    ///
+    /// Returns `None` if the geometry has no vertices or cannot be decoded
+    /// with `geometry_type`.
+    pub fn bounding_box(&self, geometry_type: GeometryType) -> Option<BoundingBox> {
+        BoundingBox::from_points(self.decode(geometry_type).ok()?)
+    }
+
+    /// The point `fraction` of the way along the geometry, by haversine
+    /// distance, interpolating linearly between vertices.
+    ///
+    /// Returns `None` if the geometry has no vertices or cannot be decoded
+    /// with `geometry_type`.
+    pub(crate) fn point_at_fraction(
+        &self,
+        fraction: f64,
+        geometry_type: GeometryType,
+    ) -> Option<Point> {
+        let vertices = self.decode(geometry_type).ok()?;
+        let lengths: Vec<f64> = vertices
+            .windows(2)
+            .map(|pair| pair[0].haversine_distance(&pair[1]))
+            .collect();
+        let mut remaining = fraction.clamp(0.0, 1.0) * lengths.iter().sum::<f64>();
+        for (pair, length) in vertices.windows(2).zip(lengths) {
+            if remaining <= length && length > 0.0 {
+                let t = remaining / length;
+                let (from, to) = (pair[0], pair[1]);
+                return Some(Point::new_unchecked(
+                    from.latitude() + t * (to.latitude() - from.latitude()),
+                    from.longitude() + t * (to.longitude() - from.longitude()),
+                ));
+            }
+            remaining -= length;
+        }
+        // Degenerate geometry, or the end after rounding
+        vertices.last().copied()
+    }
+
+    /// The vertices of the geometry, regardless of its encoding.
+    ///
+    /// Responses do not record the precision of polylines, so pass the
+    /// `geometry_type` the request was built with. It is ignored for GeoJSON
+    /// geometries, whose coordinates are returned directly.
+    ///
+    /// ```
+    /// use osrm_interface::{osrm_response_types::Geometry, request_types::GeometryType};
+    ///
+    /// let geometry = Geometry::Polyline("_p~iF~ps|U_ulLnnqC".to_string());
+    /// let points = geometry
+    ///     .decode(GeometryType::Polyline)
+    ///     .expect("Invalid polyline");
+    /// assert_eq!(points[0].as_tuple(), (38.5, -120.2));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GeometryDecodeError::UnknownPrecision`] for a polyline with
+    /// [`GeometryType::GeoJSON`], or [`GeometryDecodeError::Polyline`] if the
+    /// polyline is malformed.
+    pub fn decode(&self, geometry_type: GeometryType) -> Result<Vec<Point>, GeometryDecodeError> {
+        match self {
+            Self::Polyline(_) => self.decode_with(
+                geometry_type
+                    .polyline_precision()
+                    .ok_or(GeometryDecodeError::UnknownPrecision)?,
+            ),
+            Self::GeoJson(_) => self.decode_with(0),
+        }
+    }
+
+    /// The vertices of the geometry, decoding polylines with `precision`
+    /// decimal places, ie. 5 for [`GeometryType::Polyline`] and 6 for
+    /// [`GeometryType::Polyline6`]. See [`decode`](Self::decode).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`GeometryDecodeError::Polyline`] if the polyline is malformed.
+    pub fn decode_with(&self, precision: u32) -> Result<Vec<Point>, GeometryDecodeError> {
+        match self {
+            Self::Polyline(polyline) => Ok(crate::polyline::decode_polyline(polyline, precision)?),
+            Self::GeoJson(line) => Ok(line
+                .coordinates
+                .iter()
+                .copied()
+                .map(lon_lat_point)
+                .collect()),
+        }
+    }
+}
+
+/// Errors from decoding a [`Geometry`] with [`Geometry::decode`].
+#[derive(Error, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeometryDecodeError {
+    /// The polyline is malformed.
+    #[error("Failed to decode polyline: {0}")]
+    Polyline(#[from] PolylineDecodeError),
+    /// A polyline was decoded with [`GeometryType::GeoJSON`], which does not
+    /// give its precision.
+    #[error(
+        "Geometry is a polyline but the geometry type is GeoJSON; pass the geometry type the request was built with"
+    )]
+    UnknownPrecision,
+}
+
 // The approach of this implementation may need to change when support
 // for flatbuffers is added
 #[cfg(feature = "serde")]
@@ -77,7 +506,7 @@ impl<'de> serde::Deserialize<'de> for Geometry {
                     match e.classify() {
                         serde_json::error::Category::Syntax => {
                             let index = crate::get_index_of_line_col(trimmed, e.line(), e.column()).unwrap();
-                            let min = 0.max(index - 20);
+                            let min = index.saturating_sub(20);
                             let max = trimmed.len().min( index + 20);
                             serde::de::Error::custom(format!("Syntax error when attempting to parse geojson at line {}, col {}. Error occurred in this substring {}", e.line(), e.column(), trimmed.get(min..max).unwrap()))
                         }
@@ -88,7 +517,7 @@ impl<'de> serde::Deserialize<'de> for Geometry {
                             trimmed.get(len.saturating_sub(10)..).unwrap()
                         ))
                         }
-                        e => serde::de::Error::custom(format!("{:?}",e)),
+                        _ => serde::de::Error::custom(e),
                     }
                 )?),
             )
@@ -103,7 +532,7 @@ impl<'de> serde::Deserialize<'de> for Geometry {
                             trimmed.get(len.saturating_sub(10)..).unwrap()
                         ))
                     }
-                    e => serde::de::Error::custom(format!("{:?}", e)),
+                    _ => serde::de::Error::custom(e),
                 },
             )?))
         // Want to ensure we can deserialize, even if we have json inside a string with the " in
@@ -181,6 +610,91 @@ pub struct RouteLeg {
     pub annotation: Option<Annotation>,
 }
 
+impl RouteLeg {
+    /// Whether both the distance and duration of the leg are within
+    /// [`EMPTY_LEG_EPSILON`] of zero.
+    pub fn is_empty(&self) -> bool {
+        self.distance.abs() < EMPTY_LEG_EPSILON && self.duration.abs() < EMPTY_LEG_EPSILON
+    }
+
+    /// The average speed over the leg in meters per second.
+    ///
+    /// Returns `None` if the duration is within [`EMPTY_LEG_EPSILON`] of zero.
+    pub fn average_speed(&self) -> Option<f64> {
+        (self.duration.abs() >= EMPTY_LEG_EPSILON).then(|| self.distance / self.duration)
+    }
+
+    /// The annotation segments of the leg merged into runs which likely
+    /// belong to the same OSM way.
+    ///
+    /// OSRM does not return way IDs, so the grouping is a heuristic. A new
+    /// run starts at a segment when either
+    /// - its datasource differs from that of the previous segment, or
+    /// - its node IDs are not adjacent (differ by more than one), as the nodes
+    ///   of a way are often numbered consecutively.
+    ///
+    /// Requires the leg to have been requested with `annotations=true`,
+    /// otherwise the result is empty.
+    pub fn aggregated_segments(&self) -> Vec<AggregatedSegment> {
+        let Some(annotation) = &self.annotation else {
+            return Vec::new();
+        };
+        let segment_count = annotation
+            .distance
+            .len()
+            .min(annotation.duration.len())
+            .min(annotation.datasources.len())
+            .min(annotation.nodes.len().saturating_sub(1));
+
+        let mut segments: Vec<AggregatedSegment> = Vec::new();
+        for i in 0..segment_count {
+            let adjacent = (annotation.nodes[i + 1] - annotation.nodes[i]).abs() == 1.0;
+            let same_datasource =
+                i > 0 && annotation.datasources[i] == annotation.datasources[i - 1];
+            match segments.last_mut() {
+                Some(segment) if adjacent && same_datasource => {
+                    segment.distance += annotation.distance[i];
+                    segment.duration += annotation.duration[i];
+                    segment.node_range = *segment.node_range.start()..=i + 1;
+                }
+                _ => segments.push(AggregatedSegment {
+                    distance: annotation.distance[i],
+                    duration: annotation.duration[i],
+                    node_range: i..=i + 1,
+                }),
+            }
+        }
+        segments
+    }
+}
+
+/// A run of consecutive annotation segments, likely belonging to the same
+/// OSM way. See [`RouteLeg::aggregated_segments`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct AggregatedSegment {
+    /// The summed distance of the segments, in meters.
+    pub distance: f64,
+    /// The summed duration of the segments, in seconds.
+    pub duration: f64,
+    /// The indices into [`Annotation::nodes`] of the first and last node
+    /// of the run.
+    pub node_range: RangeInclusive<usize>,
+}
+
+/// The lanes at the maneuver of a step. See [`Route::lane_guidance`].
+#[derive(Clone, Debug)]
+pub struct LaneGuidance {
+    /// The index of the leg containing the step.
+    pub leg_index: usize,
+    /// The index of the step within its leg.
+    pub step_index: usize,
+    /// Every lane at the maneuver, from left to right.
+    pub lanes: Vec<Lane>,
+    /// The indices into `lanes` of the lanes which are valid for the
+    /// maneuver.
+    pub valid_lanes: Vec<usize>,
+}
+
 impl Default for RouteLeg {
     fn default() -> Self {
         Self {
@@ -197,20 +711,30 @@ impl Default for RouteLeg {
 /// Annotation of the whole route leg with fine-grained information about each
 /// segment or node id.
 ///
+/// Only the fields requested with
+/// [`Annotations`](crate::request_types::Annotations) are populated, the rest
+/// are empty.
+///
 /// Implements [`serde::Deserialize`] and
 /// [`serde::Serialize`] if `feature="serde"` is set.
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub struct Annotation {
     /// The distance, in meters, between each pair of coordinates.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub distance: Vec<f64>,
     /// The duration, in seconds, between each pair of coordinates.
     /// Does not include the duration of any turns.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub duration: Vec<f64>,
     /// The index of the data source for the speed between each pair of coordinates.
     /// `0` is the default profile, other values are supplied via `--segment-speed-file`
     /// to `osrm-contract` or `osrm-customize`. String-like names are in the
     /// `metadata.datasource_names` array.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, deserialize_with = "crate::serde_ops::vec_integer")
+    )]
     pub datasources: Vec<u64>,
     /// The OSM node ID for each coordinate along the route, excluding the
     /// first/last user-supplied coordinates.
@@ -219,13 +743,19 @@ pub struct Annotation {
     /// results in large integers being formatted in scientific notation. This can cause
     /// large (>10^10) node_ids to be returned incorrectly (by OSRM, they parse correctly
     /// but are serialized incorrectly)
+    #[cfg_attr(feature = "serde", serde(default))]
     pub nodes: Vec<f64>,
     /// The weights between each pair of coordinates.
     /// Does not include any turn costs.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub weight: Vec<f64>,
     /// Convenience field: calculation of `distance / duration` rounded to one decimal place.
+    #[cfg_attr(feature = "serde", serde(default))]
     pub speed: Vec<f64>,
     /// Metadata related to other annotations.
+    ///
+    /// Only returned with `datasources` annotations, otherwise it is empty.
+    #[cfg_attr(feature = "serde", serde(default = "crate::serde_ops::empty_metadata"))]
     pub metadata: Metadata,
 }
 
@@ -323,12 +853,9 @@ impl Default for RouteStep {
 ///
 /// Implements [`serde::Deserialize`] and
 /// [`serde::Serialize`] if `feature="serde"` is set.
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
-#[cfg_attr(
-    any(feature = "native", feature = "remote"),
-    serde(rename_all = "lowercase")
-)]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum DrivingSide {
     Right,
     Left,
@@ -354,6 +881,16 @@ impl Default for Metadata {
     }
 }
 
+/// Convert a `location` of a response, a `[longitude, latitude]` pair as
+/// returned by OSRM, into a [`Point`]. Every `as_point` method of the
+/// response types goes through here, so the order is only handled once.
+///
+/// The location is not range checked, as OSRM only returns locations on the
+/// map.
+pub fn lon_lat_point([longitude, latitude]: [f64; 2]) -> Point {
+    Point::new_unchecked(latitude, longitude)
+}
+
 /// An intersection gives a full representation of any cross-way the path passes by.
 /// For every step, the very first intersection (`intersections[0]`) corresponds
 /// to the location of the `StepManeuver`. Further intersections are listed for every
@@ -368,6 +905,10 @@ pub struct Intersection {
     pub location: [f64; 2],
     /// A list of bearing values (0-359) that are available at the intersection.
     /// These describe all available roads at the intersection.
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::serde_ops::vec_integer")
+    )]
     pub bearings: Vec<u16>,
     /// Classes of the roads exiting the intersection (as specified in the routing profile).
     pub classes: Option<Vec<String>>,
@@ -377,9 +918,17 @@ pub struct Intersection {
     pub entry: Vec<bool>,
     /// Index into bearings/entry array for the incoming road.
     /// Used to calculate the bearing just before the turn. Not supplied for `depart`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, deserialize_with = "crate::serde_ops::option_integer")
+    )]
     pub r#in: Option<usize>,
     /// Index into bearings/entry array for the outgoing road.
     /// Used to extract the bearing just after the turn. Not supplied for `arrive`.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, deserialize_with = "crate::serde_ops::option_integer")
+    )]
     pub out: Option<usize>,
     /// Array of `Lane` objects that denote the available turn lanes at the intersection.
     /// If no lane information is available, this is `None`.
@@ -400,6 +949,13 @@ impl Default for Intersection {
     }
 }
 
+impl Intersection {
+    /// The location of the intersection. See [`lon_lat_point`].
+    pub fn as_point(&self) -> Point {
+        lon_lat_point(self.location)
+    }
+}
+
 /// The object is used to describe the waypoint on a route.
 ///
 /// Implements [`serde::Deserialize`] and
@@ -436,6 +992,13 @@ impl Default for Waypoint {
     }
 }
 
+impl Waypoint {
+    /// The snapped location of the waypoint. See [`lon_lat_point`].
+    pub fn as_point(&self) -> Point {
+        lon_lat_point(self.location)
+    }
+}
+
 /// The object is used to describe the waypoint on a trip.
 ///
 /// Differs from a [`Waypoint`] by the `trips_index` and `waypoint_index`
@@ -461,9 +1024,17 @@ pub struct TripWaypoint {
     pub distance: f64,
     /// Index to `trips` in [`TripResonse`](crate::trip::TripResponse). The sub-trip the
     /// point was matched to.
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::serde_ops::integer")
+    )]
     pub trips_index: usize,
     /// Index to the point in [`TripRequest`](crate::trip::TripRequest) that the waypoint
     /// was matched to.
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::serde_ops::integer")
+    )]
     pub waypoint_index: usize,
 }
 
@@ -483,6 +1054,13 @@ impl Default for TripWaypoint {
     }
 }
 
+impl TripWaypoint {
+    /// The snapped location of the waypoint. See [`lon_lat_point`].
+    pub fn as_point(&self) -> Point {
+        lon_lat_point(self.location)
+    }
+}
+
 /// The object used to describe a waypoint returned by the nearest service.
 ///
 /// Implements [`serde::Deserialize`] and
@@ -524,6 +1102,13 @@ impl Default for NearestWaypoint {
     }
 }
 
+impl NearestWaypoint {
+    /// The snapped location of the waypoint. See [`lon_lat_point`].
+    pub fn as_point(&self) -> Point {
+        lon_lat_point(self.location)
+    }
+}
+
 /// Represents a maneuver in a route step, such as a turn or merge.
 ///
 /// Implements [`serde::Deserialize`] and
@@ -551,6 +1136,10 @@ pub struct StepManeuver {
     /// `slight left`, `left`, `sharp left`. Only `depart`/`arrive` may omit it.
     pub modifier: Option<String>,
     /// Optional integer indicating the exit number to take for `roundabout` / `rotary` maneuvers.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, deserialize_with = "crate::serde_ops::option_integer")
+    )]
     pub exit: Option<u64>,
 }
 
@@ -567,6 +1156,13 @@ impl Default for StepManeuver {
     }
 }
 
+impl StepManeuver {
+    /// The location of the maneuver. See [`lon_lat_point`].
+    pub fn as_point(&self) -> Point {
+        lon_lat_point(self.location)
+    }
+}
+
 /// A `Lane` represents a turn lane at the corresponding turn location.
 ///
 /// Implements [`serde::Deserialize`] and
@@ -608,64 +1204,43 @@ impl Default for Lane {
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum DrivingMode {
-    #[cfg_attr(
-        any(feature = "native", feature = "remote"),
-        serde(rename = "inaccessible")
-    )]
+    #[cfg_attr(feature = "serde", serde(rename = "inaccessible"))]
     /// Segment cannot be accessed by the current profile
     Inaccessible,
-    #[cfg_attr(any(feature = "native", feature = "remote"), serde(rename = "driving"))]
+    #[cfg_attr(feature = "serde", serde(rename = "driving"))]
     /// Standard vehicular driving
     Driving,
-    #[cfg_attr(any(feature = "native", feature = "remote"), serde(rename = "cycling"))]
+    #[cfg_attr(feature = "serde", serde(rename = "cycling"))]
     /// Cycling / bike mode
     Cycling,
-    #[cfg_attr(any(feature = "native", feature = "remote"), serde(rename = "walking"))]
+    #[cfg_attr(feature = "serde", serde(rename = "walking"))]
     /// Walking / pedestrian mode
     Walking,
-    #[cfg_attr(any(feature = "native", feature = "remote"), serde(rename = "ferry"))]
+    #[cfg_attr(feature = "serde", serde(rename = "ferry"))]
     /// Travel by ferry
     Ferry,
-    #[cfg_attr(any(feature = "native", feature = "remote"), serde(rename = "train"))]
+    #[cfg_attr(feature = "serde", serde(rename = "train"))]
     /// Travel by train
     Train,
-    #[cfg_attr(
-        any(feature = "native", feature = "remote"),
-        serde(rename = "pushing bike")
-    )]
+    #[cfg_attr(feature = "serde", serde(rename = "pushing bike"))]
     /// Walking while pushing a bicycle
     PushingBike,
-    #[cfg_attr(
-        any(feature = "native", feature = "remote"),
-        serde(rename = "steps up")
-    )]
+    #[cfg_attr(feature = "serde", serde(rename = "steps up"))]
     /// Going up steps (pedestrian)
     StepsUp,
-    #[cfg_attr(
-        any(feature = "native", feature = "remote"),
-        serde(rename = "steps down")
-    )]
+    #[cfg_attr(feature = "serde", serde(rename = "steps down"))]
     /// Going down steps (pedestrian)
     StepsDown,
-    #[cfg_attr(
-        any(feature = "native", feature = "remote"),
-        serde(rename = "river upstream")
-    )]
+    #[cfg_attr(feature = "serde", serde(rename = "river upstream"))]
     /// Travel upstream on a river
     RiverUpstream,
-    #[cfg_attr(
-        any(feature = "native", feature = "remote"),
-        serde(rename = "river downstream")
-    )]
+    #[cfg_attr(feature = "serde", serde(rename = "river downstream"))]
     /// Travel downstream on a river
     RiverDownstream,
-    #[cfg_attr(any(feature = "native", feature = "remote"), serde(rename = "route"))]
+    #[cfg_attr(feature = "serde", serde(rename = "route"))]
     /// Generic or unspecified route segment
     Route,
-    #[cfg_attr(
-        any(feature = "native", feature = "remote"),
-        serde(rename = "other", other)
-    )]
+    #[cfg_attr(feature = "serde", serde(rename = "other", other))]
     /// Fallback for unknown or custom segment types
     Other,
 }
@@ -679,44 +1254,26 @@ pub enum DrivingMode {
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 pub enum Direction {
-    #[cfg_attr(any(feature = "native", feature = "remote"), serde(rename = "uturn"))]
+    #[cfg_attr(feature = "serde", serde(rename = "uturn"))]
     Uturn,
-    #[cfg_attr(
-        any(feature = "native", feature = "remote"),
-        serde(rename = "sharp right")
-    )]
+    #[cfg_attr(feature = "serde", serde(rename = "sharp right"))]
     SharpRight,
-    #[cfg_attr(any(feature = "native", feature = "remote"), serde(rename = "right"))]
+    #[cfg_attr(feature = "serde", serde(rename = "right"))]
     Right,
-    #[cfg_attr(
-        any(feature = "native", feature = "remote"),
-        serde(rename = "slight right")
-    )]
+    #[cfg_attr(feature = "serde", serde(rename = "slight right"))]
     SlightRight,
-    #[cfg_attr(
-        any(feature = "native", feature = "remote"),
-        serde(rename = "straight")
-    )]
+    #[cfg_attr(feature = "serde", serde(rename = "straight"))]
     Straight,
-    #[cfg_attr(
-        any(feature = "native", feature = "remote"),
-        serde(rename = "slight left")
-    )]
+    #[cfg_attr(feature = "serde", serde(rename = "slight left"))]
     SlightLeft,
-    #[cfg_attr(any(feature = "native", feature = "remote"), serde(rename = "left"))]
+    #[cfg_attr(feature = "serde", serde(rename = "left"))]
     Left,
-    #[cfg_attr(
-        any(feature = "native", feature = "remote"),
-        serde(rename = "sharp left")
-    )]
+    #[cfg_attr(feature = "serde", serde(rename = "sharp left"))]
     SharpLeft,
-    #[cfg_attr(any(feature = "native", feature = "remote"), serde(rename = "none"))]
+    #[cfg_attr(feature = "serde", serde(rename = "none"))]
     /// Represents the absence of a directional marking (used in `Lane.indications`).
     None,
-    #[cfg_attr(
-        any(feature = "native", feature = "remote"),
-        serde(rename = "other", other)
-    )]
+    #[cfg_attr(feature = "serde", serde(rename = "other", other))]
     /// Fallback for unknown or future direction strings not explicitly listed.
     Other,
 }
@@ -741,10 +1298,22 @@ pub struct MatchWaypoint {
     /// The distance, in meters, from the input coordinate to the snapped coordinate
     pub distance: f64,
     /// Index to the `Route` object in `matchings` the sub-trace was matched to
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::serde_ops::integer")
+    )]
     pub matchings_index: u64,
     /// Index of the waypoint inside the matched route
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::serde_ops::integer")
+    )]
     pub waypoint_index: u64,
     /// Number of probable alternative matchings for this tracepoint. A value of zero indicates that this point was matched unambiguously. Split the trace at these points for incremental map matching
+    #[cfg_attr(
+        feature = "serde",
+        serde(deserialize_with = "crate::serde_ops::integer")
+    )]
     pub alternatives_count: u64,
 }
 
@@ -764,6 +1333,13 @@ impl Default for MatchWaypoint {
     }
 }
 
+impl MatchWaypoint {
+    /// The snapped location of the tracepoint. See [`lon_lat_point`].
+    pub fn as_point(&self) -> Point {
+        lon_lat_point(self.location)
+    }
+}
+
 /// Represents a route through (potentially multiple) waypoints.
 /// Specifically returned by the match service.
 ///
@@ -810,3 +1386,106 @@ impl Default for MatchRoute {
         }
     }
 }
+
+/// The `code` of an OSRM response. `Ok` denotes success, every other code
+/// is an error, see the
+/// [OSRM documentation](https://project-osrm.org/docs/v5.24.0/api/#responses)
+/// for their meanings.
+///
+/// Parsed from the `code` string of a response with
+/// [`RouteResponse::code_enum`](crate::route::RouteResponse::code_enum) and
+/// the like. Codes this version does not know of, eg. from newer or forked
+/// backends, are kept in [`Other`](Self::Other).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ResponseCode {
+    /// Request could be processed as expected.
+    Ok,
+    /// URL string is invalid.
+    InvalidUrl,
+    /// Service name is invalid.
+    InvalidService,
+    /// Version is not found.
+    InvalidVersion,
+    /// Options are invalid.
+    InvalidOptions,
+    /// The query string is syntactically malformed.
+    InvalidQuery,
+    /// The successfully parsed query parameters are invalid.
+    InvalidValue,
+    /// One of the supplied input coordinates could not snap to the street
+    /// segment.
+    NoSegment,
+    /// The request size violates one of the service-specific request size
+    /// restrictions.
+    TooBig,
+    /// No route found. Route service only.
+    NoRoute,
+    /// No route found between any of the input coordinates. Table service
+    /// only.
+    NoTable,
+    /// The request is not supported by the backend.
+    NotImplemented,
+    /// No trips found because the input coordinates are not connected. Trip
+    /// service only.
+    NoTrips,
+    /// No matchings found. Match service only.
+    NoMatch,
+    /// Any other code.
+    Other(String),
+}
+
+impl ResponseCode {
+    /// The code as it appears in the response, eg. `"NoSegment"`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Ok => "Ok",
+            Self::InvalidUrl => "InvalidUrl",
+            Self::InvalidService => "InvalidService",
+            Self::InvalidVersion => "InvalidVersion",
+            Self::InvalidOptions => "InvalidOptions",
+            Self::InvalidQuery => "InvalidQuery",
+            Self::InvalidValue => "InvalidValue",
+            Self::NoSegment => "NoSegment",
+            Self::TooBig => "TooBig",
+            Self::NoRoute => "NoRoute",
+            Self::NoTable => "NoTable",
+            Self::NotImplemented => "NotImplemented",
+            Self::NoTrips => "NoTrips",
+            Self::NoMatch => "NoMatch",
+            Self::Other(code) => code,
+        }
+    }
+
+    /// Whether the code denotes success.
+    pub fn is_ok(&self) -> bool {
+        *self == Self::Ok
+    }
+}
+
+impl From<&str> for ResponseCode {
+    fn from(code: &str) -> Self {
+        match code {
+            "Ok" => Self::Ok,
+            "InvalidUrl" => Self::InvalidUrl,
+            "InvalidService" => Self::InvalidService,
+            "InvalidVersion" => Self::InvalidVersion,
+            "InvalidOptions" => Self::InvalidOptions,
+            "InvalidQuery" => Self::InvalidQuery,
+            "InvalidValue" => Self::InvalidValue,
+            "NoSegment" => Self::NoSegment,
+            "TooBig" => Self::TooBig,
+            "NoRoute" => Self::NoRoute,
+            "NoTable" => Self::NoTable,
+            "NotImplemented" => Self::NotImplemented,
+            "NoTrips" => Self::NoTrips,
+            "NoMatch" => Self::NoMatch,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for ResponseCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
@@ -0,0 +1,101 @@
+//! Decoding of the [encoded polyline](https://developers.google.com/maps/documentation/utilities/polylinealgorithm)
+//! geometries returned by OSRM when requesting
+//! [`GeometryType::Polyline`](crate::request_types::GeometryType::Polyline) or
+//! [`GeometryType::Polyline6`](crate::request_types::GeometryType::Polyline6).
+//!
+//! Decoding is safe for untrusted input: malformed polylines produce a
+//! [`PolylineDecodeError`] rather than panicking.
+
+use thiserror::Error;
+
+use crate::Point;
+
+/// The ways in which an encoded polyline can be malformed.
+#[derive(Error, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolylineDecodeError {
+    /// A byte outside of the polyline alphabet (`?` to `~`) was found.
+    #[error("Invalid polyline character at byte {0}")]
+    InvalidCharacter(usize),
+    /// The polyline ended part way through a value, or with a latitude
+    /// which has no longitude.
+    #[error("Polyline is truncated")]
+    Truncated,
+    /// A value, or the running sum of values, does not fit in a coordinate.
+    #[error("Polyline value at byte {0} overflows")]
+    Overflow(usize),
+    /// The point starting at this byte is not a valid latitude and longitude.
+    #[error("Polyline point at byte {0} is out of range, is the precision correct?")]
+    OutOfRange(usize),
+}
+
+/// Decode an encoded polyline with the given precision (5 or 6 for OSRM)
+/// into its vertices.
+///
+/// ```
+/// use osrm_interface::polyline::decode_polyline;
+///
+/// let points = decode_polyline("_p~iF~ps|U_ulLnnqC", 5).expect("Invalid polyline");
+/// assert_eq!(points[0].as_tuple(), (38.5, -120.2));
+/// assert_eq!(points[1].as_tuple(), (40.7, -120.95));
+/// ```
+pub fn decode_polyline(encoded: &str, precision: u32) -> Result<Vec<Point>, PolylineDecodeError> {
+    let factor = 10f64.powi(precision as i32);
+    let mut bytes = encoded.bytes().enumerate();
+    let mut points = Vec::new();
+    let (mut latitude, mut longitude) = (0i64, 0i64);
+
+    while let Some((position, lat_delta)) = decode_value(&mut bytes)? {
+        // A latitude without a longitude is a truncated polyline
+        let (_, lon_delta) = decode_value(&mut bytes)?.ok_or(PolylineDecodeError::Truncated)?;
+        latitude = latitude
+            .checked_add(lat_delta)
+            .ok_or(PolylineDecodeError::Overflow(position))?;
+        longitude = longitude
+            .checked_add(lon_delta)
+            .ok_or(PolylineDecodeError::Overflow(position))?;
+        let point = Point::new(latitude as f64 / factor, longitude as f64 / factor)
+            .ok_or(PolylineDecodeError::OutOfRange(position))?;
+        points.push(point);
+    }
+
+    Ok(points)
+}
+
+/// Decode a single zigzag encoded value, returning it with the byte position
+/// it started at. Returns `Ok(None)` when the input is exhausted before the
+/// value starts.
+fn decode_value(
+    bytes: &mut impl Iterator<Item = (usize, u8)>,
+) -> Result<Option<(usize, i64)>, PolylineDecodeError> {
+    let mut result = 0i64;
+    let mut shift = 0;
+    let mut start = None;
+
+    loop {
+        let Some((position, byte)) = bytes.next() else {
+            return match start {
+                None => Ok(None),
+                Some(_) => Err(PolylineDecodeError::Truncated),
+            };
+        };
+        let start = *start.get_or_insert(position);
+        if !(63..=126).contains(&byte) {
+            return Err(PolylineDecodeError::InvalidCharacter(position));
+        }
+        let chunk = (byte - 63) as i64;
+        result |= (chunk & 0x1f) << shift;
+        if chunk < 0x20 {
+            let value = if result & 1 == 1 {
+                !(result >> 1)
+            } else {
+                result >> 1
+            };
+            return Ok(Some((start, value)));
+        }
+        shift += 5;
+        // Any value requiring more than 60 bits is not a valid coordinate
+        if shift > 55 {
+            return Err(PolylineDecodeError::Overflow(start));
+        }
+    }
+}
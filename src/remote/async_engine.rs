@@ -0,0 +1,261 @@
+use std::future::Future;
+use std::panic::{AssertUnwindSafe, catch_unwind, resume_unwind};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, mpsc};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use crate::errors::OsrmError;
+use crate::r#match::{MatchRequest, MatchResponse};
+use crate::nearest::{NearestRequest, NearestResponse};
+use crate::remote::OsrmEngine;
+use crate::remote::osrm_engine::remote_parse_error;
+use crate::route::{RouteRequest, RouteResponse};
+use crate::table::{TableRequest, TableResponse};
+use crate::trip::{TripRequest, TripResponse};
+
+/// An async wrapper around the remote [`OsrmEngine`], for calling OSRM from
+/// async code without blocking the executor.
+///
+/// The urls are built on the calling thread, then each request is sent by
+/// the wrapped blocking engine on a fixed pool of worker threads, completing
+/// the returned future once the response has been read. No particular runtime
+/// is required, and the urls, rate limit, snap cache and timeouts are exactly
+/// those of the wrapped engine.
+///
+/// - At most [`workers`](Self::with_workers) requests are in flight at once,
+///   further requests queue until a worker is free. The pool is shared between
+///   clones of the engine.
+/// - A [`rate_limit`](OsrmEngine::rate_limit) on the wrapped engine is waited
+///   on by the workers, so it delays queued requests without starting more
+///   threads.
+/// - Dropping a future before its request has started removes it from the
+///   queue. A request which has already been sent runs to completion and its
+///   response is discarded.
+/// - A panic while sending a request is resumed in the task awaiting it.
+///
+/// ```no_run
+/// use osrm_interface::remote::{AsyncOsrmEngine, OsrmEngine, Profile};
+/// use osrm_interface::{Point, route::RouteRequestBuilder};
+///
+/// async fn distance(engine: &AsyncOsrmEngine, points: &[Point]) -> f64 {
+///     let route_request = RouteRequestBuilder::new(points)
+///         .build()
+///         .expect("Failed to build RouteRequest");
+///     let response = engine.route(&route_request).await.expect("Failed to route");
+///     response.routes[0].distance
+/// }
+///
+/// let engine = AsyncOsrmEngine::new(OsrmEngine::new(
+///     "http://localhost:5000".to_string(),
+///     Profile::Car,
+/// ));
+/// ```
+///
+/// Locked behind the `async` feature flag.
+#[derive(Clone, Debug)]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub struct AsyncOsrmEngine {
+    engine: OsrmEngine,
+    pool: Arc<WorkerPool>,
+}
+
+/// The number of worker threads used by [`AsyncOsrmEngine::new`].
+const DEFAULT_WORKERS: usize = 4;
+
+impl AsyncOsrmEngine {
+    /// Wrap a configured remote engine, sending requests from a pool of 4
+    /// worker threads.
+    pub fn new(engine: OsrmEngine) -> Self {
+        Self::with_workers(engine, DEFAULT_WORKERS)
+    }
+
+    /// Wrap a configured remote engine, sending at most `workers` requests
+    /// at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `workers` is zero.
+    pub fn with_workers(engine: OsrmEngine, workers: usize) -> Self {
+        assert!(workers > 0, "workers must be positive");
+        Self {
+            engine,
+            pool: Arc::new(WorkerPool::new(workers)),
+        }
+    }
+
+    /// The number of worker threads sending requests.
+    pub fn workers(&self) -> usize {
+        self.pool.workers
+    }
+
+    /// The wrapped synchronous engine.
+    pub fn engine(&self) -> &OsrmEngine {
+        &self.engine
+    }
+
+    /// Send a GET request to the endpoint from the worker pool.
+    fn get(&self, url: String) -> Pending<Result<String, OsrmError>> {
+        let engine = self.engine.clone();
+        self.pool.submit(move || engine.get(url))
+    }
+
+    /// See [`OsrmEngine::table`], including its exclude warning.
+    pub async fn table(
+        &self,
+        table_request: &TableRequest<'_>,
+    ) -> Result<TableResponse, OsrmError> {
+        #[cfg(feature = "log")]
+        if let Err(mismatch) = self.engine.check_table_exclude(table_request) {
+            log::warn!("{mismatch}, sending the request regardless");
+        }
+        let response = self.get(self.engine.table_url(table_request)).await?;
+        TableResponse::from_json(&response).map_err(remote_parse_error)
+    }
+
+    /// See [`OsrmEngine::route`].
+    pub async fn route(
+        &self,
+        route_request: &RouteRequest<'_>,
+    ) -> Result<RouteResponse, OsrmError> {
+        let response = self.get(self.engine.route_url(route_request)).await?;
+        RouteResponse::from_json(&response).map_err(remote_parse_error)
+    }
+
+    /// See [`OsrmEngine::trip`].
+    pub async fn trip(&self, trip_request: &TripRequest<'_>) -> Result<TripResponse, OsrmError> {
+        let response = self.get(self.engine.trip_url(trip_request)).await?;
+        TripResponse::from_json(&response).map_err(remote_parse_error)
+    }
+
+    /// See [`OsrmEngine::nearest`]. Shares the snap cache of the wrapped
+    /// engine.
+    pub async fn nearest(
+        &self,
+        nearest_request: &NearestRequest<'_>,
+    ) -> Result<NearestResponse, OsrmError> {
+        if let Some(response) = self.engine.cached_nearest(nearest_request) {
+            return Ok(response);
+        }
+        let response = self.get(self.engine.nearest_url(nearest_request)).await?;
+        self.engine.parse_nearest(nearest_request, &response)
+    }
+
+    /// See [`OsrmEngine::r#match`](OsrmEngine::match).
+    pub async fn r#match(
+        &self,
+        match_request: &MatchRequest<'_>,
+    ) -> Result<MatchResponse, OsrmError> {
+        let response = self.get(self.engine.match_url(match_request)).await?;
+        MatchResponse::from_json(&response).map_err(remote_parse_error)
+    }
+}
+
+impl From<OsrmEngine> for AsyncOsrmEngine {
+    fn from(engine: OsrmEngine) -> Self {
+        Self::new(engine)
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// A fixed number of threads running queued jobs. The threads exit once the
+/// last clone of the engine holding the pool is dropped.
+struct WorkerPool {
+    sender: mpsc::Sender<Job>,
+    workers: usize,
+}
+
+impl WorkerPool {
+    fn new(workers: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..workers {
+            let receiver = receiver.clone();
+            thread::spawn(move || {
+                loop {
+                    // Release the lock before running the job
+                    let job = receiver.lock().unwrap().recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => break,
+                    }
+                }
+            });
+        }
+        Self { sender, workers }
+    }
+
+    /// Queue `f` to run on a worker, returning a future resolving to its
+    /// result.
+    fn submit<T: Send + 'static>(&self, f: impl FnOnce() -> T + Send + 'static) -> Pending<T> {
+        let state = Arc::new(Mutex::new(PendingState {
+            result: None,
+            waker: None,
+            cancelled: false,
+        }));
+        let shared = state.clone();
+        let job = Box::new(move || {
+            if shared.lock().unwrap().cancelled {
+                return;
+            }
+            // Caught so the worker survives, and resumed when polled
+            let result = catch_unwind(AssertUnwindSafe(f));
+            let mut state = shared.lock().unwrap();
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+        self.sender
+            .send(job)
+            .expect("Worker threads only exit once the pool is dropped");
+        Pending { state }
+    }
+}
+
+impl std::fmt::Debug for WorkerPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WorkerPool")
+            .field("workers", &self.workers)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A future resolving to the result of a job queued on a [`WorkerPool`].
+struct Pending<T> {
+    state: Arc<Mutex<PendingState<T>>>,
+}
+
+struct PendingState<T> {
+    result: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+    cancelled: bool,
+}
+
+impl<T> Future for Pending<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.state.lock().unwrap();
+        match state.result.take() {
+            Some(Ok(result)) => Poll::Ready(result),
+            Some(Err(panic)) => {
+                drop(state);
+                resume_unwind(panic)
+            }
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<T> Drop for Pending<T> {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.cancelled = true;
+        }
+    }
+}
@@ -16,31 +16,68 @@
 //!
 //! Profile is present so that when making requests to the ProjectOSRM endpoint,
 //! it can dispatch to the correct routed instance.
+//!
+//! ## Response parsing
+//!
+//! Responses are parsed with `serde_json`, so deeply nested adversarial
+//! input, eg. from a malicious endpoint, fails cleanly rather than overflowing
+//! the stack. Nesting where the response types do not expect it is rejected
+//! with a [`JsonParse`](crate::errors::RemoteOsrmError::JsonParse) error at
+//! the depth it occurs, and unknown fields are skipped without recursing.
+//! Untyped values, eg. the response read by
+//! [`probe`](OsrmEngine::probe), are subject to the `serde_json` default
+//! recursion limit of 128 nested arrays or objects.
+//!
+//! ## Connection reuse
+//!
+//! Every service sends its request through a single [`ureq::Agent`] held by
+//! the engine, which keeps connections to the endpoint alive between
+//! requests. Clones of the engine share the agent and its connections, so
+//! clone the engine rather than constructing a new one per request or thread.
+//!
+//! For small requests, eg. nearest, the connection setup can dominate the
+//! time of each request, more so for endpoints further away or behind TLS.
+//!
+//! ## Async
+//!
+//! With `feature="async"`, `AsyncOsrmEngine` wraps the engine for use from
+//! async code, eg. a tokio web handler, without blocking the executor. The
+//! requests are still sent by the blocking engine, from a bounded pool of
+//! worker threads, so no async runtime or further dependencies are needed.
 
+#[cfg(feature = "async")]
+mod async_engine;
 mod osrm_engine;
+mod rate_limiter;
+#[cfg(feature = "async")]
+pub use async_engine::AsyncOsrmEngine;
 #[cfg_attr(docsrs, doc(cfg(feature = "remote")))]
 pub use osrm_engine::OsrmEngine;
 
-/// The profile with which the underlying map data was extracted.
+pub use crate::Profile;
+
+use crate::request_types::Exclude;
+
+/// An exclude which does not match the profile of the engine, returned by
+/// [`OsrmEngine::check_table_exclude`].
 ///
-/// The profile is placed in the URL. In many cases, it is ignored.
-/// See [`crate::remote`] for more information about `Profile`.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum Profile {
-    Car,
-    Bike,
-    Foot,
+/// This is a warning rather than an error, as the backend may have been
+/// extracted with a custom profile which supports the exclude.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("Exclude {exclude:?} does not match the {} profile", profile.url_form())]
+pub struct ExcludeProfileMismatch {
+    pub profile: Profile,
+    pub exclude: Exclude,
 }
-impl Profile {
-    /// Formats the variant as a lowercase &str. The form expected
-    /// by `osrm-routed`.
-    ///
-    /// eg. `"bike"` or `"car"` or `"foot"`
-    pub fn url_form(self) -> &'static str {
-        match self {
-            Self::Bike => "bike",
-            Self::Car => "car",
-            Self::Foot => "foot",
-        }
-    }
+
+/// Information about the backend behind a remote endpoint, returned by
+/// [`OsrmEngine::probe`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BackendInfo {
+    /// Whether a connection could be established with the endpoint.
+    pub reachable: bool,
+    /// The `data_version` reported by the backend. This is the timestamp
+    /// of the OSM data used to extract the map, and is only present if
+    /// the map data included one.
+    pub data_version: Option<String>,
 }
@@ -0,0 +1,49 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A token bucket holding at most a single token, so calls are spaced
+/// evenly at the given rate with no bursting.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    requests_per_second: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(requests_per_second: f64) -> Self {
+        Self {
+            requests_per_second,
+            bucket: Mutex::new(Bucket {
+                tokens: 1.0,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take a token, blocking the calling thread until one is available.
+    pub(crate) fn acquire(&self) {
+        loop {
+            let wait = {
+                // A poisoned lock only means another thread panicked while
+                // holding it, the bucket itself is still valid
+                let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+                let now = Instant::now();
+                let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed * self.requests_per_second).min(1.0);
+                bucket.last_refill = now;
+                if bucket.tokens >= 1.0 {
+                    bucket.tokens -= 1.0;
+                    return;
+                }
+                (1.0 - bucket.tokens) / self.requests_per_second
+            };
+            std::thread::sleep(Duration::from_secs_f64(wait));
+        }
+    }
+}
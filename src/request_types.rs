@@ -1,5 +1,7 @@
 //! Common request sub-types that are used to build the service requests
 
+use itertools::Itertools;
+
 /// Specify which geometry type the service should return.
 ///
 /// For no geometry, set `OverviewZoom::False` in the builder.
@@ -31,6 +33,16 @@ impl GeometryType {
             Self::Polyline6 => "polyline6",
         }
     }
+
+    /// The number of decimal places encoded in polylines of this type, or
+    /// `None` for GeoJSON.
+    pub fn polyline_precision(self) -> Option<u32> {
+        match self {
+            Self::Polyline => Some(5),
+            Self::Polyline6 => Some(6),
+            Self::GeoJSON => None,
+        }
+    }
 }
 
 /// Specify the level of detail for the
@@ -76,11 +88,11 @@ impl OverviewZoom {
 /// `Bearing::new(0,10)`. For an arc 90 degrees either side of east, so
 /// from north to south, use `Bearing::new(90,90)`.
 ///
-/// `bearing` must be in the range `[0,360]` and `range` in `[0,180]`, both
-/// inclusive.
+/// `bearing` must be in the range `[0,359]` and `range` in `[0,180]`, both
+/// inclusive. A bearing of 360 is due north, so is written as 0.
 ///
 /// Constructing with [`new`](Self::new) will check `bearing` and `range` values.
-/// [`new`](Self::new_unchecked) is also provided.
+/// [`new_unchecked`](Self::new_unchecked) is also provided.
 ///
 /// Implements [`serde::Deserialize`] and
 /// [`serde::Serialize`] if `feature="serde"` is set.
@@ -93,12 +105,12 @@ pub struct Bearing {
 }
 
 impl Bearing {
-    /// Check 0 <= bearing <= 360 and 0 <= range <= 180.
+    /// Check 0 <= bearing <= 359 and 0 <= range <= 180.
     ///
-    /// Returns `None` if that is not satisfied. Also see
+    /// Returns `None` if either is not satisfied. Also see
     /// [`new_unchecked`](Self::new_unchecked).
     pub fn new(bearing: i16, range: i16) -> Option<Self> {
-        if !(0..=360).contains(&bearing) & !(0..=180).contains(&range) {
+        if !(0..360).contains(&bearing) || !(0..=180).contains(&range) {
             return None;
         }
         Some(Self { bearing, range })
@@ -112,6 +124,16 @@ impl Bearing {
         Self { bearing, range }
     }
 
+    /// The midpoint of the arc in degrees, clockwise from true north.
+    pub fn value(&self) -> i16 {
+        self.bearing
+    }
+
+    /// The number of degrees either side of [`value`](Self::value) in the arc.
+    pub fn range(&self) -> i16 {
+        self.range
+    }
+
     /// Formats the bearing as a comma separated pair. The form expected
     /// by `osrm-routed`.
     ///
@@ -143,6 +165,51 @@ pub enum Exclude {
     Bicycle(BicycleExclude),
 }
 
+impl Exclude {
+    /// Concatenate lists of excludes, eg. named presets, in order, dropping
+    /// duplicates after their first occurrence.
+    ///
+    /// Returns [`ExcludeError::DifferentExcludeTypes`] if the combined
+    /// excludes mix transport modes, which the request builders would reject.
+    /// Presets built as [`ExcludeSet`]s of the same mode can be combined with
+    /// [`ExcludeSet::union`] instead, which cannot fail.
+    ///
+    /// ```
+    /// use osrm_interface::request_types::{CarExclude, Exclude};
+    /// let avoid_highways = [Exclude::Car(CarExclude::Motorway)];
+    /// let avoid_costs = [Exclude::Car(CarExclude::Toll), Exclude::Car(CarExclude::Ferry)];
+    /// let excludes = Exclude::combine([&avoid_highways[..], &avoid_costs[..]])
+    ///     .expect("Presets share a mode");
+    /// assert_eq!(excludes.len(), 3);
+    /// ```
+    pub fn combine<'a>(
+        lists: impl IntoIterator<Item = &'a [Exclude]>,
+    ) -> Result<Vec<Exclude>, ExcludeError> {
+        let mut combined: Vec<Exclude> = Vec::new();
+        for &exclude in lists.into_iter().flatten() {
+            if combined.first().is_some_and(|first| {
+                std::mem::discriminant(first) != std::mem::discriminant(&exclude)
+            }) {
+                return Err(ExcludeError::DifferentExcludeTypes);
+            }
+            if !combined.contains(&exclude) {
+                combined.push(exclude);
+            }
+        }
+        Ok(combined)
+    }
+}
+
+/// Errors from combining lists of excludes with [`Exclude::combine`].
+#[derive(thiserror::Error, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExcludeError {
+    /// The excludes are not all of the same transport mode.
+    #[error(
+        "Exclude types are not all of the same type; use the excludes of a single profile, eg. only `CarExclude`"
+    )]
+    DifferentExcludeTypes,
+}
+
 /// Types of nodes from which Car routing may exclude.
 ///
 /// Implements [`serde::Deserialize`] and
@@ -222,3 +289,462 @@ impl Snapping {
         }
     }
 }
+
+/// The per-segment annotations returned with each leg by the route, trip and
+/// match services. See [`Annotation`](crate::osrm_response_types::Annotation)
+/// for the fields each enables.
+///
+/// Converts from a `bool`, where `true` requests every annotation and
+/// `false` none, so the builders accept either.
+///
+/// ```
+/// use osrm_interface::request_types::Annotations;
+///
+/// let speed_only = Annotations {
+///     speed: true,
+///     ..Annotations::NONE
+/// };
+/// assert_eq!(speed_only.url_form(), "speed");
+/// assert_eq!(Annotations::from(true), Annotations::ALL);
+/// ```
+///
+/// Implements [`serde::Deserialize`] and
+/// [`serde::Serialize`] if `feature="serde"` is set.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Annotations {
+    /// The duration between each pair of coordinates.
+    pub duration: bool,
+    /// The OSM node ID of each coordinate.
+    pub nodes: bool,
+    /// The distance between each pair of coordinates.
+    pub distance: bool,
+    /// The weight between each pair of coordinates.
+    pub weight: bool,
+    /// The data source of the speed between each pair of coordinates.
+    pub datasources: bool,
+    /// The speed between each pair of coordinates.
+    pub speed: bool,
+}
+
+impl Annotations {
+    /// No annotations, the default.
+    pub const NONE: Self = Self {
+        duration: false,
+        nodes: false,
+        distance: false,
+        weight: false,
+        datasources: false,
+        speed: false,
+    };
+
+    /// Every annotation.
+    pub const ALL: Self = Self {
+        duration: true,
+        nodes: true,
+        distance: true,
+        weight: true,
+        datasources: true,
+        speed: true,
+    };
+
+    /// Whether any annotation is requested.
+    pub fn any(&self) -> bool {
+        *self != Self::NONE
+    }
+
+    /// Formats the annotations in the form expected by `osrm-routed`.
+    ///
+    /// eg. `"false"`, `"true"` for all of them or `"duration,speed"`
+    pub fn url_form(&self) -> String {
+        match *self {
+            Self::NONE => "false".to_string(),
+            Self::ALL => "true".to_string(),
+            _ => self
+                .names()
+                .filter_map(|(name, requested)| requested.then_some(name))
+                .join(","),
+        }
+    }
+
+    /// The annotations as the `AnnotationsType` bitmask of osrm-backend,
+    /// passed to the native engine.
+    #[cfg_attr(not(feature = "native"), allow(dead_code))]
+    pub(crate) fn bits(&self) -> u8 {
+        self.names()
+            .enumerate()
+            .filter(|(_, (_, requested))| *requested)
+            .fold(0, |bits, (i, _)| bits | 1 << i)
+    }
+
+    /// The url name of each annotation and whether it is requested, in the
+    /// order of the bits of `AnnotationsType`.
+    fn names(&self) -> impl Iterator<Item = (&'static str, bool)> {
+        [
+            ("duration", self.duration),
+            ("nodes", self.nodes),
+            ("distance", self.distance),
+            ("weight", self.weight),
+            ("datasources", self.datasources),
+            ("speed", self.speed),
+        ]
+        .into_iter()
+    }
+}
+
+impl From<bool> for Annotations {
+    fn from(annotations: bool) -> Self {
+        if annotations { Self::ALL } else { Self::NONE }
+    }
+}
+
+/// Marker type for an [`ExcludeSet`] containing only [`CarExclude`]s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Car {}
+
+/// Marker type for an [`ExcludeSet`] containing only [`BicycleExclude`]s.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bicycle {}
+
+/// A list of [`Exclude`]s which are guaranteed at compile time to share
+/// the same transport mode.
+///
+/// The request builders verify that excludes are not mixed when
+/// [`build`](crate::route::RouteRequestBuilder::build) is called. Constructing
+/// excludes through an `ExcludeSet` moves that check to compile time. The set
+/// is passed to the builders through their `exclude_set` methods, which is
+/// equivalent to passing [`as_slice`](Self::as_slice) to `exclude`.
+///
+/// There are markers for [`Car`] and [`Bicycle`] only. The stock OSRM foot
+/// profile defines no excludable classes, matching [`Exclude`] having no
+/// foot variant, so a foot set could only ever be empty.
+///
+/// ## Example
+///
+/// ```
+/// use osrm_interface::{Point, request_types::ExcludeSet, route::RouteRequestBuilder};
+/// let points = [
+///     Point::new(48.040437, 10.316550).expect("Invalid point"),
+///     Point::new(49.006101, 9.052887).expect("Invalid point"),
+/// ];
+/// let excludes = ExcludeSet::car().motorway().toll();
+/// let route_request = RouteRequestBuilder::new(&points)
+///     .exclude_set(&excludes)
+///     .build()
+///     .expect("Failed to build RouteRequest");
+/// ```
+///
+/// Attempting to add an exclude of a different mode fails to compile.
+///
+/// ```compile_fail
+/// use osrm_interface::request_types::{BicycleExclude, ExcludeSet};
+/// let excludes = ExcludeSet::car().push(BicycleExclude::Ferry);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExcludeSet<M> {
+    excludes: Vec<Exclude>,
+    _mode: std::marker::PhantomData<M>,
+}
+
+impl<M> ExcludeSet<M> {
+    /// The excludes in the set, in insertion order.
+    pub fn as_slice(&self) -> &[Exclude] {
+        &self.excludes
+    }
+
+    /// Number of excludes in the set.
+    pub fn len(&self) -> usize {
+        self.excludes.len()
+    }
+
+    /// Whether the set contains no excludes.
+    pub fn is_empty(&self) -> bool {
+        self.excludes.is_empty()
+    }
+
+    /// Add every exclude of `other` which is not already in the set, eg. to
+    /// compose named presets. The mode of both sets is the same, so unlike
+    /// [`Exclude::combine`] this cannot fail.
+    pub fn union(self, other: &Self) -> Self {
+        other
+            .excludes
+            .iter()
+            .fold(self, |set, &exclude| set.with(exclude))
+    }
+
+    fn with(mut self, exclude: Exclude) -> Self {
+        if !self.excludes.contains(&exclude) {
+            self.excludes.push(exclude);
+        }
+        self
+    }
+}
+
+impl ExcludeSet<Car> {
+    /// Create an empty set of car excludes.
+    pub fn car() -> Self {
+        Self {
+            excludes: Vec::new(),
+            _mode: std::marker::PhantomData,
+        }
+    }
+
+    /// Add a car exclude to the set. Duplicates are ignored.
+    pub fn push(self, exclude: CarExclude) -> Self {
+        self.with(Exclude::Car(exclude))
+    }
+
+    /// Exclude toll roads.
+    pub fn toll(self) -> Self {
+        self.push(CarExclude::Toll)
+    }
+
+    /// Exclude motorways.
+    pub fn motorway(self) -> Self {
+        self.push(CarExclude::Motorway)
+    }
+
+    /// Exclude ferries.
+    pub fn ferry(self) -> Self {
+        self.push(CarExclude::Ferry)
+    }
+}
+
+impl ExcludeSet<Bicycle> {
+    /// Create an empty set of bicycle excludes.
+    pub fn bicycle() -> Self {
+        Self {
+            excludes: Vec::new(),
+            _mode: std::marker::PhantomData,
+        }
+    }
+
+    /// Add a bicycle exclude to the set. Duplicates are ignored.
+    pub fn push(self, exclude: BicycleExclude) -> Self {
+        self.with(Exclude::Bicycle(exclude))
+    }
+
+    /// Exclude ferries.
+    pub fn ferry(self) -> Self {
+        self.push(BicycleExclude::Ferry)
+    }
+}
+
+/// Errors from reading points out of GeoJSON with
+/// [`points_from_feature_collection`].
+#[cfg(feature = "serde")]
+#[derive(thiserror::Error, Clone, Debug, PartialEq, Eq)]
+pub enum GeoJsonParseError {
+    /// The value is not a GeoJSON `FeatureCollection`.
+    #[error("Value is not a GeoJSON FeatureCollection")]
+    NotAFeatureCollection,
+    /// The feature at the index is malformed, eg. a `Point` without a
+    /// `[longitude, latitude]` coordinate pair.
+    #[error("Feature {0} is not a valid GeoJSON feature")]
+    InvalidFeature(usize),
+    /// The coordinates of the `Point` feature at the index are out of range.
+    #[error("Feature {0} has coordinates out of range")]
+    InvalidCoordinates(usize),
+}
+
+/// Extract the `Point` features of a GeoJSON `FeatureCollection`, in order.
+///
+/// Features with any other geometry type, or no geometry, are skipped. GeoJSON
+/// coordinates are longitude first and are validated as in [`Point::new`](crate::Point::new).
+///
+/// ```
+/// use osrm_interface::request_types::points_from_feature_collection;
+///
+/// let collection = serde_json::json!({
+///     "type": "FeatureCollection",
+///     "features": [{
+///         "type": "Feature",
+///         "geometry": { "type": "Point", "coordinates": [10.31655, 48.040437] },
+///         "properties": { "name": "Depot" }
+///     }]
+/// });
+/// let points = points_from_feature_collection(&collection).expect("Invalid GeoJSON");
+/// assert_eq!(points[0].as_lon_lat(), (10.31655, 48.040437));
+/// ```
+#[cfg(feature = "serde")]
+pub fn points_from_feature_collection(
+    value: &serde_json::Value,
+) -> Result<Vec<crate::Point>, GeoJsonParseError> {
+    if value.get("type").and_then(|t| t.as_str()) != Some("FeatureCollection") {
+        return Err(GeoJsonParseError::NotAFeatureCollection);
+    }
+    let features = value
+        .get("features")
+        .and_then(|f| f.as_array())
+        .ok_or(GeoJsonParseError::NotAFeatureCollection)?;
+
+    let mut points = Vec::new();
+    for (i, feature) in features.iter().enumerate() {
+        if feature.get("type").and_then(|t| t.as_str()) != Some("Feature") {
+            return Err(GeoJsonParseError::InvalidFeature(i));
+        }
+        let Some(geometry) = feature.get("geometry").filter(|g| !g.is_null()) else {
+            continue;
+        };
+        if geometry.get("type").and_then(|t| t.as_str()) != Some("Point") {
+            continue;
+        }
+        let coordinates = geometry
+            .get("coordinates")
+            .and_then(|c| c.as_array())
+            .filter(|c| c.len() >= 2)
+            .ok_or(GeoJsonParseError::InvalidFeature(i))?;
+        let (Some(longitude), Some(latitude)) = (coordinates[0].as_f64(), coordinates[1].as_f64())
+        else {
+            return Err(GeoJsonParseError::InvalidFeature(i));
+        };
+        points.push(
+            crate::Point::from_lng_lat(longitude, latitude)
+                .ok_or(GeoJsonParseError::InvalidCoordinates(i))?,
+        );
+    }
+    Ok(points)
+}
+
+/// Thin a dense GPS trace before map matching, returning the indices of the
+/// points to keep.
+///
+/// The first point is always kept, then each point is kept only if it is at
+/// least `min_spacing_m` metres (haversine) from the last kept point. Matching
+/// tends to be faster, and often more accurate, on a trace thinned to roughly
+/// one point per 10-20m. As indices are returned, parallel arrays such as the
+/// timestamps can be subset in the same way. The final point may be dropped.
+///
+/// ```
+/// use osrm_interface::{Point, request_types::downsample};
+///
+/// let trace = [
+///     Point::new(48.0, 10.0).expect("Invalid point"),
+///     Point::new(48.00001, 10.0).expect("Invalid point"),
+///     Point::new(48.0002, 10.0).expect("Invalid point"),
+/// ];
+/// let timestamps = [0u64, 1, 2];
+/// let kept = downsample(&trace, 15.0);
+/// assert_eq!(kept, vec![0, 2]);
+/// let kept_timestamps: Vec<u64> = kept.iter().map(|&i| timestamps[i]).collect();
+/// assert_eq!(kept_timestamps, vec![0, 2]);
+/// ```
+pub fn downsample(points: &[crate::Point], min_spacing_m: f64) -> Vec<usize> {
+    let mut kept: Vec<usize> = Vec::new();
+    for (i, point) in points.iter().enumerate() {
+        if kept
+            .last()
+            .is_none_or(|&last| points[last].haversine_distance(point) >= min_spacing_m)
+        {
+            kept.push(i);
+        }
+    }
+    kept
+}
+
+/// The convex hull of the points, eg. to display the area covered by a batch
+/// of stops.
+///
+/// Computed with the monotone chain algorithm, treating longitude and
+/// latitude as planar coordinates. This is fine for local areas, but not for
+/// points spanning the antimeridian or near the poles.
+///
+/// The hull is returned counter-clockwise, starting from the westernmost
+/// point, without repeating the first point. Duplicate points and points on
+/// the edges of the hull are dropped, so fewer than three points are
+/// returned if all the points are collinear.
+///
+/// ```
+/// use osrm_interface::{Point, request_types::convex_hull};
+///
+/// let points = [
+///     Point::new(0.0, 0.0).expect("Invalid point"),
+///     Point::new(0.0, 1.0).expect("Invalid point"),
+///     Point::new(1.0, 1.0).expect("Invalid point"),
+///     Point::new(1.0, 0.0).expect("Invalid point"),
+///     Point::new(0.5, 0.5).expect("Invalid point"),
+/// ];
+/// assert_eq!(convex_hull(&points).len(), 4);
+/// ```
+pub fn convex_hull(points: &[crate::Point]) -> Vec<crate::Point> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| {
+        a.longitude()
+            .total_cmp(&b.longitude())
+            .then(a.latitude().total_cmp(&b.latitude()))
+    });
+    sorted.dedup();
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    // Positive if o -> a -> b turns counter-clockwise
+    let cross = |o: &crate::Point, a: &crate::Point, b: &crate::Point| {
+        (a.longitude() - o.longitude()) * (b.latitude() - o.latitude())
+            - (a.latitude() - o.latitude()) * (b.longitude() - o.longitude())
+    };
+    let chain = |points: &mut dyn Iterator<Item = &crate::Point>| {
+        let mut chain: Vec<crate::Point> = Vec::new();
+        for point in points {
+            while chain.len() >= 2
+                && cross(&chain[chain.len() - 2], &chain[chain.len() - 1], point) <= 0.0
+            {
+                chain.pop();
+            }
+            chain.push(*point);
+        }
+        // The last point of each chain is the first point of the other
+        chain.pop();
+        chain
+    };
+    let mut hull = chain(&mut sorted.iter());
+    hull.extend(chain(&mut sorted.iter().rev()));
+    hull
+}
+
+/// Collapse nearby points, eg. several stops at one building, to reduce the
+/// size of a table request.
+///
+/// Returns the representatives of the clusters, and for each point the index
+/// of its representative. Clusters are formed greedily in input order: each
+/// point joins the first representative within `radius_m` metres (haversine),
+/// otherwise it becomes a new representative. So every point is within
+/// `radius_m` of its representative, but points within `radius_m` of each
+/// other may land in different clusters if they straddle two
+/// representatives.
+///
+/// Lookups are linear in the number of clusters.
+///
+/// ```
+/// use osrm_interface::{Point, request_types::cluster};
+///
+/// let points = [
+///     Point::new(48.0, 10.0).expect("Invalid point"),
+///     Point::new(49.0, 10.0).expect("Invalid point"),
+///     Point::new(48.00001, 10.0).expect("Invalid point"),
+/// ];
+/// let (representatives, assignment) = cluster(&points, 5.0);
+/// assert_eq!(representatives, [points[0], points[1]]);
+/// assert_eq!(assignment, [0, 1, 0]);
+///
+/// // Run the table on the representatives, then expand the results back
+/// let durations = [[0.0, 10.0], [10.0, 0.0]];
+/// let duration = |i: usize, j: usize| durations[assignment[i]][assignment[j]];
+/// assert_eq!(duration(2, 1), 10.0);
+/// ```
+pub fn cluster(points: &[crate::Point], radius_m: f64) -> (Vec<crate::Point>, Vec<usize>) {
+    let mut representatives: Vec<crate::Point> = Vec::new();
+    let assignment = points
+        .iter()
+        .map(|point| {
+            representatives
+                .iter()
+                .position(|r| r.haversine_distance(point) <= radius_m)
+                .unwrap_or_else(|| {
+                    representatives.push(*point);
+                    representatives.len() - 1
+                })
+        })
+        .collect();
+    (representatives, assignment)
+}
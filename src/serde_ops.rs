@@ -0,0 +1,125 @@
+//! Lenient deserialisers for integer response fields. Some OSRM versions and
+//! forks emit whole numbers as floats (eg. `2.0`), which would otherwise fail
+//! the parse of the entire response.
+//!
+//! Also the parsing shared by the `from_json` constructors of the responses,
+//! and the defaults of fields OSRM omits.
+
+use serde::{Deserialize, Deserializer, de::DeserializeOwned, de::Error};
+
+use crate::errors::OsrmError;
+use crate::osrm_response_types::Metadata;
+
+/// The fields common to every OSRM response, including error responses
+/// which contain nothing else.
+#[derive(Deserialize)]
+struct ResponseStatus {
+    code: String,
+    message: Option<String>,
+}
+
+/// Parse a response, reporting responses with a code other than `"Ok"` as
+/// [`OsrmError::Service`]. Error responses from OSRM lack the fields of the
+/// response type, so the status is only parsed separately when the full
+/// parse fails or the code is not `"Ok"`.
+pub(crate) fn from_json<T: DeserializeOwned>(
+    json: &str,
+    code: impl Fn(&T) -> &str,
+) -> Result<T, OsrmError> {
+    match serde_json::from_str::<T>(json) {
+        Ok(response) if code(&response) == "Ok" => Ok(response),
+        Ok(response) => Err(service_error(
+            code(&response).to_string(),
+            serde_json::from_str::<ResponseStatus>(json)
+                .ok()
+                .and_then(|status| status.message)
+                .unwrap_or_default(),
+        )),
+        Err(e) => match serde_json::from_str::<ResponseStatus>(json) {
+            Ok(status) if status.code != "Ok" => Err(service_error(
+                status.code,
+                status.message.unwrap_or_default(),
+            )),
+            _ => Err(e.into()),
+        },
+    }
+}
+
+/// The [`OsrmError::Service`] for the body of an error response, if it is
+/// one. Used where the body is not otherwise parsed, eg. when the status code
+/// already marks the response as failed.
+#[cfg(any(feature = "remote", feature = "native", feature = "wasm"))]
+pub(crate) fn error_response(json: &str) -> Option<OsrmError> {
+    let status = serde_json::from_str::<ResponseStatus>(json).ok()?;
+    (status.code != "Ok").then(|| service_error(status.code, status.message.unwrap_or_default()))
+}
+
+/// The [`OsrmError::Service`] for an error response. OSRM reports the
+/// coordinate which failed to snap at the end of the message of `NoSegment`
+/// responses, eg. `"Could not find a matching segment for coordinate 3"`.
+fn service_error(code: String, message: String) -> OsrmError {
+    let failed_coordinate_index = (code == "NoSegment")
+        .then(|| message.rsplit_once("coordinate ")?.1.trim().parse().ok())
+        .flatten();
+    OsrmError::Service {
+        code,
+        message,
+        failed_coordinate_index,
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum IntOrFloat {
+    Int(u64),
+    Float(f64),
+}
+
+impl IntOrFloat {
+    /// Floats are accepted only when they are whole numbers, eg. `2.0`.
+    /// Fractional, negative, non-finite and out of range values are rejected.
+    fn into_integer<T: TryFrom<u64>, E: Error>(self) -> Result<T, E> {
+        let value = match self {
+            Self::Int(v) => v,
+            Self::Float(v) if v.fract() == 0.0 && v >= 0.0 && v <= u64::MAX as f64 => v as u64,
+            Self::Float(v) => return Err(E::custom(format!("{v} is not a valid integer"))),
+        };
+        T::try_from(value).map_err(|_| E::custom(format!("{value} is out of range")))
+    }
+}
+
+pub(crate) fn integer<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<u64>,
+{
+    IntOrFloat::deserialize(deserializer)?.into_integer()
+}
+
+pub(crate) fn option_integer<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<u64>,
+{
+    Option::<IntOrFloat>::deserialize(deserializer)?
+        .map(IntOrFloat::into_integer)
+        .transpose()
+}
+
+pub(crate) fn vec_integer<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: TryFrom<u64>,
+{
+    Vec::<IntOrFloat>::deserialize(deserializer)?
+        .into_iter()
+        .map(IntOrFloat::into_integer)
+        .collect()
+}
+
+/// The metadata of an annotation without `datasources`, which OSRM omits.
+pub(crate) fn empty_metadata() -> Metadata {
+    Metadata {
+        datasource_names: Vec::new(),
+    }
+}
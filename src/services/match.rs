@@ -1,13 +1,21 @@
 //! Given a set of coordinates (and optionally timestamps) determine
 //! the likely route taken. Matching those coordinates to a route.
 
+use std::borrow::Cow;
+
+use itertools::Itertools;
 use thiserror::Error;
 
 use crate::{
     Point,
-    osrm_response_types::{MatchRoute, MatchWaypoint},
-    request_types::{Bearing, Exclude, GeometryType, OverviewZoom, Snapping},
-    services::{Approach, DimensionMismatch},
+    osrm_response_types::{GeoJsonLineString, Geometry, MatchRoute, MatchWaypoint, ResponseCode},
+    request_types::{
+        Annotations, Bearing, Exclude, ExcludeSet, GeometryType, OverviewZoom, Snapping,
+    },
+    services::{
+        Approach, DimensionMismatch, Location, UrlParts, split_locations, unconstrained_approaches,
+        url_approach, url_bearing, url_coordinates, url_exclude, url_hint, url_list, url_radius,
+    },
 };
 
 /// The request object passed to the match service. Constructed
@@ -18,11 +26,11 @@ use crate::{
 #[derive(Clone, Debug)]
 #[allow(dead_code)]
 pub struct MatchRequest<'a> {
-    pub(crate) points: &'a [Point],
+    pub(crate) points: Cow<'a, [Point]>,
     pub(crate) steps: bool,
     pub(crate) geometry: GeometryType,
     pub(crate) overview: OverviewZoom,
-    pub(crate) annotations: bool,
+    pub(crate) annotations: Annotations,
     pub(crate) timestamps: Option<&'a [u64]>,
     pub(crate) gaps: MatchGapsBehaviour,
     pub(crate) tidy: bool,
@@ -30,13 +38,114 @@ pub struct MatchRequest<'a> {
     pub(crate) bearings: Option<&'a [Option<Bearing>]>,
     pub(crate) radiuses: Option<&'a [Option<f64>]>,
     pub(crate) generate_hints: bool,
-    pub(crate) hints: Option<&'a [Option<&'a str>]>,
+    pub(crate) hints: Option<Cow<'a, [Option<&'a str>]>>,
     pub(crate) approaches: Option<&'a [Approach]>,
     pub(crate) exclude: Option<&'a [Exclude]>,
     pub(crate) snapping: Option<Snapping>,
     pub(crate) skip_waypoints: bool,
 }
 
+impl<'a> MatchRequest<'a> {
+    /// The path and query of the url for the request, for sending it with a
+    /// custom HTTP client. See
+    /// [`RouteRequest::to_url_parts`](crate::route::RouteRequest::to_url_parts).
+    pub fn to_url_parts(&self, profile: &str, version: &str, format: Option<&str>) -> UrlParts {
+        let coordinates = url_coordinates(self.points.iter());
+        let mut parts = UrlParts::new("match", version, profile, &coordinates, format);
+        parts.push("steps", self.steps);
+        parts.push("geometries", self.geometry.url_form());
+        parts.push("overview", self.overview.url_form());
+        parts.push("annotations", self.annotations.url_form());
+        parts.push("gaps", self.gaps.url_form());
+        parts.push("tidy", self.tidy);
+        parts.push("generate_hints", self.generate_hints);
+        parts.push("skip_waypoints", self.skip_waypoints);
+
+        if let Some(timestamps) = self.timestamps {
+            parts.push("timestamps", url_list(timestamps, u64::to_string));
+        }
+        if let Some(waypoints) = self.waypoints {
+            parts.push("waypoints", url_list(waypoints, usize::to_string));
+        }
+        if let Some(bearings) = self.bearings {
+            parts.push("bearings", url_list(bearings, url_bearing));
+        }
+        if let Some(radiuses) = self.radiuses {
+            parts.push("radiuses", url_list(radiuses, url_radius));
+        }
+        if let Some(hints) = &self.hints {
+            parts.push("hints", url_list(hints, url_hint));
+        }
+        if let Some(approaches) = self.approaches {
+            parts.push("approaches", url_list(approaches, url_approach));
+        }
+        if let Some(exclude) = self.exclude {
+            parts.push("exclude", url_exclude(exclude));
+        }
+        if let Some(snapping) = self.snapping {
+            parts.push("snapping", snapping.url_form());
+        }
+
+        parts
+    }
+
+    /// Split the request into sub-traces at the given point indices, eg. those
+    /// from [`MatchResponse::ambiguous_split_points`], for incremental map
+    /// matching. Each split point ends one sub-trace and starts the next, so
+    /// consecutive sub-traces join up.
+    ///
+    /// Per-point options, such as timestamps and bearings, are split along
+    /// with the points, all other options are kept. `waypoints` are dropped,
+    /// treating every point of a sub-trace as a waypoint, as the indices no
+    /// longer apply.
+    ///
+    /// Indices are sorted and deduplicated, and those of the first and last
+    /// points, or out of bounds, are ignored as they would not split the trace.
+    /// Returns a single sub-trace equal to the request, less its waypoints,
+    /// when there is nothing to split at.
+    ///
+    /// # Panics
+    ///
+    /// If a per-point option is shorter than the points, which
+    /// [`build`](MatchRequestBuilder::build) rules out.
+    pub fn split_at(&self, indices: &[usize]) -> Vec<MatchRequest<'a>> {
+        let len_points = self.points.len();
+        let boundaries = std::iter::once(0)
+            .chain(
+                indices
+                    .iter()
+                    .copied()
+                    .filter(|&i| i > 0 && i + 1 < len_points)
+                    .sorted_unstable()
+                    .dedup(),
+            )
+            .chain(std::iter::once(len_points.saturating_sub(1)));
+
+        boundaries
+            .tuple_windows()
+            .map(|(start, end)| {
+                let range = start..end + 1;
+                MatchRequest {
+                    points: match &self.points {
+                        Cow::Borrowed(points) => Cow::Borrowed(&points[range.clone()]),
+                        Cow::Owned(points) => Cow::Owned(points[range.clone()].to_vec()),
+                    },
+                    timestamps: self.timestamps.map(|t| &t[range.clone()]),
+                    waypoints: None,
+                    bearings: self.bearings.map(|b| &b[range.clone()]),
+                    radiuses: self.radiuses.map(|r| &r[range.clone()]),
+                    hints: self.hints.as_ref().map(|hints| match hints {
+                        Cow::Borrowed(hints) => Cow::Borrowed(&hints[range.clone()]),
+                        Cow::Owned(hints) => Cow::Owned(hints[range.clone()].to_vec()),
+                    }),
+                    approaches: self.approaches.map(|a| &a[range.clone()]),
+                    ..self.clone()
+                }
+            })
+            .collect()
+    }
+}
+
 /// Helper struct for building a [`MatchRequest`].
 ///
 /// Set options using the struct methods before calling
@@ -58,8 +167,8 @@ pub struct MatchRequest<'a> {
 /// - **`overview`** (*default:* `OverviewZoom::Simplified`) — Controls the
 ///   generalization level of the route overview geometry. See [`OverviewZoom`].
 ///
-/// - **`annotations`** (*default:* `false`) — When enabled, includes metadata such as
-///   distance, duration, and speed values for each segment.
+/// - **`annotations`** (*default:* `Annotations::NONE`) — Which metadata, such as
+///   distance, duration and speed, to include for each segment. See [`Annotations`].
 ///
 /// - **`gaps`** (*default:* `MatchGapsBehaviour::Split`) — Defines how to handle gaps
 ///   in GPS traces. Options are:
@@ -132,11 +241,11 @@ pub struct MatchRequest<'a> {
 /// ```
 #[derive(Clone, Debug)]
 pub struct MatchRequestBuilder<'a> {
-    points: &'a [Point],
+    points: Cow<'a, [Point]>,
     steps: bool,
     geometry: GeometryType,
     overview: OverviewZoom,
-    annotations: bool,
+    annotations: Annotations,
     timestamps: Option<&'a [u64]>,
     gaps: MatchGapsBehaviour,
     tidy: bool,
@@ -144,11 +253,12 @@ pub struct MatchRequestBuilder<'a> {
     bearings: Option<&'a [Option<Bearing>]>,
     radiuses: Option<&'a [Option<f64>]>,
     generate_hints: bool,
-    hints: Option<&'a [Option<&'a str>]>,
+    hints: Option<Cow<'a, [Option<&'a str>]>>,
     approaches: Option<&'a [Approach]>,
     exclude: Option<&'a [Exclude]>,
     snapping: Option<Snapping>,
     skip_waypoints: bool,
+    strict: bool,
 }
 
 impl<'a> MatchRequestBuilder<'a> {
@@ -157,11 +267,11 @@ impl<'a> MatchRequestBuilder<'a> {
     /// Default values are applied to all other options.
     pub fn new(points: &'a [Point]) -> Self {
         Self {
-            points,
+            points: Cow::Borrowed(points),
             geometry: GeometryType::Polyline,
             overview: OverviewZoom::Simplified,
             steps: false,
-            annotations: false,
+            annotations: Annotations::NONE,
             timestamps: None,
             gaps: MatchGapsBehaviour::Split,
             tidy: false,
@@ -174,6 +284,65 @@ impl<'a> MatchRequestBuilder<'a> {
             exclude: None,
             snapping: None,
             skip_waypoints: false,
+            strict: false,
+        }
+    }
+
+    /// Creates a new [`MatchRequestBuilder`] through the given [`Location`]s,
+    /// sending the hints of any hinted locations. See [`Location`] for when
+    /// OSRM uses the hints.
+    pub fn from_locations(locations: &[Location<'a>]) -> Self {
+        let (points, hints) = split_locations(locations);
+        Self {
+            points: Cow::Owned(points),
+            hints: hints.map(Cow::Owned),
+            ..Self::new(&[])
+        }
+    }
+
+    /// Creates a new [`MatchRequestBuilder`] with default parameters and no points.
+    ///
+    /// Allows options to be configured before the points are known.
+    /// [`points`](Self::points) must be called before [`build`](Self::build),
+    /// otherwise `build` will return `InsufficientPoints`.
+    pub fn empty() -> Self {
+        Self::new(&[])
+    }
+
+    /// Creates a new [`MatchRequestBuilder`] from a trace prepared with
+    /// [`MatchTrace::new`], setting the points, timestamps and bearings.
+    ///
+    /// If the trace has no timestamps, `gaps` is set to
+    /// [`MatchGapsBehaviour::Ignore`] as splitting requires timestamps.
+    ///
+    /// ```
+    /// use osrm_interface::{
+    ///     Point,
+    ///     r#match::{MatchRequestBuilder, MatchTrace, TraceOptions},
+    /// };
+    ///
+    /// let points = [
+    ///     Point::new(48.0, 10.0).expect("Invalid point"),
+    ///     Point::new(48.0, 10.0).expect("Invalid point"),
+    ///     Point::new(48.001, 10.0).expect("Invalid point"),
+    /// ];
+    /// let timestamps = [0, 1, 10];
+    /// let trace = MatchTrace::new(&points, Some(&timestamps), None, TraceOptions::default())
+    ///     .expect("Invalid trace");
+    /// assert_eq!(trace.timestamps(), Some(&[0, 10][..]));
+    /// let match_request = MatchRequestBuilder::from_trace(&trace)
+    ///     .build()
+    ///     .expect("Failed to build MatchRequest");
+    /// ```
+    pub fn from_trace(trace: &'a MatchTrace) -> Self {
+        let builder = Self::new(&trace.points);
+        let builder = match &trace.timestamps {
+            Some(timestamps) => builder.timestamps(timestamps),
+            None => builder.gaps(MatchGapsBehaviour::Ignore),
+        };
+        match &trace.bearings {
+            Some(bearings) => builder.bearings(bearings),
+            None => builder,
         }
     }
 
@@ -183,7 +352,7 @@ impl<'a> MatchRequestBuilder<'a> {
     /// Take care that array-like options are still the same length as points,
     /// [`build`](Self::build) will throw an error if not.
     pub fn points(mut self, points: &'a [Point]) -> Self {
-        self.points = points;
+        self.points = Cow::Borrowed(points);
         self
     }
 
@@ -193,9 +362,10 @@ impl<'a> MatchRequestBuilder<'a> {
         self
     }
 
-    /// Sets whether to include per-segment annotations in the route.
-    pub fn annotations(mut self, include_annotations: bool) -> Self {
-        self.annotations = include_annotations;
+    /// Sets the per-segment annotations to include with each leg, either
+    /// [`Annotations`] or a `bool` to include all or none of them.
+    pub fn annotations(mut self, annotations: impl Into<Annotations>) -> Self {
+        self.annotations = annotations.into();
         self
     }
 
@@ -261,7 +431,12 @@ impl<'a> MatchRequestBuilder<'a> {
         self
     }
 
-    /// Sets whether to include generated location hints in the response.
+    /// Sets whether OSRM generates hints for the tracepoints in the response.
+    /// Enabled by default, as in OSRM.
+    ///
+    /// Hints are needed to pass the snapped tracepoints to later requests, see
+    /// [`hints`](Self::hints). Disable them to reduce the response size
+    /// otherwise.
     pub fn generate_hints(mut self, generate_hints: bool) -> Self {
         self.generate_hints = generate_hints;
         self
@@ -275,7 +450,7 @@ impl<'a> MatchRequestBuilder<'a> {
     /// Passing hints will result in radiuses, bearings,
     /// approaches being ignored for that point.
     pub fn hints(mut self, coordinate_hints: &'a [Option<&'a str>]) -> Self {
-        self.hints = Some(coordinate_hints);
+        self.hints = Some(Cow::Borrowed(coordinate_hints));
         self
     }
 
@@ -293,6 +468,17 @@ impl<'a> MatchRequestBuilder<'a> {
         self
     }
 
+    /// Sets which road classes should be excluded from the request using
+    /// an [`ExcludeSet`], which guarantees at compile time that all
+    /// excludes belong to the same transport mode.
+    ///
+    /// Equivalent to calling [`exclude`](Self::exclude) with
+    /// [`ExcludeSet::as_slice`].
+    pub fn exclude_set<M>(mut self, exclude_set: &'a ExcludeSet<M>) -> Self {
+        self.exclude = Some(exclude_set.as_slice());
+        self
+    }
+
     /// Sets the snapping behavior for input coordinates.
     pub fn snapping(mut self, snapping: Snapping) -> Self {
         self.snapping = Some(snapping);
@@ -369,7 +555,7 @@ impl<'a> MatchRequestBuilder<'a> {
         }
 
         #[allow(clippy::collapsible_if)]
-        if let Some(hints) = self.hints {
+        if let Some(hints) = &self.hints {
             if hints.len() != self.points.len() {
                 return Err(MatchRequestError::DimensionMismatch(
                     DimensionMismatch::Hints,
@@ -398,8 +584,42 @@ impl<'a> MatchRequestBuilder<'a> {
             }
         }
 
-        Ok(MatchRequest {
-            points: self.points,
+        let unconstrained = unconstrained_approaches(
+            self.approaches,
+            self.bearings.is_some() || self.radiuses.is_some(),
+        );
+        if unconstrained && self.strict {
+            return Err(MatchRequestError::UnconstrainedApproaches);
+        }
+        #[cfg(feature = "log")]
+        if unconstrained {
+            crate::services::warn_unconstrained_approaches();
+        }
+
+        Ok(self.build_unchecked())
+    }
+
+    /// Enables strict validation in [`build`](Self::build), turning checks which
+    /// are otherwise skipped into errors. Disabled by default.
+    ///
+    /// In strict mode, setting approaches other than `Approach::Unrestricted`
+    /// without any bearings or radiuses returns `UnconstrainedApproaches`, as
+    /// some OSRM configurations reject approaches without a snapping constraint.
+    /// Otherwise, with `feature="log"`, a warning is logged through the `log`
+    /// crate.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Builds a [`MatchRequest`] without validating the configured parameters.
+    ///
+    /// A performance escape hatch for hot loops over inputs which are already
+    /// known to be valid. As no checks are performed, the resulting request may
+    /// be rejected by OSRM. Prefer [`build`](Self::build) otherwise.
+    pub fn build_unchecked(&self) -> MatchRequest<'a> {
+        MatchRequest {
+            points: self.points.clone(),
             steps: self.steps,
             geometry: self.geometry,
             overview: self.overview,
@@ -411,13 +631,158 @@ impl<'a> MatchRequestBuilder<'a> {
             bearings: self.bearings,
             radiuses: self.radiuses,
             generate_hints: self.generate_hints,
-            hints: self.hints,
+            hints: self.hints.clone(),
             approaches: self.approaches,
             exclude: self.exclude,
             snapping: self.snapping,
             skip_waypoints: self.skip_waypoints,
+        }
+    }
+}
+
+/// Options for preparing a raw GPS trace with [`MatchTrace::new`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TraceOptions {
+    min_spacing_m: f64,
+    bearing_range: i16,
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        Self {
+            min_spacing_m: 10.0,
+            bearing_range: 45,
+        }
+    }
+}
+
+impl TraceOptions {
+    /// Sets the minimum spacing in metres between kept points, see
+    /// [`downsample`](crate::request_types::downsample). Defaults to 10m, use
+    /// 0.0 to keep every distinct point.
+    pub fn min_spacing(mut self, min_spacing_m: f64) -> Self {
+        self.min_spacing_m = min_spacing_m;
+        self
+    }
+
+    /// Sets the range in degrees either side of each heading within which
+    /// OSRM may snap, clamped to `[0,180]`. Defaults to 45.
+    pub fn bearing_range(mut self, bearing_range: i16) -> Self {
+        self.bearing_range = bearing_range.clamp(0, 180);
+        self
+    }
+}
+
+/// A GPS trace cleaned up for the match service, from which a request is
+/// built with [`MatchRequestBuilder::from_trace`].
+///
+/// [`new`](Self::new) runs the usual preprocessing in one call:
+///
+/// 1. Checks that timestamps and headings have one element per point and
+///    that the timestamps are sorted.
+/// 2. Drops consecutive duplicate points, as produced by a stationary
+///    receiver.
+/// 3. Thins the trace with [`downsample`](crate::request_types::downsample),
+///    always keeping the final point.
+/// 4. Converts headings, in degrees clockwise from north, to [`Bearing`]s.
+///    Non-finite headings leave the point unconstrained.
+///
+/// The builder borrows its inputs, so the trace owns the processed arrays.
+#[derive(Clone, Debug)]
+pub struct MatchTrace {
+    points: Vec<Point>,
+    timestamps: Option<Vec<u64>>,
+    bearings: Option<Vec<Option<Bearing>>>,
+    indices: Vec<usize>,
+}
+
+impl MatchTrace {
+    /// Validates and normalises a raw trace, see [`MatchTrace`].
+    ///
+    /// Returns `DimensionMismatch` if `timestamps` or `headings` do not have
+    /// one element per point, `TimestampsNotSorted` if the timestamps
+    /// decrease and `InsufficientPoints` if fewer than two points remain.
+    pub fn new(
+        points: &[Point],
+        timestamps: Option<&[u64]>,
+        headings: Option<&[f64]>,
+        options: TraceOptions,
+    ) -> Result<Self, MatchRequestError> {
+        if let Some(timestamps) = timestamps {
+            if timestamps.len() != points.len() {
+                return Err(MatchRequestError::DimensionMismatch(
+                    DimensionMismatch::Timestamps,
+                ));
+            }
+            if !timestamps.is_sorted() {
+                return Err(MatchRequestError::TimestampsNotSorted);
+            }
+        }
+        if headings.is_some_and(|headings| headings.len() != points.len()) {
+            return Err(MatchRequestError::DimensionMismatch(
+                DimensionMismatch::Bearings,
+            ));
+        }
+
+        let deduped: Vec<usize> = (0..points.len())
+            .filter(|&i| i == 0 || points[i] != points[i - 1])
+            .collect();
+        let deduped_points: Vec<Point> = deduped.iter().map(|&i| points[i]).collect();
+        let mut indices: Vec<usize> =
+            crate::request_types::downsample(&deduped_points, options.min_spacing_m)
+                .into_iter()
+                .map(|i| deduped[i])
+                .collect();
+        // Keep the end of the trace, replacing the last kept point so the
+        // spacing is not much reduced
+        let end = deduped.last().copied();
+        if end.is_some_and(|end| indices.last() != Some(&end)) {
+            if indices.len() > 1 {
+                indices.pop();
+            }
+            indices.extend(end);
+        }
+        if indices.len() < 2 {
+            return Err(MatchRequestError::InsufficientPoints);
+        }
+
+        let bearing = |heading: f64| {
+            heading.is_finite().then(|| {
+                Bearing::new_unchecked(
+                    heading.round().rem_euclid(360.0) as i16,
+                    options.bearing_range,
+                )
+            })
+        };
+        Ok(Self {
+            points: indices.iter().map(|&i| points[i]).collect(),
+            timestamps: timestamps.map(|t| indices.iter().map(|&i| t[i]).collect()),
+            bearings: headings.map(|h| indices.iter().map(|&i| bearing(h[i])).collect()),
+            indices,
         })
     }
+
+    /// The points kept from the raw trace.
+    pub fn points(&self) -> &[Point] {
+        &self.points
+    }
+
+    /// The timestamps of the kept points, if timestamps were given.
+    pub fn timestamps(&self) -> Option<&[u64]> {
+        self.timestamps.as_deref()
+    }
+
+    /// The bearings derived from the headings of the kept points, if headings
+    /// were given.
+    pub fn bearings(&self) -> Option<&[Option<Bearing>]> {
+        self.bearings.as_deref()
+    }
+
+    /// For each kept point, its index in the raw trace. Useful for mapping
+    /// tracepoints in the response back to the raw trace.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
 }
 
 /// The comprehensive error type returned when attempting to
@@ -425,30 +790,44 @@ impl<'a> MatchRequestBuilder<'a> {
 #[derive(Error, Debug)]
 pub enum MatchRequestError {
     /// Match requires at least 2 points
-    #[error("Match requires at least 2 points")]
+    #[error("Match requires at least 2 points; pass at least a start and an end point")]
     InsufficientPoints,
     /// Mismatch of number of elements between points and one
     /// of the array-like options.
-    #[error("Mismatch of dimensions between Points and {0:?}")]
+    #[error("Mismatch of dimensions between Points and {0:?}; {hint}", hint = .0.hint())]
     DimensionMismatch(DimensionMismatch),
     /// Timestamps must be in increasing order.
-    #[error("Timestamps must be increasing order")]
+    #[error("Timestamps must be increasing order; sort the points and timestamps by time")]
     TimestampsNotSorted,
     /// To use `GapsBehaviour::Split`, timestamps must be provided.
-    #[error("Timestamps must be included for GapsBehaviour::Split")]
+    #[error(
+        "Timestamps must be included for GapsBehaviour::Split; pass timestamps or use `MatchGapsBehaviour::Ignore`"
+    )]
     TimestampsRequiredForSplitBehaviour,
     /// If waypoints is specified as Some(), it may not be empty.
-    #[error("Waypoints when non-None must have non-zero length")]
+    #[error(
+        "Waypoints when non-None must have non-zero length; leave waypoints unset to treat every point as a waypoint"
+    )]
     EmptyWaypoints,
     /// Waypoint values must be in bounds of the points array.
-    #[error("Waypoints contain index {0} which is out of bounds for points with size {1}")]
+    #[error(
+        "Waypoints contain index {0} which is out of bounds for points with size {1}; indices must be less than {1}"
+    )]
     WaypointIndexOutOfBounds(usize, usize),
     /// Cannot mix excludes of different [`Exclude`] variants.
-    #[error("Exclude types are not all of the same type")]
+    #[error(
+        "Exclude types are not all of the same type; use the excludes of a single profile, eg. only `CarExclude`"
+    )]
     DifferentExcludeTypes,
     /// Radius values must be non-negative.
-    #[error("Radii must be non-negative")]
+    #[error("Radii must be non-negative; pass `None` for an unlimited radius")]
     NegativeRadius,
+    /// In strict mode, approaches must be accompanied by bearings or
+    /// radiuses to constrain snapping.
+    #[error(
+        "Approaches are set without bearings or radiuses to constrain snapping; set bearings or radiuses, or disable strict mode"
+    )]
+    UnconstrainedApproaches,
 }
 
 /// If there are large gaps in the timestamps (>60s), allow
@@ -499,3 +878,100 @@ pub struct MatchResponse {
     /// An array of [`MatchRoute`] objects that assemble the trace.
     pub matchings: Vec<MatchRoute>,
 }
+
+#[cfg(feature = "serde")]
+impl MatchResponse {
+    /// Parse the JSON body of a response returned by OSRM. See
+    /// [`RouteResponse::from_json`](crate::route::RouteResponse::from_json).
+    pub fn from_json(json: &str) -> Result<Self, crate::errors::OsrmError> {
+        crate::serde_ops::from_json(json, |response: &Self| &response.code)
+    }
+}
+
+impl MatchResponse {
+    /// The [`code`](Self::code) of the response as a [`ResponseCode`]. See
+    /// [`RouteResponse::code_enum`](crate::route::RouteResponse::code_enum).
+    pub fn code_enum(&self) -> ResponseCode {
+        ResponseCode::from(self.code.as_str())
+    }
+
+    /// Concatenate the geometries of all matchings, in order, into a single
+    /// GeoJSON LineString. Useful for displaying a trace split into several
+    /// sub-traces as one line.
+    ///
+    /// Gaps between sub-traces appear as straight segments joining the end of
+    /// one matching to the start of the next. Where consecutive matchings share
+    /// an endpoint, it is included only once.
+    ///
+    /// Pass the `geometry_type` the request was built with, as for
+    /// [`Geometry::decode`]. Matchings without geometry, ie. when requested
+    /// with `overview=false`, or which cannot be decoded with `geometry_type`
+    /// are skipped.
+    pub fn combined_geometry(&self, geometry_type: GeometryType) -> Geometry {
+        let mut coordinates: Vec<[f64; 2]> = Vec::new();
+        for vertices in self
+            .matchings
+            .iter()
+            .filter_map(|m| m.geometry.as_ref()?.decode(geometry_type).ok())
+        {
+            for vertex in vertices {
+                let coordinate = [vertex.longitude(), vertex.latitude()];
+                if coordinates.last() != Some(&coordinate) {
+                    coordinates.push(coordinate);
+                }
+            }
+        }
+        Geometry::GeoJson(GeoJsonLineString {
+            r#type: "LineString".to_string(),
+            coordinates,
+        })
+    }
+
+    /// Pair each input `Point` with the tracepoint it was matched to.
+    ///
+    /// `input` should be the points slice passed to the request. Tracepoints
+    /// omitted as outliers are paired with `None`. Allows computing the snap
+    /// distance of each point or identifying dropped points.
+    ///
+    /// If `input` and `tracepoints` differ in length, the result is truncated
+    /// to the shorter of the two.
+    pub fn tracepoint_pairs<'a>(
+        &'a self,
+        input: &'a [Point],
+    ) -> Vec<(Point, Option<&'a MatchWaypoint>)> {
+        input
+            .iter()
+            .zip(self.tracepoints.iter())
+            .map(|(p, t)| (*p, t.as_ref()))
+            .collect()
+    }
+
+    /// The indices of the tracepoints which were matched ambiguously, ie. with
+    /// a non-zero [`alternatives_count`](MatchWaypoint::alternatives_count).
+    ///
+    /// OSRM suggests splitting the trace at these points for incremental map
+    /// matching, see [`MatchRequest::split_at`]. Tracepoints omitted as
+    /// outliers are never included.
+    pub fn ambiguous_split_points(&self) -> Vec<usize> {
+        self.tracepoints
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.as_ref().is_some_and(|t| t.alternatives_count > 0))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Whether every matched tracepoint refers to an existing matching, and to
+    /// a waypoint within it. A matching with `n` legs has `n + 1` waypoints.
+    ///
+    /// Intended as a debug check after transforming a response, where stale
+    /// indices are easy to introduce and hard to notice.
+    pub fn validate_indices(&self) -> bool {
+        self.tracepoints.iter().flatten().all(|t| {
+            usize::try_from(t.matchings_index)
+                .ok()
+                .and_then(|i| self.matchings.get(i))
+                .is_some_and(|m| t.waypoint_index <= m.legs.len() as u64)
+        })
+    }
+}
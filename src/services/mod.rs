@@ -9,6 +9,542 @@ pub mod route;
 pub mod table;
 pub mod trip;
 
+use itertools::Itertools;
+
+use crate::Point;
+use crate::errors::OsrmError;
+use crate::request_types::{Bearing, Exclude};
+use r#match::{MatchRequest, MatchRequestBuilder, MatchResponse};
+use nearest::{NearestRequest, NearestRequestBuilder, NearestResponse};
+use route::{RouteRequest, RouteRequestBuilder, RouteResponse, SimpleRouteResponse};
+use table::{TableAnnotation, TableRequest, TableRequestBuilder, TableResponse};
+use trip::{
+    TripDestination, TripImprovement, TripRequest, TripRequestBuilder, TripResponse, TripSource,
+};
+
+/// The services shared by all engines, allowing code to be written
+/// generically over the engine used.
+///
+/// The engines also provide these as inherent methods, so the trait
+/// only needs to be imported when writing generic code or calling the
+/// provided helper methods.
+///
+/// ```
+/// use osrm_interface::{Point, services::OsrmService};
+///
+/// fn is_circuitous<E: OsrmService>(engine: &E, from: Point, to: Point) -> bool {
+///     engine.detour_factor(from, to).is_ok_and(|f| f > 2.0)
+/// }
+///
+/// let engine = osrm_interface::mock::OsrmEngine::new();
+/// let from = Point::new(48.040437, 10.316550).expect("Invalid point");
+/// let to = Point::new(49.006101, 9.052887).expect("Invalid point");
+/// assert!(!is_circuitous(&engine, from, to));
+/// ```
+pub trait OsrmService {
+    /// See the `table` method of the engines.
+    fn table(&self, table_request: &TableRequest) -> Result<TableResponse, OsrmError>;
+
+    /// See the `route` method of the engines.
+    fn route(&self, route_request: &RouteRequest) -> Result<RouteResponse, OsrmError>;
+
+    /// See the `trip` method of the engines.
+    fn trip(&self, trip_request: &TripRequest) -> Result<TripResponse, OsrmError>;
+
+    /// See the `simple_route` method of the engines.
+    fn simple_route(&self, from: Point, to: Point) -> Result<SimpleRouteResponse, OsrmError>;
+
+    /// See the `nearest` method of the engines.
+    fn nearest(&self, nearest_request: &NearestRequest) -> Result<NearestResponse, OsrmError>;
+
+    /// See the `match` method of the engines.
+    fn r#match(&self, match_request: &MatchRequest) -> Result<MatchResponse, OsrmError>;
+
+    /// The ratio of the route distance between two points to their crow-fly
+    /// (haversine) distance. Useful for flagging suspiciously circuitous routes.
+    ///
+    /// Costs one [`simple_route`](Self::simple_route) call. When the points
+    /// coincide, the crow-fly distance is zero and the factor is reported as
+    /// `1.0`.
+    fn detour_factor(&self, from: Point, to: Point) -> Result<f64, OsrmError> {
+        let crow_fly = from.haversine_distance(&to);
+        if crow_fly < f64::EPSILON {
+            return Ok(1.0);
+        }
+        let response = self.simple_route(from, to)?;
+        Ok(response.distance / crow_fly)
+    }
+
+    /// Whether `point` is likely within the map loaded by the backend, judged
+    /// by whether it snaps to the road network within `max_snap_m` meters.
+    ///
+    /// Snapping is not limited by distance, so a point outside of the map
+    /// still snaps to the closest road inside it, often hundreds of
+    /// kilometers away. Costs one [`nearest`](Self::nearest) call for a
+    /// single candidate. A point in a remote area of the map may also be
+    /// rejected, so choose the threshold to suit the density of the roads.
+    fn is_on_map(&self, point: Point, max_snap_m: f64) -> Result<bool, OsrmError> {
+        let nearest_request = NearestRequestBuilder::new(&point, 1).build_unchecked();
+        let waypoint = self
+            .nearest(&nearest_request)?
+            .waypoints
+            .into_iter()
+            .next()
+            .ok_or_else(|| {
+                OsrmError::EmptyResponse("Nearest response contained no waypoints".to_string())
+            })?;
+        Ok(waypoint.distance <= max_snap_m)
+    }
+
+    /// Lazily route a stream of origin-destination pairs, issuing one
+    /// [`simple_route`](Self::simple_route) call per item as the returned
+    /// iterator is consumed. Memory use is constant in the number of pairs,
+    /// so this suits unbounded streams which shouldn't be collected first.
+    ///
+    /// An error for one pair does not end the iteration. Not available on
+    /// `dyn OsrmService`, as the method is generic over `pairs`.
+    fn route_pairs_iter<'a, I>(
+        &'a self,
+        pairs: I,
+    ) -> impl Iterator<Item = Result<SimpleRouteResponse, OsrmError>> + 'a
+    where
+        Self: Sized,
+        I: Iterator<Item = (Point, Point)> + 'a,
+    {
+        pairs.map(move |(from, to)| self.simple_route(from, to))
+    }
+
+    /// Whether every point can be routed to from every other point. Useful as
+    /// a cheap pre-check before a [`trip`](Self::trip), which requires all
+    /// points to be connected and otherwise fails with `NoTrips` after doing
+    /// the work.
+    ///
+    /// Costs one [`table`](Self::table) call of durations between all of the
+    /// points. Returns `false` if any cell of the table is `None`. Fewer than two
+    /// points are trivially connected.
+    fn check_connectivity(&self, points: &[Point]) -> Result<bool, OsrmError> {
+        if points.len() < 2 {
+            return Ok(true);
+        }
+        let table_request = TableRequestBuilder::new(points, points)
+            .annotations(TableAnnotation::Duration)
+            .build_unchecked();
+        let durations = self.table(&table_request)?.durations.ok_or_else(|| {
+            OsrmError::EmptyResponse("Table response did not contain durations".to_string())
+        })?;
+        Ok(durations.iter().flatten().all(Option::is_some))
+    }
+
+    /// Complete a partially known duration matrix between `sources` and
+    /// `destinations`, eg. one assembled from a cache of earlier results.
+    ///
+    /// `known[i][j]` is the duration in seconds from the i-th source to the
+    /// j-th destination. Cells which are `None`, or absent because a row is
+    /// short or missing, are computed and known cells are returned as given.
+    /// Cells which remain `None` could not be routed.
+    ///
+    /// OSRM cannot compute individual cells of a table, so this costs one
+    /// [`table`](Self::table) call between only the sources and destinations
+    /// with a missing cell, or no call if nothing is missing. The saving is
+    /// largest when the missing cells share a few rows or columns, eg. after
+    /// adding a point to a cached matrix.
+    fn table_fill_missing(
+        &self,
+        sources: &[Point],
+        destinations: &[Point],
+        known: &[Vec<Option<f64>>],
+    ) -> Result<Vec<Vec<Option<f64>>>, OsrmError> {
+        let mut durations: Vec<Vec<Option<f64>>> = (0..sources.len())
+            .map(|i| {
+                (0..destinations.len())
+                    .map(|j| known.get(i).and_then(|row| row.get(j)).copied().flatten())
+                    .collect()
+            })
+            .collect();
+        let missing_sources: Vec<usize> = (0..sources.len())
+            .filter(|&i| durations[i].iter().any(Option::is_none))
+            .collect();
+        let missing_destinations: Vec<usize> = (0..destinations.len())
+            .filter(|&j| durations.iter().any(|row| row[j].is_none()))
+            .collect();
+        if missing_sources.is_empty() {
+            return Ok(durations);
+        }
+
+        let table_sources: Vec<Point> = missing_sources.iter().map(|&i| sources[i]).collect();
+        let table_destinations: Vec<Point> = missing_destinations
+            .iter()
+            .map(|&j| destinations[j])
+            .collect();
+        let table_request = TableRequestBuilder::new(&table_sources, &table_destinations)
+            .annotations(TableAnnotation::Duration)
+            .build_unchecked();
+        let computed = self.table(&table_request)?.durations.ok_or_else(|| {
+            OsrmError::EmptyResponse("Table response did not contain durations".to_string())
+        })?;
+        for (row, &i) in computed.iter().zip(&missing_sources) {
+            for (&duration, &j) in row.iter().zip(&missing_destinations) {
+                if durations[i][j].is_none() {
+                    durations[i][j] = duration;
+                }
+            }
+        }
+        Ok(durations)
+    }
+
+    /// Compare the duration of an optimised trip through the points with the
+    /// duration of a route through the points in the order given.
+    ///
+    /// Costs one [`trip`](Self::trip) and one [`route`](Self::route) call. The
+    /// trip starts at the first point and ends at the last, so only the order
+    /// of the intermediate points is optimised. Fewer than two points have no
+    /// duration and no improvement.
+    fn trip_improvement(&self, points: &[Point]) -> Result<TripImprovement, OsrmError> {
+        if points.len() < 2 {
+            return Ok(TripImprovement {
+                input_order_duration: 0.0,
+                optimised_duration: 0.0,
+                improvement_percent: 0.0,
+            });
+        }
+        let trip_request = TripRequestBuilder::new(points)
+            .roundtrip(false)
+            .source(TripSource::First)
+            .destination(TripDestination::Last)
+            .build_unchecked();
+        let optimised_duration = self
+            .trip(&trip_request)?
+            .trips
+            .first()
+            .ok_or_else(|| {
+                OsrmError::EmptyResponse("Trip response contained no trips".to_string())
+            })?
+            .duration;
+
+        let route_request = RouteRequestBuilder::new(points).build_unchecked();
+        let input_order_duration = self
+            .route(&route_request)?
+            .routes
+            .first()
+            .ok_or_else(|| {
+                OsrmError::EmptyResponse("Route response contained no routes".to_string())
+            })?
+            .duration;
+
+        let improvement_percent = if input_order_duration > 0.0 {
+            100.0 * (input_order_duration - optimised_duration) / input_order_duration
+        } else {
+            0.0
+        };
+        Ok(TripImprovement {
+            input_order_duration,
+            optimised_duration,
+            improvement_percent,
+        })
+    }
+}
+
+/// A set of points shared between multiple service calls.
+///
+/// Creates the request builders of each service, pre-populated with the
+/// points, so calling several services over the same points does not require
+/// passing the slice to each builder.
+///
+/// ```
+/// use osrm_interface::{Point, services::PointSet};
+/// let points = [
+///     Point::new(48.040437, 10.316550).expect("Invalid point"),
+///     Point::new(49.006101, 9.052887).expect("Invalid point"),
+///     Point::new(48.942296, 10.510960).expect("Invalid point"),
+/// ];
+/// let points = PointSet::new(&points);
+/// let route_request = points
+///     .route()
+///     .steps(true)
+///     .build()
+///     .expect("Failed to build RouteRequest");
+/// let trip_request = points.trip().build().expect("Failed to build TripRequest");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct PointSet<'a> {
+    points: &'a [Point],
+}
+
+impl<'a> PointSet<'a> {
+    /// Creates a [`PointSet`] borrowing `points`. Builders created from the
+    /// set validate the points when built, as if constructed directly.
+    pub fn new(points: &'a [Point]) -> Self {
+        Self { points }
+    }
+
+    /// The points shared by the builders, as passed to [`new`](Self::new).
+    pub fn points(&self) -> &'a [Point] {
+        self.points
+    }
+
+    /// A [`RouteRequestBuilder`] through the points, in order.
+    pub fn route(&self) -> RouteRequestBuilder<'a> {
+        RouteRequestBuilder::new(self.points)
+    }
+
+    /// A [`TableRequestBuilder`] with the points as sources.
+    pub fn table_to(&self, destinations: &'a [Point]) -> TableRequestBuilder<'a> {
+        TableRequestBuilder::new(self.points, destinations)
+    }
+
+    /// A [`TripRequestBuilder`] visiting the points.
+    pub fn trip(&self) -> TripRequestBuilder<'a> {
+        TripRequestBuilder::new(self.points)
+    }
+
+    /// A [`MatchRequestBuilder`] treating the points as a GPS trace.
+    pub fn match_trace(&self) -> MatchRequestBuilder<'a> {
+        MatchRequestBuilder::new(self.points)
+    }
+}
+
+impl<'a> From<&'a [Point]> for PointSet<'a> {
+    fn from(points: &'a [Point]) -> Self {
+        Self::new(points)
+    }
+}
+
+/// A location passed to the route, trip and match services, either a
+/// coordinate to be snapped to the road network or a hint from a previous
+/// response, which skips snapping.
+///
+/// OSRM requires a coordinate for every location, and only uses a hint when
+/// the coordinate is exactly the input coordinate of the request which
+/// returned it. Otherwise, or when the hint is from a different dataset,
+/// the hint is ignored and the coordinate is snapped as usual. As such
+/// [`Location::Hint`] carries the input coordinate rather than a
+/// placeholder.
+///
+/// Builders are created from locations with eg.
+/// [`RouteRequestBuilder::from_locations`].
+///
+/// ```
+/// use osrm_interface::{Point, route::RouteRequestBuilder, services::Location};
+///
+/// let start = Point::new(48.040437, 10.316550).expect("Invalid point");
+/// let end = Point::new(49.006101, 9.052887).expect("Invalid point");
+/// // The hint returned for `start` by an earlier request
+/// let hint = "pKUJgP___38ZAAAAAAAAAAAAAAAAAAAA";
+/// let locations = [Location::Hint { hint, input: start }, Location::Point(end)];
+/// let route_request = RouteRequestBuilder::from_locations(&locations)
+///     .build()
+///     .expect("Failed to build RouteRequest");
+/// let url = route_request.to_url_parts("driving", "v1", None).to_url("");
+/// assert!(url.contains(&format!("hints={hint};")));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Location<'a> {
+    /// A coordinate, snapped to the nearest segment by OSRM.
+    Point(Point),
+    /// A hint returned with a waypoint of a previous response, along with
+    /// the coordinate supplied for that waypoint in the request.
+    Hint { hint: &'a str, input: Point },
+}
+
+impl<'a> Location<'a> {
+    /// The coordinate sent for the location.
+    pub fn point(&self) -> Point {
+        match self {
+            Self::Point(point) | Self::Hint { input: point, .. } => *point,
+        }
+    }
+
+    /// The hint sent for the location, if any.
+    pub fn hint(&self) -> Option<&'a str> {
+        match self {
+            Self::Point(_) => None,
+            Self::Hint { hint, .. } => Some(hint),
+        }
+    }
+}
+
+impl From<Point> for Location<'_> {
+    fn from(point: Point) -> Self {
+        Self::Point(point)
+    }
+}
+
+/// Split locations into the points and hints arrays of a request. The hints
+/// are `None` when no location has a hint, so none are sent.
+pub(crate) fn split_locations<'a>(
+    locations: &[Location<'a>],
+) -> (Vec<Point>, Option<Vec<Option<&'a str>>>) {
+    let points = locations.iter().map(Location::point).collect();
+    let hints = locations
+        .iter()
+        .any(|location| location.hint().is_some())
+        .then(|| locations.iter().map(Location::hint).collect());
+    (points, hints)
+}
+
+/// The components of the url of a request to the OSRM HTTP API, allowing the
+/// request to be sent with any HTTP client. Created by the `to_url_parts`
+/// method of each request, eg. [`RouteRequest::to_url_parts`].
+///
+/// [`query`](Self::query) holds the raw values. [`to_url`](Self::to_url)
+/// percent-encodes everything except the `;` and `,` separators, which OSRM
+/// expects unescaped.
+///
+/// ```
+/// use osrm_interface::{Point, route::RouteRequestBuilder};
+/// let points = [
+///     Point::new(48.040437, 10.316550).expect("Invalid point"),
+///     Point::new(49.006101, 9.052887).expect("Invalid point"),
+/// ];
+/// let route_request = RouteRequestBuilder::new(&points)
+///     .build()
+///     .expect("Failed to build RouteRequest");
+/// let parts = route_request.to_url_parts("driving", "v1", None);
+/// assert_eq!(
+///     parts.path,
+///     "/route/v1/driving/10.316550,48.040437;9.052887,49.006101"
+/// );
+/// assert!(parts.query.contains(&("steps".to_string(), "false".to_string())));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UrlParts {
+    /// The path of the url, eg. `/route/v1/driving/13.388860,52.517037;13.397634,52.529407`.
+    pub path: String,
+    /// The query parameters, in order.
+    pub query: Vec<(String, String)>,
+}
+
+impl UrlParts {
+    /// Create the parts with the path
+    /// `/{service}/{version}/{profile}/{coordinates}[.{format}]`.
+    pub(crate) fn new(
+        service: &str,
+        version: &str,
+        profile: &str,
+        coordinates: &str,
+        format: Option<&str>,
+    ) -> Self {
+        let mut path = format!("/{service}/{version}/{profile}/{coordinates}");
+        if let Some(format) = format {
+            path.push('.');
+            path.push_str(format);
+        }
+        Self {
+            path,
+            query: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, key: &str, value: impl ToString) {
+        self.query.push((key.to_string(), value.to_string()));
+    }
+
+    /// The full url of the request to `endpoint`, eg. `http://localhost:5000`.
+    ///
+    /// The endpoint may include a base path, eg. `https://example.com/osrm`
+    /// for a backend mounted under a subpath by a reverse proxy. Trailing
+    /// slashes are ignored, so exactly one slash separates the endpoint and
+    /// the path.
+    ///
+    /// The query values are percent-encoded, except for the `;` and `,`
+    /// separating the values of the points. The values in
+    /// [`query`](Self::query) are not, so hints, which are base64 and may
+    /// contain `+`, `/` and `=`, must be encoded when sending the parts with
+    /// another client.
+    pub fn to_url(&self, endpoint: &str) -> String {
+        let endpoint = endpoint.trim_end_matches('/');
+        let query = self
+            .query
+            .iter()
+            .map(|(key, value)| format!("{key}={}", percent_encode(value)))
+            .join("&");
+        if query.is_empty() {
+            format!("{endpoint}{}", self.path)
+        } else {
+            format!("{endpoint}{}?{query}", self.path)
+        }
+    }
+}
+
+/// Percent-encode everything but unreserved characters and the `;` and `,`
+/// separators in a query value.
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b';' | b',' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    encoded
+}
+
+/// The `;` separated `longitude,latitude` pairs of the points, as they appear
+/// in the url path. See the [`Display`](std::fmt::Display) impl of [`Point`].
+pub(crate) fn url_coordinates<'a>(mut points: impl Iterator<Item = &'a Point>) -> String {
+    points.join(";")
+}
+
+/// The url form of an optional per-point value, empty when `None`.
+pub(crate) fn url_bearing(bearing: &Option<Bearing>) -> String {
+    bearing.map(|b| b.url_form()).unwrap_or_default()
+}
+
+/// See [`url_bearing`].
+pub(crate) fn url_radius(radius: &Option<f64>) -> String {
+    radius.map(|r| format!("{r:.12}")).unwrap_or_default()
+}
+
+/// See [`url_bearing`].
+pub(crate) fn url_hint(hint: &Option<&str>) -> String {
+    hint.unwrap_or("").to_string()
+}
+
+/// See [`url_bearing`].
+pub(crate) fn url_approach(approach: &Approach) -> String {
+    approach.url_form().to_string()
+}
+
+/// The `;` separated url form of each per-point value.
+pub(crate) fn url_list<T>(values: &[T], url_form: impl Fn(&T) -> String) -> String {
+    values.iter().map(url_form).join(";")
+}
+
+/// The `,` separated excludes, as they appear in the url query.
+pub(crate) fn url_exclude(exclude: &[Exclude]) -> String {
+    exclude
+        .iter()
+        .map(|exclude| match &exclude {
+            Exclude::Bicycle(v) => v.as_str(),
+            Exclude::Car(v) => v.as_str(),
+        })
+        .join(",")
+}
+
+/// Whether approaches other than [`Approach::Unrestricted`] are set without
+/// bearings or radiuses (`constrained`) to direct snapping. Checked by the
+/// builders, which return an error in strict mode and otherwise call
+/// [`warn_unconstrained_approaches`] with `feature="log"`.
+pub(crate) fn unconstrained_approaches(approaches: Option<&[Approach]>, constrained: bool) -> bool {
+    !constrained
+        && approaches.is_some_and(|approaches| {
+            approaches
+                .iter()
+                .any(|approach| *approach != Approach::Unrestricted)
+        })
+}
+
+/// Log that approaches are set without bearings or radiuses, as some OSRM
+/// configurations reject the request.
+#[cfg(feature = "log")]
+pub(crate) fn warn_unconstrained_approaches() {
+    log::warn!(
+        "Approaches are set without bearings or radiuses to constrain snapping; OSRM may reject the request"
+    );
+}
+
 /// The array-like option for which there
 /// was a dimension mismatch when constructing
 /// a request.
@@ -21,6 +557,30 @@ pub enum DimensionMismatch {
     Approaches,
 }
 
+impl DimensionMismatch {
+    /// A one line suggestion for fixing the mismatch.
+    ///
+    /// Array-like options must have one element per point, or per source or
+    /// destination for the table service.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::Timestamps => "timestamps must have exactly one element per point",
+            Self::Bearings => {
+                "bearings must have exactly one element per point; pass `None` to leave a point unconstrained"
+            }
+            Self::Radiuses => {
+                "radiuses must have exactly one element per point; pass `None` for an unlimited radius"
+            }
+            Self::Hints => {
+                "hints must have exactly one element per point; pass `None` for points without a hint"
+            }
+            Self::Approaches => {
+                "approaches must have exactly one element per point; pass `Approach::Unrestricted` to leave a point unrestricted"
+            }
+        }
+    }
+}
+
 /// Allows restricting the direction on the road network at a waypoint.
 /// Relative to the input coordinate.
 ///
@@ -28,20 +588,24 @@ pub enum DimensionMismatch {
 /// ([`Approach::Curb`]) when arriving at a waypoint, being on the opposite
 /// side, or being unrestricted.
 ///
+/// The discriminants and size match `osrm::engine::Approach`, a
+/// `std::uint8_t` enum, as arrays of approaches are passed directly to
+/// osrm-backend by the native engine.
+///
 /// Implements [`serde::Deserialize`] and
 /// [`serde::Serialize`] if `feature="serde"` is set.
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-#[repr(C)]
+#[repr(u8)]
 pub enum Approach {
     /// Require that the approach is made on the correct side of the road to
     /// the waypoint.
-    Curb,
+    Curb = 0,
+    /// Make no requirements about how the waypoint is approached.
+    Unrestricted = 1,
     /// Require that the approach is made on the opposite side of the road to
     /// the waypoint.
-    Opposite,
-    /// Make no requirements about how the waypoint is approached.
-    Unrestricted,
+    Opposite = 2,
 }
 impl Approach {
     /// Formats the variant as a lowercase &str. The form expected
@@ -5,9 +5,9 @@ use thiserror::Error;
 
 use crate::{
     Point,
-    osrm_response_types::Waypoint,
-    request_types::{Bearing, Exclude, Snapping},
-    services::Approach,
+    osrm_response_types::{ResponseCode, Waypoint},
+    request_types::{Bearing, Exclude, ExcludeSet, Snapping},
+    services::{Approach, UrlParts, unconstrained_approaches, url_coordinates, url_exclude},
 };
 
 /// The request object passed to the nearest service. Constructed
@@ -27,6 +27,34 @@ pub struct NearestRequest<'a> {
     pub(crate) snapping: Option<Snapping>,
 }
 
+impl NearestRequest<'_> {
+    /// The path and query of the url for the request, for sending it with a
+    /// custom HTTP client. See
+    /// [`RouteRequest::to_url_parts`](crate::route::RouteRequest::to_url_parts).
+    pub fn to_url_parts(&self, profile: &str, version: &str, format: Option<&str>) -> UrlParts {
+        let coordinates = url_coordinates(std::iter::once(self.point));
+        let mut parts = UrlParts::new("nearest", version, profile, &coordinates, format);
+        parts.push("number", self.number);
+        if let Some(bearing) = self.bearing {
+            parts.push("bearings", bearing.url_form());
+        }
+        if let Some(radius) = self.radius {
+            parts.push("radiuses", format!("{radius:.12}"));
+        }
+        if let Some(approach) = self.approach {
+            parts.push("approaches", approach.url_form());
+        }
+        if let Some(exclude) = self.exclude {
+            parts.push("exclude", url_exclude(exclude));
+        }
+        if let Some(snapping) = self.snapping {
+            parts.push("snapping", snapping.url_form());
+        }
+
+        parts
+    }
+}
+
 /// Helper struct for building a [`NearestRequest`].
 ///
 /// Set nearest options using the struct methods before calling
@@ -40,6 +68,7 @@ pub struct NearestRequest<'a> {
 ///   road segments are queried.
 ///
 /// - **`number`** (*required*) — The maximum number of nearest segments to return.
+///   Must be at least 1.
 ///
 /// - **`bearing`** (*optional*) — A [`Bearing`] restricting the direction
 ///   in which the coordinate may be snapped to a road segment.
@@ -56,8 +85,19 @@ pub struct NearestRequest<'a> {
 ///   transport mode (e.g., all `Exclude::Car` or all `Exclude::Bicycle`),
 ///   specifying road classes to exclude from the search.
 ///
+/// - **`with_ambiguity_check`** — Raises `number` to at least two, so that
+///   [`NearestResponse::is_ambiguous`] can decide.
+///
 /// - **`snapping`** (*optional*) — Defines how the input coordinate is snapped to
-///   the road network. See [`Snapping`] for available modes.
+///   the road network. See [`Snapping`] for available modes. When unset, all
+///   engines behave as [`Snapping::Default`], returning only segments which are
+///   valid start points. [`Snapping::Any`] additionally returns segments which
+///   are otherwise unreachable, eg. private driveways, so may return closer
+///   candidates.
+///
+/// Unlike the other builders, this one does not expose OSRM's `generate_hints`
+/// option, so the backend default of generating hints always applies. Hints
+/// are usually the reason to snap a point.
 ///
 /// ## Example
 ///
@@ -80,6 +120,7 @@ pub struct NearestRequestBuilder<'a> {
     approach: Option<Approach>,
     exclude: Option<&'a [Exclude]>,
     snapping: Option<Snapping>,
+    strict: bool,
 }
 
 impl<'a> NearestRequestBuilder<'a> {
@@ -95,6 +136,7 @@ impl<'a> NearestRequestBuilder<'a> {
             approach: None,
             exclude: None,
             snapping: None,
+            strict: false,
         }
     }
 
@@ -137,12 +179,47 @@ impl<'a> NearestRequestBuilder<'a> {
         self
     }
 
+    /// Sets which road classes should be excluded from the request using
+    /// an [`ExcludeSet`], which guarantees at compile time that all
+    /// excludes belong to the same transport mode.
+    ///
+    /// Equivalent to calling [`exclude`](Self::exclude) with
+    /// [`ExcludeSet::as_slice`].
+    pub fn exclude_set<M>(mut self, exclude_set: &'a ExcludeSet<M>) -> Self {
+        self.exclude = Some(exclude_set.as_slice());
+        self
+    }
+
     /// Sets the snapping behavior for the input coordinate.
+    ///
+    /// Defaults to [`Snapping::Default`] when not set.
     pub fn snapping(mut self, snapping: Snapping) -> Self {
         self.snapping = Some(snapping);
         self
     }
 
+    /// Request enough candidates for [`NearestResponse::is_ambiguous`] to
+    /// decide whether snapping the point is ambiguous.
+    ///
+    /// Sets `number` to at least 2.
+    pub fn with_ambiguity_check(mut self) -> Self {
+        self.number = self.number.max(2);
+        self
+    }
+
+    /// Enables strict validation in [`build`](Self::build), turning checks which
+    /// are otherwise skipped into errors. Disabled by default.
+    ///
+    /// In strict mode, setting an approach other than `Approach::Unrestricted`
+    /// without a bearing or radius returns `UnconstrainedApproach`, as some
+    /// OSRM configurations reject approaches without a snapping constraint.
+    /// Otherwise, with `feature="log"`, a warning is logged through the `log`
+    /// crate.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
     /// Builds a [`NearestRequest`] from the configured parameters.
     ///
     /// Performs validation to ensure all parameters are compatible.
@@ -150,9 +227,15 @@ impl<'a> NearestRequestBuilder<'a> {
     /// # Errors
     ///
     /// Returns a [`NearestRequestError`] if:
+    /// - `number` is zero.
     /// - Exclude types mix transport modes.
     /// - Radius is negative.
+    /// - In [`strict`](Self::strict) mode, an approach is set without a bearing or radius.
     pub fn build(&self) -> Result<NearestRequest<'a>, NearestRequestError> {
+        if self.number == 0 {
+            return Err(NearestRequestError::ZeroNumber);
+        }
+
         #[allow(clippy::collapsible_if)]
         if let Some(exclude) = self.exclude {
             if !exclude.is_empty() {
@@ -172,7 +255,28 @@ impl<'a> NearestRequestBuilder<'a> {
             }
         }
 
-        Ok(NearestRequest {
+        let unconstrained = unconstrained_approaches(
+            self.approach.as_ref().map(std::slice::from_ref),
+            self.bearing.is_some() || self.radius.is_some(),
+        );
+        if unconstrained && self.strict {
+            return Err(NearestRequestError::UnconstrainedApproach);
+        }
+        #[cfg(feature = "log")]
+        if unconstrained {
+            crate::services::warn_unconstrained_approaches();
+        }
+
+        Ok(self.build_unchecked())
+    }
+
+    /// Builds a [`NearestRequest`] without validating the configured parameters.
+    ///
+    /// A performance escape hatch for hot loops over inputs which are already
+    /// known to be valid. As no checks are performed, the resulting request may
+    /// be rejected by OSRM. Prefer [`build`](Self::build) otherwise.
+    pub fn build_unchecked(&self) -> NearestRequest<'a> {
+        NearestRequest {
             point: self.point,
             number: self.number,
             bearing: self.bearing,
@@ -180,7 +284,7 @@ impl<'a> NearestRequestBuilder<'a> {
             approach: self.approach,
             exclude: self.exclude,
             snapping: self.snapping,
-        })
+        }
     }
 }
 
@@ -193,11 +297,22 @@ impl<'a> NearestRequestBuilder<'a> {
 #[derive(Error, Debug)]
 pub enum NearestRequestError {
     /// Cannot mix excludes of different [`Exclude`] variants.
-    #[error("Exclude types are not all of the same type")]
+    #[error(
+        "Exclude types are not all of the same type; use the excludes of a single profile, eg. only `CarExclude`"
+    )]
     DifferentExcludeTypes,
     /// Radius values must be non-negative.
-    #[error("Radii must be non-negative")]
+    #[error("Radii must be non-negative; pass `None` for an unlimited radius")]
     NegativeRadius,
+    /// At least one candidate must be requested.
+    #[error("Number of candidates must be at least 1")]
+    ZeroNumber,
+    /// In strict mode, an approach must be accompanied by a bearing or
+    /// radius to constrain snapping.
+    #[error(
+        "Approach is set without a bearing or radius to constrain snapping; set a bearing or radius, or disable strict mode"
+    )]
+    UnconstrainedApproach,
 }
 
 /// The response type returned by the Nearest service.
@@ -212,3 +327,37 @@ pub struct NearestResponse {
     /// Array of Waypoint objects sorted by distance to the input coordinate
     pub waypoints: Vec<Waypoint>,
 }
+
+#[cfg(feature = "serde")]
+impl NearestResponse {
+    /// Parse the JSON body of a response returned by OSRM. See
+    /// [`RouteResponse::from_json`](crate::route::RouteResponse::from_json).
+    pub fn from_json(json: &str) -> Result<Self, crate::errors::OsrmError> {
+        crate::serde_ops::from_json(json, |response: &Self| &response.code)
+    }
+}
+
+/// The ratio of the distances to the second closest and closest candidates
+/// at or below which a nearest response is considered ambiguous.
+pub const NEAREST_AMBIGUITY_RATIO: f64 = 1.2;
+
+impl NearestResponse {
+    /// The [`code`](Self::code) of the response as a [`ResponseCode`]. See
+    /// [`RouteResponse::code_enum`](crate::route::RouteResponse::code_enum).
+    pub fn code_enum(&self) -> ResponseCode {
+        ResponseCode::from(self.code.as_str())
+    }
+
+    /// Whether the two closest candidates are similarly distant from the input
+    /// coordinate, ie. the second closest is within [`NEAREST_AMBIGUITY_RATIO`]
+    /// times the distance of the closest. The point could reasonably have been
+    /// snapped to either, eg. at an intersection or between parallel roads.
+    ///
+    /// Always `false` with fewer than two candidates.
+    pub fn is_ambiguous(&self) -> bool {
+        match self.waypoints.as_slice() {
+            [first, second, ..] => second.distance <= first.distance * NEAREST_AMBIGUITY_RATIO,
+            _ => false,
+        }
+    }
+}
@@ -1,11 +1,18 @@
 //! Given a set of coordinates, construct a route through those coordinates
 //! in the supplied order.
 
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+use itertools::Itertools;
 use thiserror::Error;
 
-use crate::osrm_response_types::{Route, Waypoint};
-use crate::request_types::{Bearing, Exclude, OverviewZoom, Snapping};
-use crate::services::{Approach, DimensionMismatch};
+use crate::osrm_response_types::{Geometry, ResponseCode, Route, RouteLeg, Waypoint};
+use crate::request_types::{Annotations, Bearing, Exclude, ExcludeSet, OverviewZoom, Snapping};
+use crate::services::{
+    Approach, DimensionMismatch, Location, UrlParts, split_locations, unconstrained_approaches,
+    url_approach, url_bearing, url_coordinates, url_exclude, url_hint, url_list, url_radius,
+};
 use crate::{Point, request_types::GeometryType};
 
 /// The request object passed to the route service. Constructed
@@ -16,21 +23,73 @@ use crate::{Point, request_types::GeometryType};
 #[derive(Clone, Debug)]
 #[allow(dead_code)]
 pub struct RouteRequest<'a> {
-    pub(crate) points: &'a [Point],
-    pub(crate) alternatives: bool,
+    pub(crate) points: Cow<'a, [Point]>,
+    pub(crate) alternatives: u8,
     pub(crate) steps: bool,
     pub(crate) geometry: GeometryType,
     pub(crate) overview: OverviewZoom,
-    pub(crate) annotations: bool,
+    pub(crate) annotations: Annotations,
     pub(crate) continue_straight: bool,
     pub(crate) bearings: Option<&'a [Option<Bearing>]>,
     pub(crate) radiuses: Option<&'a [Option<f64>]>,
     pub(crate) generate_hints: bool,
-    pub(crate) hints: Option<&'a [Option<&'a str>]>,
+    pub(crate) hints: Option<Cow<'a, [Option<&'a str>]>>,
     pub(crate) approaches: Option<&'a [Approach]>,
     pub(crate) exclude: Option<&'a [Exclude]>,
     pub(crate) snapping: Option<Snapping>,
     pub(crate) skip_waypoints: bool,
+    pub(crate) waypoints: Option<&'a [usize]>,
+}
+
+impl RouteRequest<'_> {
+    /// The path and query of the url for the request, for sending it with a
+    /// custom HTTP client. The response body can then be parsed with
+    /// [`RouteResponse::from_json`].
+    ///
+    /// `profile` is placed in the path, see [`crate::remote`] for how it is
+    /// used. `version` is `"v1"` for all current versions of OSRM. `format`,
+    /// eg. `Some("json")`, is appended to the coordinates, OSRM defaults to
+    /// JSON when it is `None`.
+    pub fn to_url_parts(&self, profile: &str, version: &str, format: Option<&str>) -> UrlParts {
+        let coordinates = url_coordinates(self.points.iter());
+        let mut parts = UrlParts::new("route", version, profile, &coordinates, format);
+        if self.alternatives == 0 {
+            parts.push("alternatives", false);
+        } else {
+            parts.push("alternatives", self.alternatives);
+        }
+        parts.push("steps", self.steps);
+        parts.push("geometries", self.geometry.url_form());
+        parts.push("overview", self.overview.url_form());
+        parts.push("continue_straight", self.continue_straight);
+        parts.push("annotations", self.annotations.url_form());
+        parts.push("generate_hints", self.generate_hints);
+        parts.push("skip_waypoints", self.skip_waypoints);
+
+        if let Some(bearings) = self.bearings {
+            parts.push("bearings", url_list(bearings, url_bearing));
+        }
+        if let Some(radiuses) = self.radiuses {
+            parts.push("radiuses", url_list(radiuses, url_radius));
+        }
+        if let Some(hints) = &self.hints {
+            parts.push("hints", url_list(hints, url_hint));
+        }
+        if let Some(approaches) = self.approaches {
+            parts.push("approaches", url_list(approaches, url_approach));
+        }
+        if let Some(exclude) = self.exclude {
+            parts.push("exclude", url_exclude(exclude));
+        }
+        if let Some(snapping) = self.snapping {
+            parts.push("snapping", snapping.url_form());
+        }
+        if let Some(waypoints) = self.waypoints {
+            parts.push("waypoints", url_list(waypoints, usize::to_string));
+        }
+
+        parts
+    }
 }
 
 /// Helper struct for building a [`RouteRequest`].
@@ -45,8 +104,9 @@ pub struct RouteRequest<'a> {
 /// - **`points`** (*required*) — A slice of [`Point`]s that define the route path.  
 ///   Must contain at least two points.
 ///
-/// - **`alternatives`** (*default:* `false`) — If `true`, OSRM will return
-///   alternative routes in addition to the recommended one.
+/// - **`alternatives`** (*default:* `0`) — The number of alternative routes
+///   OSRM may return in addition to the recommended one. Fewer, or none, are
+///   returned when no suitable alternatives are found.
 ///
 /// - **`steps`** (*default:* `false`) — If `true`, includes turn-by-turn navigation
 ///   instructions for each route leg.
@@ -57,8 +117,8 @@ pub struct RouteRequest<'a> {
 /// - **`overview`** (*default:* `OverviewZoom::Simplified`) — Controls the
 ///   generalization level of the route overview geometry. See [`OverviewZoom`].
 ///
-/// - **`annotations`** (*default:* `false`) — When enabled, includes metadata such as
-///   distance, duration, and speed for each segment of the route.
+/// - **`annotations`** (*default:* `Annotations::NONE`) — Which metadata, such as
+///   distance, duration and speed, to include for each segment. See [`Annotations`].
 ///
 /// - **`continue_straight`** (*default:* `true`) — If `true`, the route will continue
 ///   straight at waypoints where possible. If `false`, U-turns may be allowed
@@ -77,6 +137,11 @@ pub struct RouteRequest<'a> {
 /// - **`generate_hints`** (*default:* `true`) — When enabled, OSRM returns
 ///   location hints to accelerate subsequent queries.
 ///
+/// - **`waypoints`** (*optional*) — A slice of indices (into `points`) marking which
+///   points are waypoints separating legs. The remaining points are passed through
+///   as via points without starting a new leg. Must not be empty or out of bounds,
+///   and must include the first and last points.
+///
 /// ## Array options
 ///
 /// The following options require array slices as input.
@@ -115,38 +180,232 @@ pub struct RouteRequest<'a> {
 ///     .build()
 ///     .expect("Failed to build RouteRequest");
 /// ```
-
+///
+/// Building a request from an [`empty`](RouteRequestBuilder::empty) builder
+/// without supplying points does not compile, as only builders in the
+/// [`HasPoints`] state have a [`build`](RouteRequestBuilder::build) method.
+///
+/// ```compile_fail
+/// use osrm_interface::route::RouteRequestBuilder;
+/// let route_request = RouteRequestBuilder::empty().steps(true).build();
+/// ```
 #[derive(Clone, Debug)]
-pub struct RouteRequestBuilder<'a> {
-    points: &'a [Point],
-    alternatives: bool,
+pub struct RouteRequestBuilder<'a, S = HasPoints> {
+    points: Cow<'a, [Point]>,
+    alternatives: u8,
     steps: bool,
     geometry: GeometryType,
     overview: OverviewZoom,
-    annotations: bool,
+    annotations: Annotations,
     continue_straight: bool,
     bearings: Option<&'a [Option<Bearing>]>,
     radiuses: Option<&'a [Option<f64>]>,
     generate_hints: bool,
-    hints: Option<&'a [Option<&'a str>]>,
+    hints: Option<Cow<'a, [Option<&'a str>]>>,
     approaches: Option<&'a [Approach]>,
     exclude: Option<&'a [Exclude]>,
     snapping: Option<Snapping>,
     skip_waypoints: bool,
+    waypoints: Option<&'a [usize]>,
+    strict: bool,
+    state: PhantomData<S>,
 }
 
-impl<'a> RouteRequestBuilder<'a> {
+/// The state of a [`RouteRequestBuilder`] created by
+/// [`empty`](RouteRequestBuilder::empty), before any points are supplied.
+#[derive(Clone, Copy, Debug)]
+pub struct NoPoints;
+
+/// The state of a [`RouteRequestBuilder`] with points, which can be built.
+#[derive(Clone, Copy, Debug)]
+pub struct HasPoints;
+
+impl<'a> RouteRequestBuilder<'a, HasPoints> {
     /// Creates a new [`RouteRequestBuilder`] with default parameters.
     ///
     /// The builder can then be customized using its setter methods.
     pub fn new(points: &'a [Point]) -> Self {
+        Self::with_points(Cow::Borrowed(points))
+    }
+
+    /// Creates a new [`RouteRequestBuilder`] routing through previously
+    /// returned [`Waypoint`]s, eg. to re-route cached waypoints.
+    ///
+    /// The `location` of each waypoint is used as the coordinate and the
+    /// `hint`, where present, is used to skip snapping. OSRM requires
+    /// coordinates even when hints are supplied, but the hint takes
+    /// precedence.
+    pub fn from_waypoints(waypoints: &'a [Waypoint]) -> Self {
+        let points = waypoints.iter().map(Waypoint::as_point).collect();
+        let hints = waypoints.iter().map(|w| w.hint.as_deref()).collect();
+        Self {
+            points: Cow::Owned(points),
+            hints: Some(Cow::Owned(hints)),
+            ..Self::new(&[])
+        }
+    }
+
+    /// Creates a new [`RouteRequestBuilder`] through the given [`Location`]s,
+    /// sending the hints of any hinted locations. See [`Location`] for when
+    /// OSRM uses the hints.
+    pub fn from_locations(locations: &[Location<'a>]) -> Self {
+        let (points, hints) = split_locations(locations);
+        Self {
+            points: Cow::Owned(points),
+            hints: hints.map(Cow::Owned),
+            ..Self::new(&[])
+        }
+    }
+
+    /// Builds a [`RouteRequest`] from the configured parameters.
+    ///
+    /// Performs validation to ensure all per-point array options
+    /// have consistent lengths and compatible types.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`RouteRequestError`] if:
+    /// - Fewer than two points were provided.
+    /// - Array lengths do not match the number of points.
+    /// - Exclude types mix transport modes.
+    /// - Waypoints are empty, out of bounds or omit the first or last point.
+    /// - In [`strict`](Self::strict) mode, approaches are set without bearings or radiuses.
+    pub fn build(&self) -> Result<RouteRequest<'a>, RouteRequestError> {
+        if self.points.len() < 2 {
+            return Err(RouteRequestError::InsufficientPoints);
+        }
+
+        #[allow(clippy::collapsible_if)]
+        if let Some(bearings) = self.bearings {
+            if bearings.len() != self.points.len() {
+                return Err(RouteRequestError::DimensionMismatch(
+                    DimensionMismatch::Bearings,
+                ));
+            }
+        }
+
+        #[allow(clippy::collapsible_if)]
+        if let Some(radiuses) = self.radiuses {
+            if radiuses.len() != self.points.len() {
+                return Err(RouteRequestError::DimensionMismatch(
+                    DimensionMismatch::Radiuses,
+                ));
+            }
+            if !radiuses.iter().all(|r| r.is_none_or(|v| v >= 0.0)) {
+                return Err(RouteRequestError::NegativeRadius);
+            }
+        }
+
+        #[allow(clippy::collapsible_if)]
+        if let Some(hints) = &self.hints {
+            if hints.len() != self.points.len() {
+                return Err(RouteRequestError::DimensionMismatch(
+                    DimensionMismatch::Hints,
+                ));
+            }
+        }
+
+        #[allow(clippy::collapsible_if)]
+        if let Some(approaches) = self.approaches {
+            if approaches.len() != self.points.len() {
+                return Err(RouteRequestError::DimensionMismatch(
+                    DimensionMismatch::Approaches,
+                ));
+            }
+        }
+
+        #[allow(clippy::collapsible_if)]
+        if let Some(exclude) = self.exclude {
+            if !exclude.is_empty() {
+                if !match exclude[0] {
+                    Exclude::Car(_) => exclude.iter().all(|e| matches!(e, Exclude::Car(_))),
+                    Exclude::Bicycle(_) => exclude.iter().all(|e| matches!(e, Exclude::Bicycle(_))),
+                } {
+                    return Err(RouteRequestError::DifferentExcludeTypes);
+                }
+            }
+        }
+
+        #[allow(clippy::collapsible_if)]
+        if let Some(waypoints) = self.waypoints {
+            if waypoints.is_empty() {
+                return Err(RouteRequestError::EmptyWaypoints);
+            }
+            if let Some(max_idx) = waypoints.iter().max() {
+                if *max_idx >= self.points.len() {
+                    return Err(RouteRequestError::WaypointIndexOutOfBounds(
+                        *max_idx,
+                        self.points.len(),
+                    ));
+                }
+            }
+            if waypoints.first() != Some(&0) || waypoints.last() != Some(&(self.points.len() - 1)) {
+                return Err(RouteRequestError::WaypointsMissingEndpoints);
+            }
+        }
+
+        let unconstrained = unconstrained_approaches(
+            self.approaches,
+            self.bearings.is_some() || self.radiuses.is_some(),
+        );
+        if unconstrained && self.strict {
+            return Err(RouteRequestError::UnconstrainedApproaches);
+        }
+        #[cfg(feature = "log")]
+        if unconstrained {
+            crate::services::warn_unconstrained_approaches();
+        }
+
+        Ok(self.build_unchecked())
+    }
+
+    /// Builds a [`RouteRequest`] without validating the configured parameters.
+    ///
+    /// A performance escape hatch for hot loops over inputs which are already
+    /// known to be valid. As no checks are performed, the resulting request may
+    /// be rejected by OSRM. Prefer [`build`](Self::build) otherwise.
+    pub fn build_unchecked(&self) -> RouteRequest<'a> {
+        RouteRequest {
+            points: self.points.clone(),
+            alternatives: self.alternatives,
+            steps: self.steps,
+            geometry: self.geometry,
+            overview: self.overview,
+            annotations: self.annotations,
+            continue_straight: self.continue_straight,
+            bearings: self.bearings,
+            radiuses: self.radiuses,
+            generate_hints: self.generate_hints,
+            hints: self.hints.clone(),
+            approaches: self.approaches,
+            exclude: self.exclude,
+            snapping: self.snapping,
+            skip_waypoints: self.skip_waypoints,
+            waypoints: self.waypoints,
+        }
+    }
+}
+
+impl<'a> RouteRequestBuilder<'a, NoPoints> {
+    /// Creates a new [`RouteRequestBuilder`] with default parameters and no points.
+    ///
+    /// Allows options to be configured before the points are known.
+    /// [`points`](Self::points) must be called before the request can be
+    /// built.
+    pub fn empty() -> Self {
+        Self::with_points(Cow::Borrowed(&[]))
+    }
+}
+
+impl<'a, S> RouteRequestBuilder<'a, S> {
+    fn with_points(points: Cow<'a, [Point]>) -> Self {
         Self {
             points,
             geometry: GeometryType::Polyline,
             overview: OverviewZoom::Simplified,
-            alternatives: false,
+            alternatives: 0,
             steps: false,
-            annotations: false,
+            annotations: Annotations::NONE,
             continue_straight: true,
             bearings: None,
             radiuses: None,
@@ -156,22 +415,55 @@ impl<'a> RouteRequestBuilder<'a> {
             exclude: None,
             snapping: None,
             skip_waypoints: false,
+            waypoints: None,
+            strict: false,
+            state: PhantomData,
         }
     }
 
-    /// Overwrite the points provided at construction of the builder. Useful
-    /// for reusing a builder with the same options.
+    /// Set the points of the builder, overwriting any provided at
+    /// construction. Useful for reusing a builder with the same options.
     ///
     /// Take care that array-like options are still the same length as points,
-    /// [`build`](Self::build) will throw an error if not.
-    pub fn points(mut self, points: &'a [Point]) -> Self {
-        self.points = points;
-        self
+    /// [`build`](RouteRequestBuilder::build) will throw an error if not.
+    pub fn points(self, points: &'a [Point]) -> RouteRequestBuilder<'a, HasPoints> {
+        RouteRequestBuilder {
+            points: Cow::Borrowed(points),
+            alternatives: self.alternatives,
+            steps: self.steps,
+            geometry: self.geometry,
+            overview: self.overview,
+            annotations: self.annotations,
+            continue_straight: self.continue_straight,
+            bearings: self.bearings,
+            radiuses: self.radiuses,
+            generate_hints: self.generate_hints,
+            hints: self.hints,
+            approaches: self.approaches,
+            exclude: self.exclude,
+            snapping: self.snapping,
+            skip_waypoints: self.skip_waypoints,
+            waypoints: self.waypoints,
+            strict: self.strict,
+            state: PhantomData,
+        }
     }
 
-    /// Sets whether to request alternative routes.
+    /// Sets whether to request an alternative route. Equivalent to
+    /// [`alternatives_count`](Self::alternatives_count) with `1` when `true`
+    /// and `0` when `false`, as OSRM treats `alternatives=true`.
     pub fn alternatives(mut self, generate_alternatives: bool) -> Self {
-        self.alternatives = generate_alternatives;
+        self.alternatives = u8::from(generate_alternatives);
+        self
+    }
+
+    /// Sets the maximum number of alternative routes to request, eg. `3` for
+    /// up to four routes in total. `0` requests none.
+    ///
+    /// osrm-routed caps the count with its `--max-alternatives` option,
+    /// 3 by default.
+    pub fn alternatives_count(mut self, count: u8) -> Self {
+        self.alternatives = count;
         self
     }
 
@@ -181,9 +473,10 @@ impl<'a> RouteRequestBuilder<'a> {
         self
     }
 
-    /// Sets whether to include per-segment annotations in the route.
-    pub fn annotations(mut self, include_annotations: bool) -> Self {
-        self.annotations = include_annotations;
+    /// Sets the per-segment annotations to include with each leg, either
+    /// [`Annotations`] or a `bool` to include all or none of them.
+    pub fn annotations(mut self, annotations: impl Into<Annotations>) -> Self {
+        self.annotations = annotations.into();
         self
     }
 
@@ -224,10 +517,12 @@ impl<'a> RouteRequestBuilder<'a> {
         self
     }
 
-    /// Sets per-point search radiuses (in meters) for coordinate snapping.
+    /// Sets whether OSRM generates hints for the waypoints in the response.
+    /// Enabled by default, as in OSRM.
     ///
-    /// Each radius must correspond to the point at the same index. Radii must
-    /// be positive. Passing None corresponds to an infinite search radius.
+    /// Hints are needed to pass the snapped waypoints to later requests, see
+    /// [`hints`](Self::hints). Disable them to reduce the response size
+    /// otherwise.
     pub fn generate_hints(mut self, generate_hints: bool) -> Self {
         self.generate_hints = generate_hints;
         self
@@ -241,7 +536,7 @@ impl<'a> RouteRequestBuilder<'a> {
     /// Passing hints will result in radiuses, bearings,
     /// approaches being ignored for that point.
     pub fn hints(mut self, coordinate_hints: &'a [Option<&'a str>]) -> Self {
-        self.hints = Some(coordinate_hints);
+        self.hints = Some(Cow::Borrowed(coordinate_hints));
         self
     }
 
@@ -259,6 +554,17 @@ impl<'a> RouteRequestBuilder<'a> {
         self
     }
 
+    /// Sets which road classes should be excluded from the request using
+    /// an [`ExcludeSet`], which guarantees at compile time that all
+    /// excludes belong to the same transport mode.
+    ///
+    /// Equivalent to calling [`exclude`](Self::exclude) with
+    /// [`ExcludeSet::as_slice`].
+    pub fn exclude_set<M>(mut self, exclude_set: &'a ExcludeSet<M>) -> Self {
+        self.exclude = Some(exclude_set.as_slice());
+        self
+    }
+
     /// Sets the snapping behavior for input coordinates.
     pub fn snapping(mut self, snapping: Snapping) -> Self {
         self.snapping = Some(snapping);
@@ -271,90 +577,28 @@ impl<'a> RouteRequestBuilder<'a> {
         self
     }
 
-    /// Builds a [`RouteRequest`] from the configured parameters.
-    ///
-    /// Performs validation to ensure all per-point array options
-    /// have consistent lengths and compatible types.
-    ///
-    /// # Errors
+    /// Specifies which input indices should be treated as waypoints, the
+    /// remaining points are routed through without splitting the route into
+    /// separate legs.
     ///
-    /// Returns a [`RouteRequestError`] if:
-    /// - Fewer than two points were provided.
-    /// - Array lengths do not match the number of points.
-    /// - Exclude types mix transport modes.
-    pub fn build(&self) -> Result<RouteRequest<'a>, RouteRequestError> {
-        if self.points.len() < 2 {
-            return Err(RouteRequestError::InsufficientPoints);
-        }
-
-        #[allow(clippy::collapsible_if)]
-        if let Some(bearings) = self.bearings {
-            if bearings.len() != self.points.len() {
-                return Err(RouteRequestError::DimensionMismatch(
-                    DimensionMismatch::Bearings,
-                ));
-            }
-        }
-
-        #[allow(clippy::collapsible_if)]
-        if let Some(radiuses) = self.radiuses {
-            if radiuses.len() != self.points.len() {
-                return Err(RouteRequestError::DimensionMismatch(
-                    DimensionMismatch::Radiuses,
-                ));
-            }
-            if !radiuses.iter().all(|r| r.is_none_or(|v| v >= 0.0)) {
-                return Err(RouteRequestError::NegativeRadius);
-            }
-        }
-
-        #[allow(clippy::collapsible_if)]
-        if let Some(hints) = self.hints {
-            if hints.len() != self.points.len() {
-                return Err(RouteRequestError::DimensionMismatch(
-                    DimensionMismatch::Hints,
-                ));
-            }
-        }
-
-        #[allow(clippy::collapsible_if)]
-        if let Some(approaches) = self.approaches {
-            if approaches.len() != self.points.len() {
-                return Err(RouteRequestError::DimensionMismatch(
-                    DimensionMismatch::Approaches,
-                ));
-            }
-        }
-
-        #[allow(clippy::collapsible_if)]
-        if let Some(exclude) = self.exclude {
-            if !exclude.is_empty() {
-                if !match exclude[0] {
-                    Exclude::Car(_) => exclude.iter().all(|e| matches!(e, Exclude::Car(_))),
-                    Exclude::Bicycle(_) => exclude.iter().all(|e| matches!(e, Exclude::Bicycle(_))),
-                } {
-                    return Err(RouteRequestError::DifferentExcludeTypes);
-                }
-            }
-        }
+    /// Must not be empty or contain out-of-bounds indices, and must include
+    /// the first and last points.
+    pub fn waypoints(mut self, waypoint_indices: &'a [usize]) -> Self {
+        self.waypoints = Some(waypoint_indices);
+        self
+    }
 
-        Ok(RouteRequest {
-            points: self.points,
-            alternatives: self.alternatives,
-            steps: self.steps,
-            geometry: self.geometry,
-            overview: self.overview,
-            annotations: self.annotations,
-            continue_straight: self.continue_straight,
-            bearings: self.bearings,
-            radiuses: self.radiuses,
-            generate_hints: self.generate_hints,
-            hints: self.hints,
-            approaches: self.approaches,
-            exclude: self.exclude,
-            snapping: self.snapping,
-            skip_waypoints: self.skip_waypoints,
-        })
+    /// Enables strict validation in [`build`](Self::build), turning checks which
+    /// are otherwise skipped into errors. Disabled by default.
+    ///
+    /// In strict mode, setting approaches other than `Approach::Unrestricted`
+    /// without any bearings or radiuses returns `UnconstrainedApproaches`, as
+    /// some OSRM configurations reject approaches without a snapping constraint.
+    /// Otherwise, with `feature="log"`, a warning is logged through the `log`
+    /// crate.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
     }
 }
 
@@ -363,18 +607,41 @@ impl<'a> RouteRequestBuilder<'a> {
 #[derive(Error, Debug)]
 pub enum RouteRequestError {
     // Route requires at least 2 points.
-    #[error("Route requires at least 2 points")]
+    #[error("Route requires at least 2 points; pass at least a start and an end point")]
     InsufficientPoints,
     /// Mismatch of number of elements between points and one
     /// of the array-like options.
-    #[error("Mismatch of dimensions between Points and {0:?}")]
+    #[error("Mismatch of dimensions between Points and {0:?}; {hint}", hint = .0.hint())]
     DimensionMismatch(DimensionMismatch),
     /// Cannot mix excludes of different [`Exclude`] variants.
-    #[error("Exclude types are not all of the same type")]
+    #[error(
+        "Exclude types are not all of the same type; use the excludes of a single profile, eg. only `CarExclude`"
+    )]
     DifferentExcludeTypes,
     /// Radius values must be non-negative.
-    #[error("Radii must be non-negative")]
+    #[error("Radii must be non-negative; pass `None` for an unlimited radius")]
     NegativeRadius,
+    /// In strict mode, approaches must be accompanied by bearings or
+    /// radiuses to constrain snapping.
+    #[error(
+        "Approaches are set without bearings or radiuses to constrain snapping; set bearings or radiuses, or disable strict mode"
+    )]
+    UnconstrainedApproaches,
+    /// If waypoints is specified as Some(), it may not be empty.
+    #[error(
+        "Waypoints when non-None must have non-zero length; leave waypoints unset to treat every point as a waypoint"
+    )]
+    EmptyWaypoints,
+    /// Waypoint values must be in bounds of the points array.
+    #[error(
+        "Waypoints contain index {0} which is out of bounds for points with size {1}; indices must be less than {1}"
+    )]
+    WaypointIndexOutOfBounds(usize, usize),
+    /// Waypoints must start at the first point and end at the last point.
+    #[error(
+        "Waypoints must begin with the first point and end with the last point; include indices 0 and points.len() - 1"
+    )]
+    WaypointsMissingEndpoints,
 }
 
 /// The response type returned by the Route service.
@@ -411,4 +678,279 @@ pub struct SimpleRouteResponse {
     pub duration: f64,
     /// The distance of the route returned in meters.
     pub distance: f64,
+    /// The name of the street the start point snapped to. `None` if
+    /// no waypoints were returned.
+    pub start_name: Option<String>,
+    /// The name of the street the end point snapped to. `None` if
+    /// no waypoints were returned.
+    pub end_name: Option<String>,
+}
+
+/// The legs of a route between two of the input points, returned by
+/// [`RouteResponse::legs_between`].
+#[derive(Clone, Copy, Debug)]
+pub struct LegSpan<'a> {
+    /// The legs between the two points, in order.
+    pub legs: &'a [RouteLeg],
+    /// The summed distance of the legs, in meters.
+    pub distance: f64,
+    /// The summed duration of the legs, in seconds.
+    pub duration: f64,
+}
+
+/// The totals and geometry of the best route, returned by
+/// [`RouteResponse::summary`].
+///
+/// Implements [`serde::Deserialize`] and
+/// [`serde::Serialize`] if `feature="serde"` is set.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug)]
+pub struct RouteSummary {
+    /// The distance of the route in meters.
+    pub total_distance: f64,
+    /// The duration of the route in seconds.
+    pub total_duration: f64,
+    /// The overview geometry of the route. `None` when `overview=false`.
+    pub geometry: Option<Geometry>,
+    /// The number of waypoints the route passes through, including the
+    /// start and end.
+    pub waypoint_count: usize,
+}
+
+/// How an alternative route differs from the best route, returned by
+/// [`RouteResponse::alternatives_summary`]. eg. "+4 min, -2 km".
+///
+/// Implements [`serde::Deserialize`] and
+/// [`serde::Serialize`] if `feature="serde"` is set.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Clone, Debug)]
+pub struct AlternativeSummary {
+    /// The distance of the alternative less that of the best route, in meters.
+    pub distance_delta: f64,
+    /// The duration of the alternative less that of the best route, in
+    /// seconds.
+    pub duration_delta: f64,
+    /// The last point of the overview geometry shared with the best route
+    /// before the alternative leaves it.
+    pub divergence_start: Option<Point>,
+    /// The first point of the overview geometry at which the alternative
+    /// rejoins the best route, sharing the rest of its geometry.
+    pub rejoin: Option<Point>,
+}
+
+/// Vertices of two geometries closer than this, in meters, are considered
+/// shared. See [`RouteResponse::alternatives_summary`].
+const SHARED_VERTEX_EPSILON_M: f64 = 0.5;
+
+impl RouteResponse {
+    /// The [`code`](Self::code) of the response as a [`ResponseCode`].
+    ///
+    /// The engines return an [`OsrmError::Service`](crate::errors::OsrmError::Service)
+    /// rather than a response for any code other than `"Ok"`, so this is only
+    /// of interest for responses parsed or constructed elsewhere.
+    pub fn code_enum(&self) -> ResponseCode {
+        ResponseCode::from(self.code.as_str())
+    }
+
+    /// The best route, ie. the first. Any alternatives follow it in
+    /// [`routes`](Self::routes).
+    ///
+    /// Returns `None` if there are no routes.
+    pub fn best_route(&self) -> Option<&Route> {
+        self.routes.first()
+    }
+
+    /// How each alternative route differs from the best route, in order, eg. to
+    /// label route options with "+4 min, -2 km". Empty unless the request set
+    /// `alternatives` and OSRM found any.
+    ///
+    /// The divergence and rejoin points are found by comparing the overview
+    /// geometries vertex by vertex from the start and from the end. They are
+    /// `None` without overview geometries, ie. `overview=false`, when the
+    /// geometries cannot be decoded with `geometry_type`, or when the
+    /// geometries share no vertices at that end. Pass the `geometry_type` the
+    /// request was built with, as for [`Geometry::decode`].
+    pub fn alternatives_summary(&self, geometry_type: GeometryType) -> Vec<AlternativeSummary> {
+        let Some((best, alternatives)) = self.routes.split_first() else {
+            return Vec::new();
+        };
+        let vertices = |route: &Route| route.geometry.as_ref()?.decode(geometry_type).ok();
+        let best_vertices = vertices(best);
+        let shared = |a: &Point, b: &Point| a.haversine_distance(b) < SHARED_VERTEX_EPSILON_M;
+
+        alternatives
+            .iter()
+            .map(|route| {
+                let vertices = vertices(route);
+                let (divergence_start, rejoin) = match (&best_vertices, &vertices) {
+                    (Some(best_vertices), Some(vertices)) => {
+                        let prefix = best_vertices
+                            .iter()
+                            .zip(vertices)
+                            .take_while(|(a, b)| shared(a, b))
+                            .count();
+                        // The shared end may not overlap the shared start
+                        let suffix = best_vertices
+                            .iter()
+                            .rev()
+                            .zip(vertices.iter().rev())
+                            .take(best_vertices.len().min(vertices.len()) - prefix)
+                            .take_while(|(a, b)| shared(a, b))
+                            .count();
+                        (
+                            prefix.checked_sub(1).map(|i| vertices[i]),
+                            (suffix > 0).then(|| vertices[vertices.len() - suffix]),
+                        )
+                    }
+                    _ => (None, None),
+                };
+                AlternativeSummary {
+                    distance_delta: route.distance - best.distance,
+                    duration_delta: route.duration - best.duration,
+                    divergence_start,
+                    rejoin,
+                }
+            })
+            .collect()
+    }
+
+    /// Pairs of waypoint indices, `(i, j)` with `i < j`, whose snapped
+    /// locations are within `epsilon_m` meters of each other, eg. to flag
+    /// input points which collapsed onto the same location and produce zero
+    /// length legs.
+    ///
+    /// The indices are those of the input points unless the request set
+    /// `waypoints`, in which case they index that list. Empty when the
+    /// response has no waypoints, ie. with `skip_waypoints=true`.
+    pub fn collapsed_waypoints(&self, epsilon_m: f64) -> Vec<(usize, usize)> {
+        let Some(waypoints) = &self.waypoints else {
+            return Vec::new();
+        };
+        waypoints
+            .iter()
+            .map(Waypoint::as_point)
+            .enumerate()
+            .tuple_combinations()
+            .filter(|((_, a), (_, b))| a.haversine_distance(b) <= epsilon_m)
+            .map(|((i, _), (j, _))| (i, j))
+            .collect()
+    }
+
+    /// The totals and overview geometry of the best route, for when only the
+    /// overall answer is needed.
+    ///
+    /// The waypoint count is taken from the legs when the response has no
+    /// waypoints, ie. with `skip_waypoints=true`.
+    ///
+    /// Returns `None` if there are no routes.
+    pub fn summary(&self) -> Option<RouteSummary> {
+        let route = self.routes.first()?;
+        Some(RouteSummary {
+            total_distance: route.distance,
+            total_duration: route.duration,
+            geometry: route.geometry.clone(),
+            waypoint_count: self
+                .waypoints
+                .as_ref()
+                .map_or(route.legs.len() + 1, Vec::len),
+        })
+    }
+
+    /// Remove zero length legs from all routes. See [`Route::non_empty_legs`].
+    pub fn without_empty_legs(&mut self) {
+        for route in &mut self.routes {
+            route.legs.retain(|leg| !leg.is_empty());
+        }
+    }
+
+    /// Group the legs of the best route into the journeys between the
+    /// waypoints at `waypoint_indices`, eg. `[0, 3]` for the journey from the
+    /// first waypoint to the fourth, ignoring the stops in between.
+    ///
+    /// Each leg runs between consecutive waypoints, so one [`LegSpan`] is
+    /// returned for each consecutive pair of indices. The indices are those of
+    /// the waypoints, not of the input points. They only coincide when the
+    /// request did not set [`waypoints`](RouteRequestBuilder::waypoints), as
+    /// otherwise via points start no leg. With `waypoints(&[0, 2, 5])`, the
+    /// input point 5 is waypoint 2.
+    ///
+    /// Returns `None` if there are no routes, or if the indices are not
+    /// strictly increasing or are out of bounds for the waypoints.
+    pub fn legs_between(&self, waypoint_indices: &[usize]) -> Option<Vec<LegSpan<'_>>> {
+        let legs = &self.routes.first()?.legs;
+        waypoint_indices
+            .windows(2)
+            .map(|pair| {
+                let (start, end) = (pair[0], pair[1]);
+                if start >= end || end > legs.len() {
+                    return None;
+                }
+                let legs = &legs[start..end];
+                Some(LegSpan {
+                    legs,
+                    distance: legs.iter().map(|leg| leg.distance).sum(),
+                    duration: legs.iter().map(|leg| leg.duration).sum(),
+                })
+            })
+            .collect()
+    }
+
+    /// Remove the hints from all waypoints, eg. before storing or forwarding
+    /// the response.
+    pub fn strip_hints(&mut self) {
+        self.keep_hints_for(&[]);
+    }
+
+    /// Remove the hints from all waypoints except those at `indices`.
+    ///
+    /// OSRM generates hints for either all or none of the waypoints, so this
+    /// allows keeping only the hints which will be reused.
+    pub fn keep_hints_for(&mut self, indices: &[usize]) {
+        for (i, waypoint) in self.waypoints.iter_mut().flatten().enumerate() {
+            if !indices.contains(&i) {
+                waypoint.hint = None;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl RouteResponse {
+    /// Parse the JSON body of a response returned by OSRM, eg. from a cache, a
+    /// file or after sending the request from [`RouteRequest::to_url_parts`]
+    /// with a custom HTTP client.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OsrmError::Service`](crate::errors::OsrmError::Service) with
+    /// the code and message of responses whose code is not `"Ok"`, eg.
+    /// `"NoRoute"`, or [`OsrmError::JsonParse`](crate::errors::OsrmError::JsonParse)
+    /// if the JSON is not a valid response.
+    pub fn from_json(json: &str) -> Result<Self, crate::errors::OsrmError> {
+        crate::serde_ops::from_json(json, |response: &Self| &response.code)
+    }
+
+    /// Write the response as a single line of compact JSON, followed by a newline.
+    ///
+    /// See [`write_all_ndjson`] for writing many responses.
+    pub fn write_ndjson<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        serde_json::to_writer(&mut *w, self)?;
+        w.write_all(b"\n")
+    }
+}
+
+/// Write the responses as newline-delimited JSON (NDJSON), one compact
+/// JSON object per line.
+///
+/// Each line may be read back with `serde_json::from_str::<RouteResponse>`.
+#[cfg(feature = "serde")]
+pub fn write_all_ndjson<'a, W, I>(responses: I, w: &mut W) -> std::io::Result<()>
+where
+    W: std::io::Write,
+    I: IntoIterator<Item = &'a RouteResponse>,
+{
+    for response in responses {
+        response.write_ndjson(w)?;
+    }
+    Ok(())
 }
@@ -1,12 +1,16 @@
 //! Given a set of source and destination coordinates, determine the
 //! distance and/or duration to travel between those locations.
 
+use itertools::Itertools;
 use thiserror::Error;
 
 use crate::Point;
-use crate::osrm_response_types::Waypoint;
-use crate::request_types::{Bearing, Exclude, Snapping};
-use crate::services::{Approach, DimensionMismatch};
+use crate::osrm_response_types::{ResponseCode, Waypoint};
+use crate::request_types::{Bearing, Exclude, ExcludeSet, Snapping};
+use crate::services::{
+    Approach, DimensionMismatch, UrlParts, unconstrained_approaches, url_approach, url_bearing,
+    url_coordinates, url_exclude, url_hint, url_radius,
+};
 
 /// The request object passed to the table service. Constructed
 /// through [`TableRequestBuilder::build`] which verifies the
@@ -36,6 +40,79 @@ pub struct TableRequest<'a> {
     pub(crate) snapping: Option<Snapping>,
 }
 
+impl TableRequest<'_> {
+    /// The path and query of the url for the request, for sending it with a
+    /// custom HTTP client. See
+    /// [`RouteRequest::to_url_parts`](crate::route::RouteRequest::to_url_parts).
+    ///
+    /// The sources are followed by the destinations in the path.
+    pub fn to_url_parts(&self, profile: &str, version: &str, format: Option<&str>) -> UrlParts {
+        let len_sources = self.sources.len();
+        let len_destinations = self.destinations.len();
+
+        let coordinates = url_coordinates(self.sources.iter().chain(self.destinations.iter()));
+        let mut parts = UrlParts::new("table", version, profile, &coordinates, format);
+        parts.push("sources", (0..len_sources).join(";"));
+        parts.push(
+            "destinations",
+            (len_sources..(len_sources + len_destinations)).join(";"),
+        );
+        parts.push("generate_hints", self.generate_hints);
+        if self.annotations != TableAnnotation::None {
+            parts.push("annotations", self.annotations.url_form());
+        }
+
+        // Per-point options are given for the sources then the destinations,
+        // with empty values for whichever side is unset
+        let mut push_per_point =
+            |key, sources: Option<Vec<String>>, destinations: Option<Vec<String>>| {
+                if sources.is_none() && destinations.is_none() {
+                    return;
+                }
+                let sources = sources.unwrap_or_else(|| vec![String::new(); len_sources]);
+                let destinations =
+                    destinations.unwrap_or_else(|| vec![String::new(); len_destinations]);
+                parts.push(key, sources.iter().chain(destinations.iter()).join(";"));
+            };
+        push_per_point(
+            "bearings",
+            self.source_bearings
+                .map(|b| b.iter().map(url_bearing).collect()),
+            self.destination_bearings
+                .map(|b| b.iter().map(url_bearing).collect()),
+        );
+        push_per_point(
+            "radiuses",
+            self.source_radiuses
+                .map(|r| r.iter().map(url_radius).collect()),
+            self.destination_radiuses
+                .map(|r| r.iter().map(url_radius).collect()),
+        );
+        push_per_point(
+            "hints",
+            self.source_hints.map(|h| h.iter().map(url_hint).collect()),
+            self.destination_hints
+                .map(|h| h.iter().map(url_hint).collect()),
+        );
+        push_per_point(
+            "approaches",
+            self.source_approaches
+                .map(|a| a.iter().map(url_approach).collect()),
+            self.destination_approaches
+                .map(|a| a.iter().map(url_approach).collect()),
+        );
+
+        if let Some(exclude) = self.exclude {
+            parts.push("exclude", url_exclude(exclude));
+        }
+        if let Some(snapping) = self.snapping {
+            parts.push("snapping", snapping.url_form());
+        }
+
+        parts
+    }
+}
+
 /// Helper struct for building a [`TableRequest`].
 ///
 /// Set table options using the struct methods before calling
@@ -147,6 +224,7 @@ pub struct TableRequestBuilder<'a> {
     destination_approaches: Option<&'a [Approach]>,
     exclude: Option<&'a [Exclude]>,
     snapping: Option<Snapping>,
+    strict: bool,
 }
 
 impl<'a> TableRequestBuilder<'a> {
@@ -172,6 +250,7 @@ impl<'a> TableRequestBuilder<'a> {
             destination_approaches: None,
             exclude: None,
             snapping: None,
+            strict: false,
         }
     }
 
@@ -257,7 +336,12 @@ impl<'a> TableRequestBuilder<'a> {
         self
     }
 
-    /// Enables or disables hint generation.
+    /// Sets whether OSRM generates hints for the sources and destinations in
+    /// the response. Enabled by default, as in OSRM.
+    ///
+    /// Hints are needed to pass the snapped points to later requests, see
+    /// [`source_hints`](Self::source_hints). A table is often a one-off
+    /// request, so disable them to reduce the response size otherwise.
     pub fn generate_hints(mut self, generate_hints: bool) -> Self {
         self.generate_hints = generate_hints;
         self
@@ -311,12 +395,36 @@ impl<'a> TableRequestBuilder<'a> {
         self
     }
 
+    /// Sets which road classes should be excluded from the request using
+    /// an [`ExcludeSet`], which guarantees at compile time that all
+    /// excludes belong to the same transport mode.
+    ///
+    /// Equivalent to calling [`exclude`](Self::exclude) with
+    /// [`ExcludeSet::as_slice`].
+    pub fn exclude_set<M>(mut self, exclude_set: &'a ExcludeSet<M>) -> Self {
+        self.exclude = Some(exclude_set.as_slice());
+        self
+    }
+
     /// Sets the snapping mode for matching coordinates to the road network.
     pub fn snapping(mut self, snapping: Snapping) -> Self {
         self.snapping = Some(snapping);
         self
     }
 
+    /// Enables strict validation in [`build`](Self::build), turning checks which
+    /// are otherwise skipped into errors. Disabled by default.
+    ///
+    /// In strict mode, setting approaches other than `Approach::Unrestricted`
+    /// without any bearings or radiuses returns `UnconstrainedApproaches`, as
+    /// some OSRM configurations reject approaches without a snapping constraint.
+    /// Otherwise, with `feature="log"`, a warning is logged through the `log`
+    /// crate.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
     /// Builds the [`TableRequest`], validating that all configuration is consistent.
     ///
     /// Returns an error if dimensions mismatch or invalid parameters are detected.
@@ -446,7 +554,31 @@ impl<'a> TableRequestBuilder<'a> {
             }
         }
 
-        Ok(TableRequest {
+        let unconstrained = unconstrained_approaches(
+            self.source_approaches,
+            self.source_bearings.is_some() || self.source_radiuses.is_some(),
+        ) || unconstrained_approaches(
+            self.destination_approaches,
+            self.destination_bearings.is_some() || self.destination_radiuses.is_some(),
+        );
+        if unconstrained && self.strict {
+            return Err(TableRequestError::UnconstrainedApproaches);
+        }
+        #[cfg(feature = "log")]
+        if unconstrained {
+            crate::services::warn_unconstrained_approaches();
+        }
+
+        Ok(self.build_unchecked())
+    }
+
+    /// Builds a [`TableRequest`] without validating the configured parameters.
+    ///
+    /// A performance escape hatch for hot loops over inputs which are already
+    /// known to be valid. As no checks are performed, the resulting request may
+    /// be rejected by OSRM. Prefer [`build`](Self::build) otherwise.
+    pub fn build_unchecked(&self) -> TableRequest<'a> {
+        TableRequest {
             sources: self.sources,
             destinations: self.destinations,
             annotations: self.annotations,
@@ -464,7 +596,7 @@ impl<'a> TableRequestBuilder<'a> {
             destination_approaches: self.destination_approaches,
             exclude: self.exclude,
             snapping: self.snapping,
-        })
+        }
     }
 }
 
@@ -473,21 +605,21 @@ impl<'a> TableRequestBuilder<'a> {
 #[derive(Error, Debug)]
 pub enum TableRequestError {
     /// No sources in table request
-    #[error("No sources in table request")]
+    #[error("No sources in table request; pass at least one source point")]
     EmptySources,
     /// No destinations in table request
-    #[error("No destinations in table request")]
+    #[error("No destinations in table request; pass at least one destination point")]
     EmptyDestinations,
     /// Fallback speed must be greater than 0.0
-    #[error("Fallback speed must be greater than 0.0")]
+    #[error("Fallback speed must be greater than 0.0; pass a speed in meters per second")]
     NonPositiveFallbackSpeed,
     /// Scale factor must be greater than 0.0
-    #[error("Scale factor must be greater than 0.0")]
+    #[error("Scale factor must be greater than 0.0; use 1.0 to leave durations unscaled")]
     NonPositiveScaleFactor,
     /// Fallback speed and coordinate are co-dependent and must both be
     /// specified, or both null
     #[error(
-        "Fallback speed and coordinate are co-dependent and must both be specified, or both null"
+        "Fallback speed and coordinate are co-dependent and must both be specified, or both null; set both with `fallback`"
     )]
     IncompleteFallbackPair,
     /// Scale factor multiplies duration, so annotations must be
@@ -498,14 +630,22 @@ pub enum TableRequestError {
     ScaleFactorRequiresDuration,
     /// Mismatch of number of elements between points and one
     /// of the array-like options.
-    #[error("Mismatch of dimensions between Points and {0:?}")]
+    #[error("Mismatch of dimensions between Points and {0:?}; {hint}", hint = .0.hint())]
     DimensionMismatch(DimensionMismatch),
     /// Cannot mix excludes of different [`Exclude`] variants.
-    #[error("Exclude types are not all of the same type")]
+    #[error(
+        "Exclude types are not all of the same type; use the excludes of a single profile, eg. only `CarExclude`"
+    )]
     DifferentExcludeTypes,
     /// Radius values must be non-negative.
-    #[error("Radii must be non-negative")]
+    #[error("Radii must be non-negative; pass `None` for an unlimited radius")]
     NegativeRadius,
+    /// In strict mode, approaches must be accompanied by bearings or
+    /// radiuses to constrain snapping.
+    #[error(
+        "Approaches are set without bearings or radiuses to constrain snapping; set bearings or radiuses, or disable strict mode"
+    )]
+    UnconstrainedApproaches,
 }
 
 /// Which metrics should the table service calculate.
@@ -595,7 +735,34 @@ pub struct TableResponse {
     pub durations: Option<Vec<Vec<Option<f64>>>>,
     /// Array of arrays that stores the matrix in row-major order. `distances[i][j]` gives the travel distance from the i-th source to the j-th destination. Values are given in meters. Can be `null` if no route between `i` and `j` can be found
     pub distances: Option<Vec<Vec<Option<f64>>>>,
+    /// Array of arrays that stores the matrix in row-major order. `weights[i][j]` gives the weight of the route from the i-th source to the j-th destination, in the units of the profile's weight. Stock `osrm-routed` does not accept a weight table annotation, so this is only present from a backend modified to return weights, otherwise it is `None`.
+    pub weights: Option<Vec<Vec<Option<f64>>>>,
     /// Array of arrays containing the fallback speeds used for each cell (if used).
     /// Will be absent if fallback_speed is not used.
     pub fallback_speed_cells: Option<Vec<Vec<f64>>>,
 }
+
+impl TableResponse {
+    /// The [`code`](Self::code) of the response as a [`ResponseCode`]. See
+    /// [`RouteResponse::code_enum`](crate::route::RouteResponse::code_enum).
+    pub fn code_enum(&self) -> ResponseCode {
+        ResponseCode::from(self.code.as_str())
+    }
+
+    /// The weight from the i-th source to the j-th destination.
+    ///
+    /// Returns `None` if weights were not returned, the indices are out of
+    /// bounds, or no route between them was found.
+    pub fn weight(&self, i: usize, j: usize) -> Option<f64> {
+        self.weights.as_ref()?.get(i)?.get(j).copied().flatten()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TableResponse {
+    /// Parse the JSON body of a response returned by OSRM. See
+    /// [`RouteResponse::from_json`](crate::route::RouteResponse::from_json).
+    pub fn from_json(json: &str) -> Result<Self, crate::errors::OsrmError> {
+        crate::serde_ops::from_json(json, |response: &Self| &response.code)
+    }
+}
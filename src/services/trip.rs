@@ -1,13 +1,20 @@
 //! Given a set of coordinates, uses greedy heuristic to route the fastest
 //! path through those coordinates.
 
+use std::borrow::Cow;
+
 use thiserror::Error;
 
 use crate::{
     Point,
-    osrm_response_types::{Route, TripWaypoint},
-    request_types::{Bearing, Exclude, GeometryType, OverviewZoom, Snapping},
-    services::{Approach, DimensionMismatch},
+    osrm_response_types::{ResponseCode, Route, TripWaypoint},
+    request_types::{
+        Annotations, Bearing, Exclude, ExcludeSet, GeometryType, OverviewZoom, Snapping,
+    },
+    services::{
+        Approach, DimensionMismatch, Location, UrlParts, split_locations, unconstrained_approaches,
+        url_approach, url_bearing, url_coordinates, url_exclude, url_hint, url_list, url_radius,
+    },
 };
 
 /// The request object passed to the trip service. Constructed
@@ -18,24 +25,64 @@ use crate::{
 #[derive(Clone, Debug)]
 #[allow(dead_code)]
 pub struct TripRequest<'a> {
-    pub(crate) points: &'a [Point],
+    pub(crate) points: Cow<'a, [Point]>,
     pub(crate) roundtrip: bool,
     pub(crate) source: TripSource,
     pub(crate) destination: TripDestination,
     pub(crate) steps: bool,
-    pub(crate) annotations: bool,
+    pub(crate) annotations: Annotations,
     pub(crate) geometry: GeometryType,
     pub(crate) overview: OverviewZoom,
     pub(crate) bearings: Option<&'a [Option<Bearing>]>,
     pub(crate) radiuses: Option<&'a [Option<f64>]>,
     pub(crate) generate_hints: bool,
-    pub(crate) hints: Option<&'a [Option<&'a str>]>,
+    pub(crate) hints: Option<Cow<'a, [Option<&'a str>]>>,
     pub(crate) approaches: Option<&'a [Approach]>,
     pub(crate) exclude: Option<&'a [Exclude]>,
     pub(crate) snapping: Option<Snapping>,
     pub(crate) skip_waypoints: bool,
 }
 
+impl TripRequest<'_> {
+    /// The path and query of the url for the request, for sending it with a
+    /// custom HTTP client. See
+    /// [`RouteRequest::to_url_parts`](crate::route::RouteRequest::to_url_parts).
+    pub fn to_url_parts(&self, profile: &str, version: &str, format: Option<&str>) -> UrlParts {
+        let coordinates = url_coordinates(self.points.iter());
+        let mut parts = UrlParts::new("trip", version, profile, &coordinates, format);
+        parts.push("steps", self.steps);
+        parts.push("geometries", self.geometry.url_form());
+        parts.push("overview", self.overview.url_form());
+        parts.push("annotations", self.annotations.url_form());
+        parts.push("roundtrip", self.roundtrip);
+        parts.push("source", self.source.url_form());
+        parts.push("destination", self.destination.url_form());
+        parts.push("generate_hints", self.generate_hints);
+        parts.push("skip_waypoints", self.skip_waypoints);
+
+        if let Some(bearings) = self.bearings {
+            parts.push("bearings", url_list(bearings, url_bearing));
+        }
+        if let Some(radiuses) = self.radiuses {
+            parts.push("radiuses", url_list(radiuses, url_radius));
+        }
+        if let Some(hints) = &self.hints {
+            parts.push("hints", url_list(hints, url_hint));
+        }
+        if let Some(approaches) = self.approaches {
+            parts.push("approaches", url_list(approaches, url_approach));
+        }
+        if let Some(exclude) = self.exclude {
+            parts.push("exclude", url_exclude(exclude));
+        }
+        if let Some(snapping) = self.snapping {
+            parts.push("snapping", snapping.url_form());
+        }
+
+        parts
+    }
+}
+
 /// Helper struct for building a [`TripRequest`].
 ///
 /// Set options using the struct methods before calling
@@ -66,8 +113,8 @@ pub struct TripRequest<'a> {
 /// - **`overview`** (*default:* `OverviewZoom::Simplified`) — Controls the
 ///   generalization level of the route overview geometry. See [`OverviewZoom`].
 ///
-/// - **`annotations`** (*default:* `false`) — When enabled, includes metadata such as
-///   distance, duration, and speed for each segment of the route.
+/// - **`annotations`** (*default:* `Annotations::NONE`) — Which metadata, such as
+///   distance, duration and speed, to include for each segment. See [`Annotations`].
 ///
 /// - **`exclude`** (*optional*) — A slice of [`Exclude`] values, all of the same
 ///   transport mode (e.g., all `Exclude::Car` or all `Exclude::Bicycle`),
@@ -126,9 +173,9 @@ pub struct TripRequest<'a> {
 /// ```
 #[derive(Clone, Debug)]
 pub struct TripRequestBuilder<'a> {
-    points: &'a [Point],
+    points: Cow<'a, [Point]>,
     steps: bool,
-    annotations: bool,
+    annotations: Annotations,
     geometry: GeometryType,
     overview: OverviewZoom,
     roundtrip: bool,
@@ -137,11 +184,12 @@ pub struct TripRequestBuilder<'a> {
     bearings: Option<&'a [Option<Bearing>]>,
     radiuses: Option<&'a [Option<f64>]>,
     generate_hints: bool,
-    hints: Option<&'a [Option<&'a str>]>,
+    hints: Option<Cow<'a, [Option<&'a str>]>>,
     approaches: Option<&'a [Approach]>,
     exclude: Option<&'a [Exclude]>,
     snapping: Option<Snapping>,
     skip_waypoints: bool,
+    strict: bool,
 }
 
 impl<'a> TripRequestBuilder<'a> {
@@ -150,9 +198,9 @@ impl<'a> TripRequestBuilder<'a> {
     /// The builder can then be customized using its setter methods.
     pub fn new(points: &'a [Point]) -> Self {
         Self {
-            points,
+            points: Cow::Borrowed(points),
             steps: false,
-            annotations: false,
+            annotations: Annotations::NONE,
             geometry: GeometryType::Polyline,
             overview: OverviewZoom::False,
             roundtrip: true,
@@ -166,16 +214,38 @@ impl<'a> TripRequestBuilder<'a> {
             exclude: None,
             snapping: None,
             skip_waypoints: false,
+            strict: false,
         }
     }
 
+    /// Creates a new [`TripRequestBuilder`] through the given [`Location`]s,
+    /// sending the hints of any hinted locations. See [`Location`] for when
+    /// OSRM uses the hints.
+    pub fn from_locations(locations: &[Location<'a>]) -> Self {
+        let (points, hints) = split_locations(locations);
+        Self {
+            points: Cow::Owned(points),
+            hints: hints.map(Cow::Owned),
+            ..Self::new(&[])
+        }
+    }
+
+    /// Creates a new [`TripRequestBuilder`] with default parameters and no points.
+    ///
+    /// Allows options to be configured before the points are known.
+    /// [`points`](Self::points) must be called before [`build`](Self::build),
+    /// otherwise `build` will return `InsufficientPoints`.
+    pub fn empty() -> Self {
+        Self::new(&[])
+    }
+
     /// Overwrite the points provided at construction of the builder. Useful
     /// for reusing a builder with the same options.
     ///
     /// Take care that array-like options are still the same length as points,
     /// [`build`](Self::build) will throw an error if not.
     pub fn points(mut self, points: &'a [Point]) -> Self {
-        self.points = points;
+        self.points = Cow::Borrowed(points);
         self
     }
 
@@ -185,9 +255,10 @@ impl<'a> TripRequestBuilder<'a> {
         self
     }
 
-    /// Sets whether to include per-segment annotations in the route.
-    pub fn annotations(mut self, annotations: bool) -> Self {
-        self.annotations = annotations;
+    /// Sets the per-segment annotations to include with each leg, either
+    /// [`Annotations`] or a `bool` to include all or none of them.
+    pub fn annotations(mut self, annotations: impl Into<Annotations>) -> Self {
+        self.annotations = annotations.into();
         self
     }
 
@@ -240,10 +311,12 @@ impl<'a> TripRequestBuilder<'a> {
         self
     }
 
-    /// Sets per-point search radiuses (in meters) for coordinate snapping.
+    /// Sets whether OSRM generates hints for the waypoints in the response.
+    /// Enabled by default, as in OSRM.
     ///
-    /// Each radius must correspond to the point at the same index. Radii must
-    /// be positive. Passing None corresponds to an infinite search radius.
+    /// Hints are needed to pass the snapped waypoints to later requests, see
+    /// [`hints`](Self::hints). Disable them to reduce the response size
+    /// otherwise.
     pub fn generate_hints(mut self, generate_hints: bool) -> Self {
         self.generate_hints = generate_hints;
         self
@@ -257,7 +330,7 @@ impl<'a> TripRequestBuilder<'a> {
     /// Passing hints will result in radiuses, bearings,
     /// approaches being ignored for that point.
     pub fn hints(mut self, coordinate_hints: &'a [Option<&'a str>]) -> Self {
-        self.hints = Some(coordinate_hints);
+        self.hints = Some(Cow::Borrowed(coordinate_hints));
         self
     }
 
@@ -275,6 +348,17 @@ impl<'a> TripRequestBuilder<'a> {
         self
     }
 
+    /// Sets which road classes should be excluded from the request using
+    /// an [`ExcludeSet`], which guarantees at compile time that all
+    /// excludes belong to the same transport mode.
+    ///
+    /// Equivalent to calling [`exclude`](Self::exclude) with
+    /// [`ExcludeSet::as_slice`].
+    pub fn exclude_set<M>(mut self, exclude_set: &'a ExcludeSet<M>) -> Self {
+        self.exclude = Some(exclude_set.as_slice());
+        self
+    }
+
     /// Sets the snapping behavior for input coordinates.
     pub fn snapping(mut self, snapping: Snapping) -> Self {
         self.snapping = Some(snapping);
@@ -287,6 +371,19 @@ impl<'a> TripRequestBuilder<'a> {
         self
     }
 
+    /// Enables strict validation in [`build`](Self::build), turning checks which
+    /// are otherwise skipped into errors. Disabled by default.
+    ///
+    /// In strict mode, setting approaches other than `Approach::Unrestricted`
+    /// without any bearings or radiuses returns `UnconstrainedApproaches`, as
+    /// some OSRM configurations reject approaches without a snapping constraint.
+    /// Otherwise, with `feature="log"`, a warning is logged through the `log`
+    /// crate.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
     /// Builds a [`TripRequest`] from the configured parameters.
     ///
     /// Performs validation to ensure all per-point array options
@@ -299,6 +396,7 @@ impl<'a> TripRequestBuilder<'a> {
     /// - Array lengths do not match the number of points.
     /// - Exclude types mix transport modes.
     /// - Any radii are negative.
+    /// - In [`strict`](Self::strict) mode, approaches are set without bearings or radiuses.
     pub fn build(&self) -> Result<TripRequest<'a>, TripRequestError> {
         if self.points.len() < 2 {
             return Err(TripRequestError::InsufficientPoints);
@@ -326,7 +424,7 @@ impl<'a> TripRequestBuilder<'a> {
         }
 
         #[allow(clippy::collapsible_if)]
-        if let Some(hints) = self.hints {
+        if let Some(hints) = &self.hints {
             if hints.len() != self.points.len() {
                 return Err(TripRequestError::DimensionMismatch(
                     DimensionMismatch::Hints,
@@ -354,8 +452,29 @@ impl<'a> TripRequestBuilder<'a> {
             }
         }
 
-        Ok(TripRequest {
-            points: self.points,
+        let unconstrained = unconstrained_approaches(
+            self.approaches,
+            self.bearings.is_some() || self.radiuses.is_some(),
+        );
+        if unconstrained && self.strict {
+            return Err(TripRequestError::UnconstrainedApproaches);
+        }
+        #[cfg(feature = "log")]
+        if unconstrained {
+            crate::services::warn_unconstrained_approaches();
+        }
+
+        Ok(self.build_unchecked())
+    }
+
+    /// Builds a [`TripRequest`] without validating the configured parameters.
+    ///
+    /// A performance escape hatch for hot loops over inputs which are already
+    /// known to be valid. As no checks are performed, the resulting request may
+    /// be rejected by OSRM. Prefer [`build`](Self::build) otherwise.
+    pub fn build_unchecked(&self) -> TripRequest<'a> {
+        TripRequest {
+            points: self.points.clone(),
             steps: self.steps,
             annotations: self.annotations,
             geometry: self.geometry,
@@ -366,12 +485,12 @@ impl<'a> TripRequestBuilder<'a> {
             bearings: self.bearings,
             radiuses: self.radiuses,
             generate_hints: self.generate_hints,
-            hints: self.hints,
+            hints: self.hints.clone(),
             approaches: self.approaches,
             exclude: self.exclude,
             snapping: self.snapping,
             skip_waypoints: self.skip_waypoints,
-        })
+        }
     }
 }
 
@@ -380,18 +499,26 @@ impl<'a> TripRequestBuilder<'a> {
 #[derive(Error, Debug)]
 pub enum TripRequestError {
     /// Trip requires at least 2 points.
-    #[error("Trip requires at least 2 points")]
+    #[error("Trip requires at least 2 points; pass at least a start and an end point")]
     InsufficientPoints,
     /// Mismatch of number of elements between points and one
     /// of the array-like options.
-    #[error("Mismatch of dimensions between Points and {0:?}")]
+    #[error("Mismatch of dimensions between Points and {0:?}; {hint}", hint = .0.hint())]
     DimensionMismatch(DimensionMismatch),
     /// Cannot mix excludes of different [`Exclude`] variants.
-    #[error("Exclude types are not all of the same type")]
+    #[error(
+        "Exclude types are not all of the same type; use the excludes of a single profile, eg. only `CarExclude`"
+    )]
     DifferentExcludeTypes,
     /// Radius values must be non-negative.
-    #[error("Radii must be non-negative")]
+    #[error("Radii must be non-negative; pass `None` for an unlimited radius")]
     NegativeRadius,
+    /// In strict mode, approaches must be accompanied by bearings or
+    /// radiuses to constrain snapping.
+    #[error(
+        "Approaches are set without bearings or radiuses to constrain snapping; set bearings or radiuses, or disable strict mode"
+    )]
+    UnconstrainedApproaches,
 }
 
 /// The response type returned by the Trip service.
@@ -412,6 +539,64 @@ pub struct TripResponse {
     pub waypoints: Option<Vec<TripWaypoint>>,
 }
 
+#[cfg(feature = "serde")]
+impl TripResponse {
+    /// Parse the JSON body of a response returned by OSRM. See
+    /// [`RouteResponse::from_json`](crate::route::RouteResponse::from_json).
+    pub fn from_json(json: &str) -> Result<Self, crate::errors::OsrmError> {
+        crate::serde_ops::from_json(json, |response: &Self| &response.code)
+    }
+}
+
+impl TripResponse {
+    /// The [`code`](Self::code) of the response as a [`ResponseCode`]. See
+    /// [`RouteResponse::code_enum`](crate::route::RouteResponse::code_enum).
+    pub fn code_enum(&self) -> ResponseCode {
+        ResponseCode::from(self.code.as_str())
+    }
+
+    /// The input indices of the points in visiting order, with the start
+    /// repeated at the end to close the loop, eg. to draw a roundtrip.
+    ///
+    /// The response does not record whether a roundtrip was requested. A
+    /// roundtrip has a leg for every waypoint, the last returning to the
+    /// start, whereas an open trip has one leg fewer, so the trip is
+    /// considered a roundtrip if it has as many legs as waypoints. For an open
+    /// trip, the visiting order is simply the input indices sorted by
+    /// [`waypoint_index`](crate::osrm_response_types::TripWaypoint::waypoint_index).
+    ///
+    /// Returns `None` for an open trip, if there is not exactly one trip, eg.
+    /// the points were not all connected, or if the waypoints were skipped.
+    pub fn roundtrip_order(&self) -> Option<Vec<usize>> {
+        let [trip] = self.trips.as_slice() else {
+            return None;
+        };
+        let waypoints = self.waypoints.as_ref()?;
+        if waypoints.is_empty() || trip.legs.len() != waypoints.len() {
+            return None;
+        }
+        let mut order: Vec<usize> = (0..waypoints.len()).collect();
+        order.sort_by_key(|&i| waypoints[i].waypoint_index);
+        order.push(order[0]);
+        Some(order)
+    }
+}
+
+/// The duration of a trip through points in the optimised order compared to
+/// the order the points were given in. Returned by
+/// [`OsrmService::trip_improvement`](crate::services::OsrmService::trip_improvement).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TripImprovement {
+    /// The duration, in seconds, of the route through the points in
+    /// input order.
+    pub input_order_duration: f64,
+    /// The duration, in seconds, of the optimised trip.
+    pub optimised_duration: f64,
+    /// The reduction in duration of the optimised trip as a percentage of
+    /// `input_order_duration`. `0.0` when `input_order_duration` is zero.
+    pub improvement_percent: f64,
+}
+
 /// For specifying whether a trip may start anywhere or only
 /// at the first provided point.
 ///
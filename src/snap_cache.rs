@@ -0,0 +1,72 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use crate::nearest::{NearestRequest, NearestResponse};
+
+/// Coordinates are rounded to this many decimal places to form the cache key,
+/// the precision at which coordinates are sent to OSRM.
+const KEY_PRECISION: f64 = 1e6;
+
+/// A least recently used cache of single result nearest responses, keyed by
+/// the rounded coordinates of the request.
+///
+/// Lookups are linear in the capacity, which is expected to be small, eg. a
+/// handful of depots.
+#[derive(Debug)]
+pub(crate) struct SnapCache {
+    capacity: usize,
+    // Most recently used first
+    entries: Mutex<VecDeque<((i64, i64), NearestResponse)>>,
+}
+
+impl SnapCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// The key of the request, or `None` if the response depends on more
+    /// than the coordinates, in which case it is not cached.
+    fn key(request: &NearestRequest) -> Option<(i64, i64)> {
+        let cacheable = request.number == 1
+            && request.bearing.is_none()
+            && request.radius.is_none()
+            && request.approach.is_none()
+            && request.exclude.is_none()
+            && request.snapping.is_none();
+        cacheable.then(|| {
+            (
+                (request.point.latitude() * KEY_PRECISION).round() as i64,
+                (request.point.longitude() * KEY_PRECISION).round() as i64,
+            )
+        })
+    }
+
+    /// The cached response to the request, marking it as recently used.
+    pub(crate) fn get(&self, request: &NearestRequest) -> Option<NearestResponse> {
+        let key = Self::key(request)?;
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        let index = entries.iter().position(|(k, _)| *k == key)?;
+        let entry = entries.remove(index)?;
+        let response = entry.1.clone();
+        entries.push_front(entry);
+        Some(response)
+    }
+
+    /// Cache the response to the request, evicting the least recently used
+    /// entry if the cache is full.
+    pub(crate) fn insert(&self, request: &NearestRequest, response: &NearestResponse) {
+        let Some(key) = Self::key(request) else {
+            return;
+        };
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|(k, _)| *k != key);
+        entries.truncate(self.capacity - 1);
+        entries.push_front((key, response.clone()));
+    }
+}
@@ -0,0 +1,50 @@
+//! A version of the remote engine for WebAssembly frontends, calling the Web
+//! API of OSRM with the browser's `fetch`, eg. to route from the browser
+//! against a CORS-enabled `osrm-routed`. Locked behind the `wasm` feature flag.
+//!
+//! The urls and response parsing are exactly those of the remote engine, only
+//! the transport differs. The services are `async fn`s mirroring the remote
+//! engine, see [`crate::remote`] for more information about profiles and
+//! endpoints.
+//!
+//! ## Target requirements
+//!
+//! - Build for `wasm32-unknown-unknown` with only `feature="wasm"`. The
+//!   `remote` and `native` features pull in ureq and the C++ wrapper, neither
+//!   of which build for the browser.
+//! - The endpoint must send CORS headers allowing the page's origin, eg.
+//!   `Access-Control-Allow-Origin`. `osrm-routed` sends
+//!   `Access-Control-Allow-Origin: *` by default, a reverse proxy in front of
+//!   it must pass the header on.
+//! - Pages served over https can only fetch from an https endpoint.
+//!
+//! ## Fetch
+//!
+//! The request is sent through the [`Fetch`] implementation the engine is
+//! constructed with, which wraps the fetch binding of the frontend, eg.
+//! `gloo-net`:
+//!
+//! ```ignore
+//! use osrm_interface::wasm::{Fetch, FetchResponse};
+//!
+//! struct GlooFetch;
+//!
+//! impl Fetch for GlooFetch {
+//!     async fn get(&self, url: &str) -> Result<FetchResponse, String> {
+//!         let response = gloo_net::http::Request::get(url)
+//!             .send()
+//!             .await
+//!             .map_err(|e| e.to_string())?;
+//!         Ok(FetchResponse {
+//!             status: response.status(),
+//!             body: response.text().await.map_err(|e| e.to_string())?,
+//!         })
+//!     }
+//! }
+//! ```
+
+mod osrm_engine;
+#[cfg_attr(docsrs, doc(cfg(feature = "wasm")))]
+pub use osrm_engine::{Fetch, FetchResponse, OsrmEngine};
+
+pub use crate::Profile;
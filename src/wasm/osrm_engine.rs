@@ -0,0 +1,195 @@
+use std::future::Future;
+
+use crate::errors::{OsrmError, RemoteOsrmError};
+use crate::r#match::{MatchRequest, MatchResponse};
+use crate::nearest::{NearestRequest, NearestResponse};
+use crate::route::{RouteRequest, RouteResponse};
+use crate::table::{TableRequest, TableResponse};
+use crate::trip::{TripRequest, TripResponse};
+use crate::wasm::Profile;
+
+/// The transport of the wasm [`OsrmEngine`], sending a GET request with the
+/// fetch binding of the frontend. See the [module](crate::wasm) documentation
+/// for an example.
+pub trait Fetch {
+    /// Send a GET request to `url`, resolving to the response, or a
+    /// description of the failure if no response was received, eg. when the
+    /// endpoint is unreachable or the request is blocked by CORS.
+    fn get(&self, url: &str) -> impl Future<Output = Result<FetchResponse, String>>;
+}
+
+/// A response received by a [`Fetch`] implementation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FetchResponse {
+    /// The HTTP status code of the response.
+    pub status: u16,
+    /// The body of the response, as text.
+    pub body: String,
+}
+
+/// The engine for calling into osrm-backend through the HTTP web API from
+/// WebAssembly, sending requests through `F`.
+///
+/// ```
+/// # use osrm_interface::wasm::{Fetch, FetchResponse};
+/// # struct GlooFetch;
+/// # impl Fetch for GlooFetch {
+/// #     async fn get(&self, url: &str) -> Result<FetchResponse, String> {
+/// #         unimplemented!()
+/// #     }
+/// # }
+/// use osrm_interface::wasm::{OsrmEngine, Profile};
+/// use osrm_interface::{Point, route::RouteRequestBuilder};
+///
+/// async fn distance(engine: &OsrmEngine<GlooFetch>, points: &[Point]) -> f64 {
+///     let route_request = RouteRequestBuilder::new(points)
+///         .build()
+///         .expect("Failed to build RouteRequest");
+///     let response = engine.route(&route_request).await.expect("Failed to route");
+///     response.routes[0].distance
+/// }
+///
+/// let engine = OsrmEngine::new("https://maps.example.com/osrm".to_string(), Profile::Car, GlooFetch);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(docsrs, doc(cfg(feature = "wasm")))]
+pub struct OsrmEngine<F> {
+    endpoint: String,
+    profile: Profile,
+    fetch: F,
+}
+
+impl<F: Fetch> OsrmEngine<F> {
+    /// Initialise the engine, sending requests through `fetch`.
+    ///
+    /// `endpoint` and `profile` are as for
+    /// [`remote::OsrmEngine::new`](crate::remote::OsrmEngine::new).
+    pub fn new(endpoint: String, profile: Profile, fetch: F) -> Self {
+        Self {
+            endpoint: endpoint.trim_end_matches('/').to_string(),
+            profile,
+            fetch,
+        }
+    }
+
+    /// The [`Fetch`] implementation sending the requests.
+    pub fn fetch(&self) -> &F {
+        &self.fetch
+    }
+
+    /// Send a GET request to the endpoint, returning the body of an OSRM
+    /// response.
+    ///
+    /// The browser follows redirects itself, otherwise statuses are handled as
+    /// by the remote engine: only `200` is a successful response, and other
+    /// statuses with an OSRM error in the body are reported as
+    /// [`OsrmError::Service`].
+    async fn get(&self, url: String) -> Result<String, OsrmError> {
+        let response = self
+            .fetch
+            .get(&url)
+            .await
+            .map_err(|e| OsrmError::Remote(RemoteOsrmError::EndpointError(e)))?;
+        if response.body.trim().is_empty() {
+            return Err(OsrmError::EmptyResponse(format!(
+                "Endpoint responded with {} and an empty body",
+                response.status
+            )));
+        }
+        if response.status != 200 {
+            if let Some(error) = crate::serde_ops::error_response(&response.body) {
+                return Err(error);
+            }
+            return Err(OsrmError::Remote(RemoteOsrmError::EndpointError(format!(
+                "Unexpected status {}",
+                response.status
+            ))));
+        }
+        Ok(response.body)
+    }
+
+    /// The url which [`table`](Self::table) sends the request to.
+    pub fn table_url(&self, table_request: &TableRequest) -> String {
+        table_request
+            .to_url_parts(self.profile.url_form(), "v1", None)
+            .to_url(&self.endpoint)
+    }
+
+    /// See [`remote::OsrmEngine::table`](crate::remote::OsrmEngine::table).
+    pub async fn table(
+        &self,
+        table_request: &TableRequest<'_>,
+    ) -> Result<TableResponse, OsrmError> {
+        let response = self.get(self.table_url(table_request)).await?;
+        TableResponse::from_json(&response).map_err(parse_error)
+    }
+
+    /// The url which [`route`](Self::route) sends the request to.
+    pub fn route_url(&self, route_request: &RouteRequest) -> String {
+        route_request
+            .to_url_parts(self.profile.url_form(), "v1", None)
+            .to_url(&self.endpoint)
+    }
+
+    /// See [`remote::OsrmEngine::route`](crate::remote::OsrmEngine::route).
+    pub async fn route(
+        &self,
+        route_request: &RouteRequest<'_>,
+    ) -> Result<RouteResponse, OsrmError> {
+        let response = self.get(self.route_url(route_request)).await?;
+        RouteResponse::from_json(&response).map_err(parse_error)
+    }
+
+    /// The url which [`trip`](Self::trip) sends the request to.
+    pub fn trip_url(&self, trip_request: &TripRequest) -> String {
+        trip_request
+            .to_url_parts(self.profile.url_form(), "v1", None)
+            .to_url(&self.endpoint)
+    }
+
+    /// See [`remote::OsrmEngine::trip`](crate::remote::OsrmEngine::trip).
+    pub async fn trip(&self, trip_request: &TripRequest<'_>) -> Result<TripResponse, OsrmError> {
+        let response = self.get(self.trip_url(trip_request)).await?;
+        TripResponse::from_json(&response).map_err(parse_error)
+    }
+
+    /// The url which [`nearest`](Self::nearest) sends the request to.
+    pub fn nearest_url(&self, nearest_request: &NearestRequest) -> String {
+        nearest_request
+            .to_url_parts(self.profile.url_form(), "v1", None)
+            .to_url(&self.endpoint)
+    }
+
+    /// See [`remote::OsrmEngine::nearest`](crate::remote::OsrmEngine::nearest).
+    pub async fn nearest(
+        &self,
+        nearest_request: &NearestRequest<'_>,
+    ) -> Result<NearestResponse, OsrmError> {
+        let response = self.get(self.nearest_url(nearest_request)).await?;
+        NearestResponse::from_json(&response).map_err(parse_error)
+    }
+
+    /// The url which [`r#match`](Self::match) sends the request to.
+    pub fn match_url(&self, match_request: &MatchRequest) -> String {
+        match_request
+            .to_url_parts(self.profile.url_form(), "v1", None)
+            .to_url(&self.endpoint)
+    }
+
+    /// See [`remote::OsrmEngine::r#match`](crate::remote::OsrmEngine::match).
+    pub async fn r#match(
+        &self,
+        match_request: &MatchRequest<'_>,
+    ) -> Result<MatchResponse, OsrmError> {
+        let response = self.get(self.match_url(match_request)).await?;
+        MatchResponse::from_json(&response).map_err(parse_error)
+    }
+}
+
+/// Report parse errors as from a remote backend, as the remote engine does.
+fn parse_error(error: OsrmError) -> OsrmError {
+    match error {
+        OsrmError::JsonParse(e) => OsrmError::Remote(RemoteOsrmError::JsonParse(e)),
+        error => error,
+    }
+}
@@ -15,9 +15,7 @@
 // Should not extern uint8_t enums as the C ABI is different
 enum RouteFlags : uint8_t
 {
-    ROUTE_ALTERNATIVES = 1 << 0,
     ROUTE_STEPS = 1 << 1,
-    ROUTE_ANNOTATIONS = 1 << 2,
     ROUTE_CONTINUE_STRAIGHT = 1 << 3,
     ROUTE_GENERATE_HINTS = 1 << 4,
     ROUTE_SKIP_WAYPOINTS = 1 << 5,
@@ -27,11 +25,18 @@ enum MatchFlags : uint8_t
 {
     MATCH_TIDY = 1 << 0,
     MATCH_STEPS = 1 << 1,
-    MATCH_ANNOTATIONS = 1 << 2,
     MATCH_GENERATE_HINTS = 1 << 3,
     MATCH_SKIP_WAYPOINTS = 1 << 4,
 };
 
+enum TripFlags : uint8_t
+{
+    TRIP_STEPS = 1 << 0,
+    TRIP_GENERATE_HINTS = 1 << 2,
+    TRIP_SKIP_WAYPOINTS = 1 << 3,
+    TRIP_ROUNDTRIP = 1 << 4,
+};
+
 struct ArrayString
 {
     size_t len;
@@ -287,28 +292,11 @@ extern "C"
         osrm::json::Object result;
         const auto status = osrm_ptr->Table(params, result);
 
+        // On error, the response holds both the code and the message
+        const int code = status == osrm::Status::Ok ? 0 : 1;
         std::ostringstream oss;
-        std::string result_str;
-        int code;
-
-        if (status == osrm::Status::Ok)
-        {
-            code = 0;
-            serialize_object(oss, result);
-            result_str = oss.str();
-        }
-        else
-        {
-            code = 1;
-            try
-            {
-                result_str = std::get<osrm::util::json::String>(result.values.at("message")).value;
-            }
-            catch (const std::exception &e)
-            {
-                result_str = "Unknown OSRM error";
-            }
-        }
+        serialize_object(oss, result);
+        const std::string result_str = oss.str();
 
         char *message = new char[result_str.length() + 1];
         strcpy(message, result_str.c_str());
@@ -322,6 +310,10 @@ extern "C"
                            enum GeometryType geometry_type,
                            enum OverviewZoom overview_zoom,
                            uint8_t flags,
+                           uint8_t annotations,
+                           uint8_t number_of_alternatives,
+                           const size_t *waypoints,
+                           size_t num_waypoints,
                            const osrm::engine::Bearing *bearings,
                            size_t num_bearings,
                            const double *radiuses,
@@ -353,13 +345,24 @@ extern "C"
 
         params.geometries = static_cast<osrm::engine::api::RouteParameters::GeometriesType>(geometry_type);
         params.overview = static_cast<osrm::engine::api::RouteParameters::OverviewType>(overview_zoom);
-        params.alternatives = (flags & ROUTE_ALTERNATIVES) != 0;
+        params.alternatives = number_of_alternatives > 0;
+        params.number_of_alternatives = number_of_alternatives;
         params.steps = (flags & ROUTE_STEPS) != 0;
-        params.annotations = (flags & ROUTE_ANNOTATIONS) != 0;
+        // A bitmask of osrm::engine::api::RouteParameters::AnnotationsType
+        params.annotations = annotations != 0;
+        params.annotations_type = static_cast<osrm::engine::api::RouteParameters::AnnotationsType>(annotations);
         params.continue_straight = (flags & ROUTE_CONTINUE_STRAIGHT) != 0;
         params.generate_hints = (flags & ROUTE_GENERATE_HINTS) != 0;
         params.skip_waypoints = (flags & ROUTE_SKIP_WAYPOINTS) != 0;
         params.snapping = static_cast<osrm::engine::api::BaseParameters::SnappingType>(snapping);
+        if (num_waypoints > 0)
+        {
+            params.waypoints.reserve(num_waypoints);
+            for (size_t i = 0; i < num_waypoints; i++)
+            {
+                params.waypoints.push_back(waypoints[i]);
+            }
+        }
         if (num_bearings > 0)
         {
             if (num_bearings != num_coordinates)
@@ -461,28 +464,11 @@ extern "C"
         osrm::json::Object result;
         const auto status = osrm_ptr->Route(params, result);
 
+        // On error, the response holds both the code and the message
+        const int code = status == osrm::Status::Ok ? 0 : 1;
         std::ostringstream oss;
-        std::string result_str;
-        int code;
-
-        if (status == osrm::Status::Ok)
-        {
-            code = 0;
-            serialize_object(oss, result);
-            result_str = oss.str();
-        }
-        else
-        {
-            code = 1;
-            try
-            {
-                result_str = std::get<osrm::util::json::String>(result.values.at("message")).value;
-            }
-            catch (const std::exception &e)
-            {
-                result_str = "Unknown OSRM error";
-            }
-        }
+        serialize_object(oss, result);
+        const std::string result_str = oss.str();
 
         char *message = new char[result_str.length() + 1];
         strcpy(message, result_str.c_str());
@@ -507,6 +493,7 @@ extern "C"
                            const size_t *waypoints,
                            size_t num_waypoints,
                            uint8_t flags,
+                           uint8_t annotations,
                            const osrm::engine::Bearing *bearings,
                            size_t num_bearings,
                            const double *radiuses,
@@ -541,7 +528,9 @@ extern "C"
         params.gaps = static_cast<osrm::engine::api::MatchParameters::GapsType>(gaps_type);
         params.tidy = (flags & MATCH_TIDY) != 0;
         params.steps = (flags & MATCH_STEPS) != 0;
-        params.annotations = (flags & MATCH_ANNOTATIONS) != 0;
+        // A bitmask of osrm::engine::api::RouteParameters::AnnotationsType
+        params.annotations = annotations != 0;
+        params.annotations_type = static_cast<osrm::engine::api::RouteParameters::AnnotationsType>(annotations);
         params.generate_hints = (flags & MATCH_GENERATE_HINTS) != 0;
         params.skip_waypoints = (flags & MATCH_SKIP_WAYPOINTS) != 0;
         params.snapping = static_cast<osrm::engine::api::BaseParameters::SnappingType>(snapping);
@@ -669,28 +658,11 @@ extern "C"
         osrm::json::Object result;
         const auto status = osrm_ptr->Match(params, result);
 
+        // On error, the response holds both the code and the message
+        const int code = status == osrm::Status::Ok ? 0 : 1;
         std::ostringstream oss;
-        std::string result_str;
-        int code;
-
-        if (status == osrm::Status::Ok)
-        {
-            code = 0;
-            serialize_object(oss, result);
-            result_str = oss.str();
-        }
-        else
-        {
-            code = 1;
-            try
-            {
-                result_str = std::get<osrm::util::json::String>(result.values.at("message")).value;
-            }
-            catch (const std::exception &e)
-            {
-                result_str = "Unknown OSRM error";
-            }
-        }
+        serialize_object(oss, result);
+        const std::string result_str = oss.str();
 
         char *message = new char[result_str.length() + 1];
         strcpy(message, result_str.c_str());
@@ -698,9 +670,38 @@ extern "C"
         return {code, message};
     }
 
+    enum class TripSourceType
+    {
+        TripSourceType_Any = 0,
+        TripSourceType_First = 1,
+    };
+
+    enum class TripDestinationType
+    {
+        TripDestinationType_Any = 0,
+        TripDestinationType_Last = 1,
+    };
+
     OSRM_Result osrm_trip(void *osrm_instance,
                           const double *coordinates,
-                          size_t num_coordinates)
+                          size_t num_coordinates,
+                          enum GeometryType geometry_type,
+                          enum OverviewZoom overview_zoom,
+                          enum TripSourceType source,
+                          enum TripDestinationType destination,
+                          uint8_t flags,
+                          uint8_t annotations,
+                          const osrm::engine::Bearing *bearings,
+                          size_t num_bearings,
+                          const double *radiuses,
+                          size_t num_radiuses,
+                          const ArrayString *hints,
+                          size_t num_hints,
+                          const osrm::engine::Approach *approaches,
+                          size_t num_approaches,
+                          const ArrayString *excludes,
+                          size_t num_excludes,
+                          enum Snapping snapping)
     {
 
         if (!osrm_instance)
@@ -720,31 +721,123 @@ extern "C"
                                           osrm::util::FloatLatitude{coordinates[i * 2 + 1]}});
         }
 
-        osrm::json::Object result;
-        const auto status = osrm_ptr->Trip(params, result);
-
-        std::ostringstream oss;
-        std::string result_str;
-        int code;
+        params.geometries = static_cast<osrm::engine::api::RouteParameters::GeometriesType>(geometry_type);
+        params.overview = static_cast<osrm::engine::api::RouteParameters::OverviewType>(overview_zoom);
+        params.source = static_cast<osrm::engine::api::TripParameters::SourceType>(source);
+        params.destination = static_cast<osrm::engine::api::TripParameters::DestinationType>(destination);
+        params.steps = (flags & TRIP_STEPS) != 0;
+        // A bitmask of osrm::engine::api::RouteParameters::AnnotationsType
+        params.annotations = annotations != 0;
+        params.annotations_type = static_cast<osrm::engine::api::RouteParameters::AnnotationsType>(annotations);
+        params.generate_hints = (flags & TRIP_GENERATE_HINTS) != 0;
+        params.skip_waypoints = (flags & TRIP_SKIP_WAYPOINTS) != 0;
+        params.roundtrip = (flags & TRIP_ROUNDTRIP) != 0;
+        params.snapping = static_cast<osrm::engine::api::BaseParameters::SnappingType>(snapping);
+        if (num_bearings > 0)
+        {
+            if (num_bearings != num_coordinates)
+            {
+                const char *err = "num_bearings must equal num_coordinates";
+                char *msg = new char[strlen(err) + 1];
+                strcpy(msg, err);
+                return {1, msg};
+            }
+            params.bearings.reserve(num_bearings);
+            for (size_t i = 0; i < num_bearings; i++)
+            {
+                params.bearings.push_back(bearings[i]);
+            }
+        }
 
-        if (status == osrm::Status::Ok)
+        if (num_radiuses > 0)
+        {
+            if (num_radiuses != num_coordinates)
+            {
+                const char *err = "num_radiuses must equal num_coordinates";
+                char *msg = new char[strlen(err) + 1];
+                strcpy(msg, err);
+                return {1, msg};
+            }
+            params.radiuses.reserve(num_radiuses);
+            for (size_t i = 0; i < num_radiuses; i++)
+            {
+                if (std::isinf(radiuses[i]))
+                {
+                    params.radiuses.push_back(std::nullopt);
+                }
+                else
+                {
+                    params.radiuses.push_back(radiuses[i]);
+                }
+            }
+        }
+        if (num_hints > 0)
         {
-            code = 0;
-            serialize_object(oss, result);
-            result_str = oss.str();
+            if (num_hints != num_coordinates)
+            {
+                const char *err = "num_hints must equal num_coordinates";
+                char *msg = new char[strlen(err) + 1];
+                strcpy(msg, err);
+                return {1, msg};
+            }
+            params.hints.reserve(num_hints);
+            for (size_t i = 0; i < num_hints; i++)
+            {
+                const ArrayString &h = hints[i];
+
+                if (h.pointer == nullptr || h.len == 0)
+                {
+                    params.hints.emplace_back();
+                    continue;
+                }
+
+                std::string encoded_hint(reinterpret_cast<const char *>(h.pointer), h.len);
+
+                osrm::engine::Hint hint;
+                hint.FromBase64(encoded_hint);
+                params.hints.push_back(std::move(hint));
+            }
         }
-        else
+        if (num_approaches > 0)
         {
-            code = 1;
-            try
+            if (num_approaches != num_coordinates)
             {
-                result_str = std::get<osrm::util::json::String>(result.values.at("message")).value;
+                const char *err = "num_approaches must equal num_coordinates";
+                char *msg = new char[strlen(err) + 1];
+                strcpy(msg, err);
+                return {1, msg};
             }
-            catch (const std::exception &e)
+            params.approaches.reserve(num_approaches);
+            for (size_t i = 0; i < num_approaches; i++)
             {
-                result_str = "Unknown OSRM error";
+                params.approaches.push_back(approaches[i]);
             }
         }
+        if (num_excludes > 0)
+        {
+            params.exclude.reserve(num_excludes);
+            for (size_t i = 0; i < num_excludes; i++)
+            {
+                const ArrayString &e = excludes[i];
+
+                if (e.pointer == nullptr || e.len == 0)
+                {
+                    continue;
+                }
+
+                std::string exclude_str(reinterpret_cast<const char *>(e.pointer), e.len);
+
+                params.exclude.push_back(std::move(exclude_str));
+            }
+        }
+        osrm::json::Object result;
+        const auto status = osrm_ptr->Trip(params, result);
+
+        // On error, the response holds both the code and the message
+        const int code = status == osrm::Status::Ok ? 0 : 1;
+        std::ostringstream oss;
+        serialize_object(oss, result);
+        const std::string result_str = oss.str();
 
         char *message = new char[result_str.length() + 1];
         strcpy(message, result_str.c_str());
@@ -816,28 +909,11 @@ extern "C"
         osrm::json::Object result;
         const auto status = osrm_ptr->Nearest(params, result);
 
+        // On error, the response holds both the code and the message
+        const int code = status == osrm::Status::Ok ? 0 : 1;
         std::ostringstream oss;
-        std::string result_str;
-        int code;
-
-        if (status == osrm::Status::Ok)
-        {
-            code = 0;
-            serialize_object(oss, result);
-            result_str = oss.str();
-        }
-        else
-        {
-            code = 1;
-            try
-            {
-                result_str = std::get<osrm::util::json::String>(result.values.at("message")).value;
-            }
-            catch (const std::exception &e)
-            {
-                result_str = "Unknown OSRM error";
-            }
-        }
+        serialize_object(oss, result);
+        const std::string result_str = oss.str();
 
         char *message = new char[result_str.length() + 1];
         strcpy(message, result_str.c_str());
@@ -0,0 +1,41 @@
+use std::cell::RefCell;
+use std::sync::Once;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+thread_local! {
+    static WARNINGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+}
+
+/// A logger which records warnings per thread, so that tests running in
+/// parallel only see the warnings they emitted.
+struct CaptureLogger;
+
+impl Log for CaptureLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            WARNINGS.with(|w| w.borrow_mut().push(record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: CaptureLogger = CaptureLogger;
+static INIT: Once = Once::new();
+
+/// Run `f`, returning its result and the warnings logged on this thread
+/// while it ran.
+pub fn capture_warnings<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+    INIT.call_once(|| {
+        log::set_logger(&LOGGER).expect("Logger already set");
+        log::set_max_level(LevelFilter::Warn);
+    });
+    WARNINGS.with(|w| w.borrow_mut().clear());
+    let result = f();
+    (result, WARNINGS.with(|w| w.take()))
+}
@@ -0,0 +1,39 @@
+//! Canned osrm-routed responses for tests which do not require a backend.
+
+/// A two point route response with waypoints named "Start Street" and
+/// "End Street".
+pub const ROUTE_RESPONSE: &str = r#"{
+    "code": "Ok",
+    "routes": [
+        {
+            "distance": 1200.5,
+            "duration": 180.2,
+            "weight": 180.2,
+            "weight_name": "routability",
+            "geometry": "_p~iF~ps|U_ulLnnqC",
+            "legs": [
+                {
+                    "distance": 1200.5,
+                    "duration": 180.2,
+                    "weight": 180.2,
+                    "summary": "Start Street, End Street",
+                    "steps": []
+                }
+            ]
+        }
+    ],
+    "waypoints": [
+        {
+            "hint": "start-hint",
+            "location": [-120.2, 38.5],
+            "name": "Start Street",
+            "distance": 1.5
+        },
+        {
+            "hint": "end-hint",
+            "location": [-120.95, 40.7],
+            "name": "End Street",
+            "distance": 2.5
+        }
+    ]
+}"#;
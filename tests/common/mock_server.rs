@@ -0,0 +1,167 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A canned HTTP response served by [`MockServer`].
+#[derive(Clone, Debug)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub delay: Option<Duration>,
+}
+
+impl MockResponse {
+    /// A response with the given status and a JSON body.
+    pub fn json(status: u16, body: &str) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: body.as_bytes().to_vec(),
+            delay: None,
+        }
+    }
+
+    /// A response with the given status and a binary body.
+    pub fn bytes(status: u16, body: &[u8]) -> Self {
+        Self {
+            status,
+            headers: vec![(
+                "Content-Type".to_string(),
+                "application/x-flatbuffers;schema=osrm.engine.api.fbresult".to_string(),
+            )],
+            body: body.to_vec(),
+            delay: None,
+        }
+    }
+
+    /// Add a header to the response.
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Wait before writing the response.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+/// A minimal HTTP server running on a background thread which serves
+/// canned responses in order, repeating the last response once the
+/// list is exhausted. The request target (path and query) of every
+/// request is recorded.
+pub struct MockServer {
+    address: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockServer {
+    pub fn start(responses: Vec<MockResponse>) -> Self {
+        assert!(!responses.is_empty(), "MockServer requires a response");
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind mock server");
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let recorded = requests.clone();
+        std::thread::spawn(move || {
+            for (i, stream) in listener.incoming().enumerate() {
+                let Ok(stream) = stream else {
+                    continue;
+                };
+                let response = responses[i.min(responses.len() - 1)].clone();
+                let recorded = recorded.clone();
+                std::thread::spawn(move || serve(stream, response, recorded));
+            }
+        });
+
+        Self { address, requests }
+    }
+
+    /// The base address of the server, eg. `http://127.0.0.1:12345`.
+    pub fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    /// The request targets received so far, in order of arrival.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+fn serve(stream: TcpStream, response: MockResponse, recorded: Arc<Mutex<Vec<String>>>) {
+    let mut reader = BufReader::new(stream);
+    let mut request_line = String::new();
+    if reader.read_line(&mut request_line).is_err() {
+        return;
+    }
+    // Drain the remaining request headers
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) if line == "\r\n" => break,
+            Ok(_) => {}
+        }
+    }
+    if let Some(target) = request_line.split_whitespace().nth(1) {
+        recorded.lock().unwrap().push(target.to_string());
+    }
+
+    if let Some(delay) = response.delay {
+        std::thread::sleep(delay);
+    }
+
+    let mut stream = reader.into_inner();
+    let mut head = format!("HTTP/1.1 {} Mock\r\n", response.status);
+    for (key, value) in &response.headers {
+        head.push_str(&format!("{key}: {value}\r\n"));
+    }
+    head.push_str(&format!(
+        "Content-Length: {}\r\nConnection: close\r\n\r\n",
+        response.body.len()
+    ));
+    let _ = stream.write_all(head.as_bytes());
+    let _ = stream.write_all(&response.body);
+    let _ = stream.flush();
+}
+
+/// A listener whose accept queue has been filled without ever accepting, so
+/// further connection attempts hang until they time out. Used to simulate an
+/// unreachable endpoint without relying on the network.
+///
+/// Only Linux reliably drops connection attempts to a full queue, other
+/// platforms may refuse them or keep accepting.
+#[cfg(target_os = "linux")]
+pub struct SaturatedListener {
+    address: String,
+    _listener: TcpListener,
+    _connections: Vec<TcpStream>,
+}
+
+#[cfg(target_os = "linux")]
+impl SaturatedListener {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to bind listener");
+        let local_addr = listener.local_addr().unwrap();
+        let mut connections = Vec::new();
+        // The accept queue is full once a connection attempt hangs. std listens
+        // with a backlog of 128, so stop well before running out of descriptors
+        while let Ok(stream) = TcpStream::connect_timeout(&local_addr, Duration::from_millis(200)) {
+            connections.push(stream);
+            assert!(connections.len() < 1_000, "Failed to saturate listener");
+        }
+        Self {
+            address: format!("http://{local_addr}"),
+            _listener: listener,
+            _connections: connections,
+        }
+    }
+
+    /// The base address of the listener, eg. `http://127.0.0.1:12345`.
+    pub fn address(&self) -> String {
+        self.address.clone()
+    }
+}
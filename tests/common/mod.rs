@@ -1,4 +1,13 @@
+#[cfg(feature = "log")]
+#[allow(dead_code)]
+pub mod capture_log;
+#[allow(dead_code)]
 pub mod dotenv;
+#[allow(dead_code)]
+pub mod fixtures;
+#[cfg(feature = "remote")]
+#[allow(dead_code)]
+pub mod mock_server;
 
 #[cfg(feature = "native")]
 #[allow(dead_code)]
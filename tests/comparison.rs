@@ -8,7 +8,7 @@ use osrm_interface::{
     r#match::MatchRequestBuilder,
     nearest::NearestRequestBuilder,
     osrm_response_types::Geometry,
-    request_types::{GeometryType, OverviewZoom},
+    request_types::{CarExclude, Exclude, GeometryType, OverviewZoom, Snapping},
     route::RouteRequestBuilder,
 };
 use rand::Rng;
@@ -119,6 +119,105 @@ fn test_compare_nearest() {
     )
 }
 
+#[test]
+fn test_compare_nearest_snapping() {
+    let remote_engine = init_remote_engine(".env");
+    let native_engine = init_native_engine(".env");
+    let num_points = 5;
+
+    let point = Point::new(48.040437, 10.316550).expect("Invalid point");
+    let mut closest = Vec::new();
+    for snapping in [None, Some(Snapping::Default), Some(Snapping::Any)] {
+        let mut builder = NearestRequestBuilder::new(&point, num_points);
+        if let Some(snapping) = snapping {
+            builder = builder.snapping(snapping);
+        }
+        let nearest_request = builder.build().expect("Failed to build nearest request");
+        let remote_response = remote_engine
+            .nearest(&nearest_request)
+            .expect("Failed to find nearest");
+        let native_response = native_engine
+            .nearest(&nearest_request)
+            .expect("Failed to find nearest");
+
+        assert_eq!(remote_response.code, "Ok", "Response code is not 'Ok'");
+        assert_eq!(
+            remote_response.code, native_response.code,
+            "Responses returned different codes for {snapping:?}"
+        );
+        assert_eq!(
+            remote_response.waypoints.len(),
+            native_response.waypoints.len(),
+            "Responses returned different number of waypoints for {snapping:?}"
+        );
+        assert!(
+            remote_response
+                .waypoints
+                .iter()
+                .zip(native_response.waypoints.iter())
+                .all(|(w_r, w_n)| {
+                    (w_r.location[0] - w_n.location[0]).abs() < 1e-6
+                        && (w_r.location[1] - w_n.location[1]).abs() < 1e-6
+                }),
+            "Responses have different snapped locations for {snapping:?}"
+        );
+        closest.push(
+            native_response
+                .waypoints
+                .iter()
+                .map(|w| w.distance)
+                .fold(f64::INFINITY, f64::min),
+        );
+    }
+
+    assert_eq!(
+        closest[0], closest[1],
+        "Unset snapping should behave as Snapping::Default"
+    );
+    assert!(
+        closest[2] <= closest[1],
+        "Snapping::Any should never return a further candidate than Snapping::Default"
+    );
+}
+
+#[test]
+fn test_compare_nearest_exclude_snapping() {
+    let remote_engine = init_remote_engine(".env");
+    let native_engine = init_native_engine(".env");
+    let num_points = 5;
+
+    let point = Point::new(48.040437, 10.316550).expect("Invalid point");
+    let excludes = [Exclude::Car(CarExclude::Motorway)];
+    let nearest_request = NearestRequestBuilder::new(&point, num_points)
+        .exclude(&excludes)
+        .snapping(Snapping::Any)
+        .build()
+        .expect("Failed to build nearest request");
+    let remote_response = remote_engine
+        .nearest(&nearest_request)
+        .expect("Failed to find nearest");
+    let native_response = native_engine
+        .nearest(&nearest_request)
+        .expect("Failed to find nearest");
+
+    assert_eq!(
+        remote_response.waypoints.len(),
+        native_response.waypoints.len(),
+        "Responses returned different number of waypoints"
+    );
+    assert!(
+        remote_response
+            .waypoints
+            .iter()
+            .zip(native_response.waypoints.iter())
+            .all(|(w_r, w_n)| {
+                (w_r.location[0] - w_n.location[0]).abs() < 1e-6
+                    && (w_r.location[1] - w_n.location[1]).abs() < 1e-6
+            }),
+        "Responses have different snapped locations with exclude and snapping set"
+    );
+}
+
 #[test]
 fn test_compare_match() {
     let remote_engine = init_remote_engine(".env");
@@ -0,0 +1,77 @@
+//! The surface of the crate available without an engine, eg. for a WASM
+//! frontend which builds requests or reads responses but never calls a
+//! backend.
+//!
+//! Only compiled when neither engine is enabled, so `cargo test` with the
+//! default features checks that the core does not depend on them.
+#![cfg(not(any(feature = "remote", feature = "native")))]
+
+use osrm_interface::{
+    BoundingBox, Point,
+    osrm_response_types::{Geometry, ResponseCode, Route},
+    polyline::decode_polyline,
+    request_types::{Bearing, GeometryType},
+    route::{RouteRequestBuilder, RouteResponse},
+    table::TableRequestBuilder,
+};
+
+#[test]
+fn test_core_types() {
+    let points = [
+        Point::new(38.5, -120.2).expect("Invalid point"),
+        Point::new(40.7, -120.95).expect("Invalid point"),
+    ];
+    let bounding_box = BoundingBox::from_points(points).expect("Missing bounding box");
+    assert!(points.iter().all(|p| bounding_box.contains(p)));
+
+    let bearing = Bearing::new(90, 45).expect("Invalid bearing");
+    assert_eq!(bearing.url_form(), "90,45");
+}
+
+#[test]
+fn test_core_geometry() {
+    let decoded = decode_polyline("_p~iF~ps|U_ulLnnqC", 5).expect("Failed to decode");
+    assert_eq!(decoded.len(), 2);
+    assert!((decoded[1].latitude() - 40.7).abs() < 1e-9);
+
+    let geometry = Geometry::Polyline("_p~iF~ps|U_ulLnnqC".to_string());
+    let points = geometry
+        .decode(GeometryType::Polyline)
+        .expect("Failed to decode geometry");
+    assert_eq!(points, decoded);
+}
+
+#[test]
+fn test_core_requests() {
+    let points = [
+        Point::new(38.5, -120.2).expect("Invalid point"),
+        Point::new(40.7, -120.95).expect("Invalid point"),
+    ];
+    let bearings = [Some(Bearing::new(0, 90).expect("Invalid bearing")), None];
+    let route_request = RouteRequestBuilder::new(&points)
+        .bearings(&bearings)
+        .build()
+        .expect("Failed to build route request");
+    let url = route_request
+        .to_url_parts("car", "v1", None)
+        .to_url("https://router.example.com");
+    assert!(url.starts_with(
+        "https://router.example.com/route/v1/car/-120.200000,38.500000;-120.950000,40.700000"
+    ));
+    assert!(url.contains("bearings=0,90;"));
+
+    TableRequestBuilder::new(&points, &points)
+        .build()
+        .expect("Failed to build table request");
+}
+
+#[test]
+fn test_core_responses() {
+    let response = RouteResponse {
+        code: "Ok".to_string(),
+        routes: vec![Route::default()],
+        waypoints: None,
+    };
+    assert_eq!(response.code_enum(), ResponseCode::Ok);
+    assert!(response.summary().is_some());
+}
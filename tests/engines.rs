@@ -0,0 +1,168 @@
+//! The same generic checks run against every engine through [`OsrmService`],
+//! so that code swapping between the engines behaves identically.
+
+#[cfg(any(feature = "native", feature = "remote"))]
+mod common;
+
+use osrm_interface::{
+    Point,
+    r#match::{MatchGapsBehaviour, MatchRequestBuilder},
+    nearest::NearestRequestBuilder,
+    route::RouteRequestBuilder,
+    services::OsrmService,
+    table::{TableAnnotation, TableRequestBuilder},
+    trip::TripRequestBuilder,
+};
+
+fn points() -> [Point; 3] {
+    [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+        Point::new(48.942296, 10.510960).expect("Invalid point"),
+    ]
+}
+
+fn check_engine<E: OsrmService>(engine: &E) {
+    let points = points();
+
+    let table_request = TableRequestBuilder::new(&points, &points[..2])
+        .annotations(TableAnnotation::All)
+        .build()
+        .expect("Failed to build table request");
+    let response = engine.table(&table_request).expect("Failed to get table");
+    let durations = response.durations.expect("Missing durations");
+    assert_eq!(durations.len(), 3, "One row per source");
+    assert!(
+        durations.iter().all(|row| row.len() == 2),
+        "One column per destination"
+    );
+    assert!(response.distances.is_some());
+
+    let route_request = RouteRequestBuilder::new(&points)
+        .build()
+        .expect("Failed to build route request");
+    let response = engine.route(&route_request).expect("Failed to route");
+    assert_eq!(response.code, "Ok");
+    assert_eq!(response.routes.len(), 1, "Alternatives were not requested");
+    assert_eq!(
+        response.routes[0].legs.len(),
+        2,
+        "One leg between each point"
+    );
+    assert_eq!(response.waypoints.map(|w| w.len()), Some(3));
+
+    let trip_request = TripRequestBuilder::new(&points)
+        .roundtrip(true)
+        .build()
+        .expect("Failed to build trip request");
+    let response = engine.trip(&trip_request).expect("Failed to navigate trip");
+    assert_eq!(response.trips.len(), 1);
+    assert_eq!(
+        response.trips[0].legs.len(),
+        3,
+        "Roundtrip returns to the start"
+    );
+    let waypoints = response.waypoints.expect("Missing waypoints");
+    let mut order: Vec<usize> = waypoints.iter().map(|w| w.waypoint_index).collect();
+    order.sort_unstable();
+    assert_eq!(order, [0, 1, 2], "Waypoint indices should be a permutation");
+    assert!(waypoints.iter().all(|w| w.trips_index == 0));
+
+    let response = engine
+        .simple_route(points[0], points[1])
+        .expect("Failed to route");
+    assert!(response.distance > 0.0 && response.duration > 0.0);
+
+    let nearest_request = NearestRequestBuilder::new(&points[0], 2)
+        .build()
+        .expect("Failed to build nearest request");
+    let response = engine
+        .nearest(&nearest_request)
+        .expect("Failed to find nearest");
+    assert_eq!(response.waypoints.len(), 2);
+    assert!(
+        response
+            .waypoints
+            .windows(2)
+            .all(|w| w[0].distance <= w[1].distance),
+        "Nearest waypoints should be sorted by distance"
+    );
+
+    let match_request = MatchRequestBuilder::new(&points)
+        .gaps(MatchGapsBehaviour::Ignore)
+        .build()
+        .expect("Failed to build match request");
+    let response = engine.r#match(&match_request).expect("Failed to match");
+    assert_eq!(response.tracepoints.len(), 3, "One tracepoint per point");
+    assert!(response.validate_indices());
+}
+
+/// Share the engine between threads, checking every thread sees the same
+/// responses as a single thread.
+fn check_engine_threaded<E: OsrmService + Sync>(engine: &E) {
+    let points = points();
+    let table_request = TableRequestBuilder::new(&points, &points)
+        .build()
+        .expect("Failed to build table request");
+    let expected = engine
+        .table(&table_request)
+        .expect("Failed to get table")
+        .durations;
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let table_request = &table_request;
+                scope.spawn(move || {
+                    let mut durations = Vec::new();
+                    for _ in 0..8 {
+                        let response = engine.table(table_request).expect("Failed to get table");
+                        durations.push(response.durations);
+                        engine
+                            .simple_route(points[i % 3], points[(i + 1) % 3])
+                            .expect("Failed to route");
+                    }
+                    durations
+                })
+            })
+            .collect();
+        for handle in handles {
+            let durations = handle.join().expect("Thread panicked");
+            assert!(durations.iter().all(|d| *d == expected));
+        }
+    });
+}
+
+#[test]
+fn test_mock_engine_generic() {
+    check_engine(&osrm_interface::mock::OsrmEngine::new());
+}
+
+#[test]
+fn test_mock_engine_threaded() {
+    check_engine_threaded(&osrm_interface::mock::OsrmEngine::new());
+}
+
+#[cfg(feature = "remote")]
+#[test]
+fn test_remote_engine_generic() {
+    check_engine(&common::init_remote_engine(".env"));
+}
+
+#[cfg(feature = "remote")]
+#[test]
+fn test_remote_engine_threaded() {
+    check_engine_threaded(&common::init_remote_engine(".env"));
+}
+
+#[cfg(feature = "native")]
+#[test]
+fn test_native_engine_generic() {
+    check_engine(&common::init_native_engine(".env"));
+}
+
+#[cfg(feature = "native")]
+#[test]
+fn test_native_engine_threaded() {
+    check_engine_threaded(&common::init_native_engine(".env"));
+}
@@ -0,0 +1,867 @@
+use osrm_interface::{
+    Point,
+    r#match::{MatchGapsBehaviour, MatchRequestBuilder},
+    mock::{DEFAULT_ASSUMED_SPEED_MPS, OsrmEngine},
+    nearest::NearestRequestBuilder,
+    osrm_response_types::{Geometry, ResponseCode},
+    request_types::{Annotations, GeometryType, OverviewZoom},
+    route::RouteRequestBuilder,
+    services::OsrmService,
+    table::{TableAnnotation, TableRequestBuilder},
+    trip::TripRequestBuilder,
+};
+
+fn points() -> [Point; 3] {
+    [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+        Point::new(48.942296, 10.510960).expect("Invalid point"),
+    ]
+}
+
+#[test]
+fn test_mock_route_geometry_type() {
+    let engine = OsrmEngine::new();
+    let points = points();
+
+    let route_request = RouteRequestBuilder::new(&points)
+        .geometry(GeometryType::GeoJSON)
+        .build()
+        .expect("Failed to build route request");
+    let response = engine
+        .route(&route_request)
+        .expect("Failed to route request");
+    assert!(
+        response
+            .routes
+            .iter()
+            .all(|r| matches!(r.geometry, Some(Geometry::GeoJson(_)))),
+        "Geometry should be GeoJson"
+    );
+
+    let route_request = RouteRequestBuilder::new(&points)
+        .geometry(GeometryType::Polyline6)
+        .build()
+        .expect("Failed to build route request");
+    let response = engine
+        .route(&route_request)
+        .expect("Failed to route request");
+    assert!(
+        response
+            .routes
+            .iter()
+            .all(|r| matches!(r.geometry, Some(Geometry::Polyline(_)))),
+        "Geometry should be Polyline"
+    );
+}
+
+#[test]
+fn test_mock_route_overview_false() {
+    let engine = OsrmEngine::new();
+    let points = points();
+
+    let route_request = RouteRequestBuilder::new(&points)
+        .overview(OverviewZoom::False)
+        .build()
+        .expect("Failed to build route request");
+    let response = engine
+        .route(&route_request)
+        .expect("Failed to route request");
+    assert!(
+        response.routes.iter().all(|r| r.geometry.is_none()),
+        "Geometry should be None for OverviewZoom::False"
+    );
+}
+
+#[test]
+fn test_mock_route_steps_and_annotations() {
+    let engine = OsrmEngine::new();
+    let points = points();
+
+    let route_request = RouteRequestBuilder::new(&points)
+        .build()
+        .expect("Failed to build route request");
+    let response = engine
+        .route(&route_request)
+        .expect("Failed to route request");
+    let legs = response.routes.iter().flat_map(|r| r.legs.iter());
+    assert!(
+        legs.clone().all(|l| l.annotation.is_none()),
+        "Annotations should be None by default"
+    );
+    assert!(
+        legs.clone().all(|l| l.steps.is_empty()),
+        "Steps should be empty by default"
+    );
+
+    let route_request = RouteRequestBuilder::new(&points)
+        .steps(true)
+        .annotations(true)
+        .geometry(GeometryType::GeoJSON)
+        .build()
+        .expect("Failed to build route request");
+    let response = engine
+        .route(&route_request)
+        .expect("Failed to route request");
+    let mut legs = response.routes.iter().flat_map(|r| r.legs.iter());
+    assert!(
+        legs.clone().all(|l| l.annotation.is_some()),
+        "Annotations should be Some when requested"
+    );
+    assert!(
+        legs.all(|l| !l.steps.is_empty()
+            && l.steps
+                .iter()
+                .all(|s| matches!(s.geometry, Geometry::GeoJson(_)))),
+        "Steps should be present and share the requested geometry type"
+    );
+}
+
+#[test]
+fn test_mock_annotation_granularity() {
+    let engine = OsrmEngine::new();
+    let points = points();
+    let speed_only = Annotations {
+        speed: true,
+        ..Annotations::NONE
+    };
+
+    let route_request = RouteRequestBuilder::new(&points)
+        .annotations(speed_only)
+        .build()
+        .expect("Failed to build route request");
+    let trip_request = TripRequestBuilder::new(&points)
+        .annotations(speed_only)
+        .build()
+        .expect("Failed to build trip request");
+    let match_request = MatchRequestBuilder::new(&points)
+        .annotations(speed_only)
+        .gaps(MatchGapsBehaviour::Ignore)
+        .build()
+        .expect("Failed to build match request");
+    let legs: Vec<_> = [
+        engine
+            .route(&route_request)
+            .expect("Failed to route")
+            .routes,
+        engine.trip(trip_request).expect("Failed to trip").trips,
+    ]
+    .into_iter()
+    .flatten()
+    .flat_map(|route| route.legs)
+    .chain(
+        engine
+            .r#match(&match_request)
+            .expect("Failed to match")
+            .matchings
+            .into_iter()
+            .flat_map(|matching| matching.legs),
+    )
+    .collect();
+    assert!(!legs.is_empty());
+    for leg in legs {
+        let annotation = leg.annotation.expect("Missing annotation");
+        assert!(!annotation.speed.is_empty(), "Speed should be populated");
+        assert!(
+            annotation.duration.is_empty()
+                && annotation.distance.is_empty()
+                && annotation.weight.is_empty()
+                && annotation.nodes.is_empty()
+                && annotation.datasources.is_empty(),
+            "Only speed should be populated, got {annotation:?}"
+        );
+    }
+
+    let route_request = RouteRequestBuilder::new(&points)
+        .annotations(true)
+        .build()
+        .expect("Failed to build route request");
+    let response = engine.route(&route_request).expect("Failed to route");
+    let annotation = response.routes[0].legs[0]
+        .annotation
+        .as_ref()
+        .expect("Missing annotation");
+    assert!(!annotation.duration.is_empty() && !annotation.speed.is_empty());
+}
+
+#[test]
+fn test_mock_skip_waypoints() {
+    let engine = OsrmEngine::new();
+    let points = points();
+
+    let route_request = RouteRequestBuilder::new(&points)
+        .skip_waypoints(true)
+        .build()
+        .expect("Failed to build route request");
+    let response = engine
+        .route(&route_request)
+        .expect("Failed to route request");
+    assert!(
+        response.waypoints.is_none(),
+        "Waypoints were returned despite skip_waypoints=true"
+    );
+
+    let trip_request = TripRequestBuilder::new(&points)
+        .skip_waypoints(true)
+        .build()
+        .expect("Failed to build trip request");
+    let response = engine.trip(trip_request).expect("Failed to navigate trip");
+    assert!(
+        response.waypoints.is_none(),
+        "Waypoints were returned despite skip_waypoints=true"
+    );
+}
+
+#[test]
+fn test_mock_match_options() {
+    let engine = OsrmEngine::new();
+    let points = points();
+
+    let match_request = MatchRequestBuilder::new(&points)
+        .gaps(MatchGapsBehaviour::Ignore)
+        .overview(OverviewZoom::Full)
+        .geometry(GeometryType::Polyline)
+        .annotations(true)
+        .build()
+        .expect("Failed to build match request");
+    let response = engine
+        .r#match(&match_request)
+        .expect("Failed to match request");
+    assert!(
+        response
+            .matchings
+            .iter()
+            .all(|m| matches!(m.geometry, Some(Geometry::Polyline(_)))),
+        "Geometry should be Polyline"
+    );
+    assert!(
+        response
+            .matchings
+            .iter()
+            .flat_map(|m| m.legs.iter())
+            .all(|l| l.annotation.is_some()),
+        "Annotations should be Some when requested"
+    );
+}
+
+#[test]
+fn test_mock_assumed_speed() {
+    let engine = OsrmEngine::new();
+    let [from, to, _] = points();
+
+    let response = engine
+        .simple_route(from, to)
+        .expect("Failed to route request");
+    assert!(
+        response.distance > 0.0,
+        "Distance should be the crow-fly distance"
+    );
+    assert!(
+        (response.duration - response.distance / DEFAULT_ASSUMED_SPEED_MPS).abs() < 1e-9,
+        "Duration should be derived from the assumed speed"
+    );
+
+    assert!(
+        response.start_name.is_some(),
+        "Start name was not populated"
+    );
+    assert!(response.end_name.is_some(), "End name was not populated");
+
+    let engine = OsrmEngine::new().assumed_speed_mps(DEFAULT_ASSUMED_SPEED_MPS / 2.0);
+    let slower = engine
+        .simple_route(from, to)
+        .expect("Failed to route request");
+    assert_eq!(slower.distance, response.distance);
+    assert!(
+        (slower.duration - 2.0 * response.duration).abs() < 1e-9,
+        "Halving the assumed speed should double the duration"
+    );
+}
+
+#[test]
+fn test_mock_detour_factor() {
+    let engine = OsrmEngine::new();
+    let [from, to, _] = points();
+
+    let factor = engine
+        .detour_factor(from, to)
+        .expect("Failed to compute detour factor");
+    assert!(
+        factor >= 1.0,
+        "Detour factor should be at least 1, got {factor}"
+    );
+
+    let factor = engine
+        .detour_factor(from, from)
+        .expect("Failed to compute detour factor");
+    assert_eq!(factor, 1.0, "Coincident points should have a factor of 1");
+}
+
+#[test]
+fn test_mock_nearest_ambiguity_check() {
+    let [point, _, _] = points();
+    let nearest_request = NearestRequestBuilder::new(&point, 1)
+        .with_ambiguity_check()
+        .build()
+        .expect("Failed to build nearest request");
+
+    // Candidates at 0m and 10m
+    let response = OsrmEngine::new()
+        .nearest(&nearest_request)
+        .expect("Failed to find nearest");
+    assert_eq!(
+        response.waypoints.len(),
+        2,
+        "Ambiguity check should request at least 2 candidates"
+    );
+    assert!(
+        !response.is_ambiguous(),
+        "A candidate on the road should not be ambiguous"
+    );
+
+    // Candidates at 100m and 110m, within the ratio of each other
+    let response = OsrmEngine::new()
+        .snap_distance_m(100.0)
+        .nearest(&nearest_request)
+        .expect("Failed to find nearest");
+    assert!(
+        response.is_ambiguous(),
+        "Similarly distant candidates should be ambiguous"
+    );
+}
+
+#[test]
+fn test_mock_check_connectivity() {
+    let engine = OsrmEngine::new();
+    let points = points();
+
+    assert!(
+        engine
+            .check_connectivity(&points)
+            .expect("Failed to check connectivity"),
+        "Mock points should be connected by default"
+    );
+
+    let engine = OsrmEngine::new().unroutable_points(vec![points[2]]);
+    assert!(
+        !engine
+            .check_connectivity(&points)
+            .expect("Failed to check connectivity"),
+        "An unroutable point should disconnect the points"
+    );
+    assert!(
+        engine
+            .check_connectivity(&points[..2])
+            .expect("Failed to check connectivity"),
+        "Remaining points should still be connected"
+    );
+}
+
+#[test]
+fn test_mock_unroutable_errors() {
+    let [a, b, c] = points();
+    let engine = OsrmEngine::new().unroutable_points(vec![c]);
+    let (connected, through, all) = ([a, b], [a, c, b], [a, b, c]);
+
+    let route_request = RouteRequestBuilder::new(&connected)
+        .build()
+        .expect("Failed to build route request");
+    engine
+        .route(&route_request)
+        .expect("Routable points should route");
+    let route_request = RouteRequestBuilder::new(&through)
+        .build()
+        .expect("Failed to build route request");
+    let error = engine
+        .route(&route_request)
+        .expect_err("Route through an unroutable point should fail");
+    assert_eq!(error.response_code(), Some(ResponseCode::NoRoute));
+    let error = engine
+        .simple_route(c, a)
+        .expect_err("Route from an unroutable point should fail");
+    assert_eq!(error.response_code(), Some(ResponseCode::NoRoute));
+
+    let trip_request = TripRequestBuilder::new(&all)
+        .build()
+        .expect("Failed to build trip request");
+    let error = engine
+        .trip(trip_request)
+        .expect_err("Trip with an unroutable point should fail");
+    assert_eq!(error.response_code(), Some(ResponseCode::NoTrips));
+}
+
+#[test]
+fn test_mock_table_fill_missing() {
+    let engine = OsrmEngine::new();
+    let points = points();
+    let table_request = TableRequestBuilder::new(&points, &points)
+        .build()
+        .expect("Failed to build table request");
+    let full = engine
+        .table(table_request)
+        .expect("Failed to table")
+        .durations
+        .expect("Missing durations");
+    assert_eq!(engine.call_count(), 1);
+
+    let filled = engine
+        .table_fill_missing(&points, &points, &full)
+        .expect("Failed to fill table");
+    assert_eq!(filled, full);
+    assert_eq!(engine.call_count(), 1, "A complete table needs no call");
+
+    // Known cells are kept even where they differ from the engine, and the
+    // short first row and missing last row are filled
+    let known = vec![vec![Some(1.0), None], vec![Some(2.0), Some(3.0), Some(4.0)]];
+    let filled = engine
+        .table_fill_missing(&points, &points, &known)
+        .expect("Failed to fill table");
+    assert_eq!(engine.call_count(), 2, "Missing cells should cost one call");
+    assert_eq!(filled[0], [Some(1.0), full[0][1], full[0][2]]);
+    assert_eq!(filled[1], [Some(2.0), Some(3.0), Some(4.0)]);
+    assert_eq!(filled[2], full[2]);
+}
+
+#[test]
+fn test_mock_trip_improvement() {
+    let engine = OsrmEngine::new();
+    let points = points();
+
+    let improvement = engine
+        .trip_improvement(&points)
+        .expect("Failed to compute trip improvement");
+    assert!(improvement.input_order_duration > 0.0);
+    assert!(improvement.optimised_duration > 0.0);
+    assert!(
+        (improvement.improvement_percent
+            - 100.0 * (improvement.input_order_duration - improvement.optimised_duration)
+                / improvement.input_order_duration)
+            .abs()
+            < 1e-9,
+        "Improvement should be relative to the input order duration"
+    );
+
+    let improvement = engine
+        .trip_improvement(&points[..1])
+        .expect("Failed to compute trip improvement");
+    assert_eq!(improvement.improvement_percent, 0.0);
+}
+
+#[test]
+fn test_mock_snap_cache() {
+    let engine = OsrmEngine::new().snap_cache(2);
+    let [a, b, c] = points();
+    let nearest = |point: &Point| {
+        let request = NearestRequestBuilder::new(point, 1)
+            .build()
+            .expect("Failed to build nearest request");
+        engine.nearest(&request).expect("Failed to find nearest")
+    };
+
+    let first = nearest(&a);
+    let repeat = nearest(&a);
+    assert_eq!(engine.call_count(), 1, "Repeated snap should hit the cache");
+    assert_eq!(first.waypoints[0].hint, repeat.waypoints[0].hint);
+
+    // Evicts `a` as the least recently used entry
+    nearest(&b);
+    nearest(&c);
+    assert_eq!(engine.call_count(), 3);
+    nearest(&a);
+    assert_eq!(engine.call_count(), 4, "Evicted snap should miss the cache");
+
+    let request = NearestRequestBuilder::new(&c, 3)
+        .build()
+        .expect("Failed to build nearest request");
+    engine.nearest(&request).expect("Failed to find nearest");
+    assert_eq!(
+        engine.call_count(),
+        5,
+        "Requests for several waypoints should not be cached"
+    );
+}
+
+#[test]
+fn test_mock_route_pairs_iter() {
+    let engine = OsrmEngine::new();
+    let [a, b, c] = points();
+    let pairs = [(a, b), (b, c), (c, a)];
+
+    let mut routes = engine.route_pairs_iter(pairs.into_iter());
+    assert_eq!(engine.call_count(), 0, "No routes should fire until pulled");
+
+    let first = routes
+        .next()
+        .expect("Missing route")
+        .expect("Failed to route pair");
+    assert!(first.distance > 0.0);
+    assert_eq!(engine.call_count(), 1, "Only the pulled route should fire");
+
+    assert_eq!(routes.count(), 2);
+    assert_eq!(engine.call_count(), 3);
+}
+
+#[test]
+fn test_mock_dyn_osrm_service() {
+    let engine = OsrmEngine::new();
+    let [a, b, _] = points();
+
+    let service: &dyn OsrmService = &engine;
+    let route = service.simple_route(a, b).expect("Failed to route");
+    assert!(route.distance > 0.0);
+    assert!(
+        service
+            .check_connectivity(&[a, b])
+            .expect("Failed to check")
+    );
+}
+
+#[test]
+fn test_mock_table_weights() {
+    let engine = OsrmEngine::new();
+    let points = points();
+
+    let table_request = TableRequestBuilder::new(&points, &points)
+        .annotations(TableAnnotation::All)
+        .build()
+        .expect("Failed to build table request");
+    let response = engine.table(table_request).expect("Failed to get table");
+    assert!(
+        response.weights.is_none(),
+        "The mock should not return weights, as stock backends do not"
+    );
+    assert_eq!(response.weight(0, 1), None);
+}
+
+#[test]
+fn test_mock_location_order() {
+    let engine = OsrmEngine::new();
+    let points = points();
+    // Locations are [longitude, latitude], as returned by OSRM
+    let expected: Vec<[f64; 2]> = points
+        .iter()
+        .map(|p| [p.longitude(), p.latitude()])
+        .collect();
+
+    let route_request = RouteRequestBuilder::new(&points)
+        .build()
+        .expect("Failed to build route request");
+    let response = engine
+        .route(&route_request)
+        .expect("Failed to route request");
+    let locations: Vec<[f64; 2]> = response
+        .waypoints
+        .expect("Missing waypoints")
+        .iter()
+        .map(|w| w.location)
+        .collect();
+    assert_eq!(locations, expected, "Route waypoints should be [lon, lat]");
+
+    let table_request = TableRequestBuilder::new(&points, &points)
+        .build()
+        .expect("Failed to build table request");
+    let response = engine.table(table_request).expect("Failed to get table");
+    for waypoints in [response.sources, response.destinations] {
+        let locations: Vec<[f64; 2]> = waypoints
+            .expect("Missing waypoints")
+            .iter()
+            .map(|w| w.location)
+            .collect();
+        assert_eq!(locations, expected, "Table waypoints should be [lon, lat]");
+    }
+
+    let trip_request = TripRequestBuilder::new(&points)
+        .build()
+        .expect("Failed to build trip request");
+    let response = engine.trip(trip_request).expect("Failed to navigate trip");
+    let locations: Vec<[f64; 2]> = response
+        .waypoints
+        .expect("Missing waypoints")
+        .iter()
+        .map(|w| w.location)
+        .collect();
+    assert_eq!(locations, expected, "Trip waypoints should be [lon, lat]");
+
+    let match_request = MatchRequestBuilder::new(&points)
+        .gaps(MatchGapsBehaviour::Ignore)
+        .build()
+        .expect("Failed to build match request");
+    let response = engine
+        .r#match(&match_request)
+        .expect("Failed to match request");
+    let locations: Vec<[f64; 2]> = response
+        .tracepoints
+        .iter()
+        .map(|t| t.as_ref().expect("Missing tracepoint").location)
+        .collect();
+    assert_eq!(locations, expected, "Tracepoints should be [lon, lat]");
+
+    let nearest_request = NearestRequestBuilder::new(&points[0], 1)
+        .build()
+        .expect("Failed to build nearest request");
+    let response = engine
+        .nearest(&nearest_request)
+        .expect("Failed to find nearest");
+    assert_eq!(
+        response.waypoints[0].location, expected[0],
+        "Nearest waypoints should be [lon, lat]"
+    );
+}
+
+#[test]
+fn test_mock_route_summary() {
+    let engine = OsrmEngine::new();
+    let points = points();
+
+    let route_request = RouteRequestBuilder::new(&points)
+        .build()
+        .expect("Failed to build route request");
+    let response = engine
+        .route(&route_request)
+        .expect("Failed to route request");
+    let summary = response.summary().expect("Missing summary");
+    let legs = &response.routes[0].legs;
+    assert_eq!(
+        summary.total_distance,
+        legs.iter().map(|l| l.distance).sum::<f64>()
+    );
+    assert_eq!(
+        summary.total_duration,
+        legs.iter().map(|l| l.duration).sum::<f64>()
+    );
+    assert!(matches!(summary.geometry, Some(Geometry::Polyline(_))));
+    assert_eq!(summary.waypoint_count, points.len());
+
+    let route_request = RouteRequestBuilder::new(&points)
+        .skip_waypoints(true)
+        .overview(OverviewZoom::False)
+        .build()
+        .expect("Failed to build route request");
+    let summary = engine
+        .route(&route_request)
+        .expect("Failed to route request")
+        .summary()
+        .expect("Missing summary");
+    assert!(summary.geometry.is_none());
+    assert_eq!(
+        summary.waypoint_count,
+        points.len(),
+        "Waypoint count should fall back to the legs"
+    );
+}
+
+#[test]
+fn test_mock_decode_step_geometries() {
+    let engine = OsrmEngine::new();
+    let points = points();
+    let decoded_steps = |geometry_type: GeometryType| {
+        let route_request = RouteRequestBuilder::new(&points)
+            .steps(true)
+            .geometry(geometry_type)
+            .build()
+            .expect("Failed to build route request");
+        let response = engine
+            .route(&route_request)
+            .expect("Failed to route request");
+        let route = &response.routes[0];
+        let overview = route
+            .geometry
+            .as_ref()
+            .expect("Missing geometry")
+            .decode(geometry_type)
+            .expect("Failed to decode overview");
+        route
+            .legs
+            .iter()
+            .flat_map(|l| l.steps.iter())
+            .map(|step| {
+                // Steps share the precision of the overview
+                let vertices = step
+                    .geometry
+                    .decode_with(geometry_type.polyline_precision().unwrap())
+                    .expect("Failed to decode step");
+                assert_eq!(vertices, overview);
+                vertices
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let polyline6 = decoded_steps(GeometryType::Polyline6);
+    let polyline = decoded_steps(GeometryType::Polyline);
+    assert!(!polyline6.is_empty());
+    for (a, b) in polyline6.iter().flatten().zip(polyline.iter().flatten()) {
+        assert!(
+            a.haversine_distance(b) < 1.0,
+            "Polyline6 steps decoded at precision 6 should match the polyline steps"
+        );
+    }
+}
+
+#[test]
+fn test_mock_match_from_trace() {
+    use osrm_interface::r#match::{MatchRequestError, MatchTrace, TraceOptions};
+
+    // A receiver heading north at ~5m/s with a few metres of jitter, stopped
+    // for the first few fixes and reporting an unknown heading once
+    let mut points = vec![Point::new(48.0, 10.0).expect("Invalid point"); 4];
+    let mut headings = vec![f64::NAN; 4];
+    for i in 1..=60 {
+        let jitter = (i as f64 * 1.7).sin() * 0.00002;
+        points.push(Point::new(48.0 + i as f64 * 0.000045, 10.0 + jitter).expect("Invalid point"));
+        headings.push(if i == 30 {
+            f64::NAN
+        } else {
+            358.0 + jitter * 1e5
+        });
+    }
+    let timestamps: Vec<u64> = (0..points.len() as u64).collect();
+
+    let trace = MatchTrace::new(
+        &points,
+        Some(&timestamps),
+        Some(&headings),
+        TraceOptions::default().min_spacing(15.0).bearing_range(30),
+    )
+    .expect("Failed to prepare trace");
+    let kept = trace.points();
+    assert!(
+        kept.len() < 30,
+        "Trace should be thinned, kept {}",
+        kept.len()
+    );
+    assert_eq!(kept[0], points[0]);
+    assert_eq!(kept.last(), points.last(), "The end of the trace is kept");
+    assert!(
+        kept.windows(2).all(|w| w[0] != w[1]),
+        "Duplicate points should be dropped"
+    );
+    assert_eq!(trace.indices()[0], 0);
+    let timestamps = trace.timestamps().expect("Missing timestamps");
+    assert!(timestamps.is_sorted());
+    assert!(
+        trace
+            .indices()
+            .iter()
+            .zip(timestamps)
+            .all(|(&i, &t)| t == i as u64)
+    );
+    let bearings = trace.bearings().expect("Missing bearings");
+    assert_eq!(bearings.len(), kept.len());
+    assert_eq!(bearings[0], None, "Unknown headings are unconstrained");
+    assert!(bearings[1..].iter().flatten().all(|b| {
+        let url = b.url_form();
+        let (degrees, range) = url.split_once(',').expect("Invalid bearing");
+        let degrees: i16 = degrees.parse().expect("Invalid bearing");
+        range == "30" && ((356..=360).contains(&degrees) || degrees == 0)
+    }));
+
+    let match_request = MatchRequestBuilder::from_trace(&trace)
+        .build()
+        .expect("Failed to build match request");
+    let response = OsrmEngine::new()
+        .r#match(&match_request)
+        .expect("Failed to match");
+    assert_eq!(response.tracepoints.len(), kept.len());
+
+    let result = MatchTrace::new(
+        &points,
+        Some(&timestamps[1..]),
+        None,
+        TraceOptions::default(),
+    );
+    assert!(matches!(
+        result,
+        Err(MatchRequestError::DimensionMismatch(_))
+    ));
+    let mut shuffled: Vec<u64> = (0..points.len() as u64).collect();
+    shuffled.swap(10, 11);
+    let result = MatchTrace::new(&points, Some(&shuffled), None, TraceOptions::default());
+    assert!(matches!(
+        result,
+        Err(MatchRequestError::TimestampsNotSorted)
+    ));
+    let result = MatchTrace::new(&points[..4], None, None, TraceOptions::default());
+    assert!(matches!(result, Err(MatchRequestError::InsufficientPoints)));
+}
+
+#[test]
+fn test_mock_is_on_map() {
+    let engine = OsrmEngine::new();
+    let point = points()[0];
+    assert!(
+        engine.is_on_map(point, 0.0).expect("Failed to snap"),
+        "Points snapping exactly onto a road should be on the map"
+    );
+
+    let engine = OsrmEngine::new().snap_distance_m(250.0);
+    assert!(!engine.is_on_map(point, 100.0).expect("Failed to snap"));
+    assert!(engine.is_on_map(point, 250.0).expect("Failed to snap"));
+    assert_eq!(engine.call_count(), 2);
+}
+
+#[test]
+#[should_panic(expected = "assumed_speed_mps must be positive and finite")]
+fn test_mock_zero_assumed_speed() {
+    let _ = OsrmEngine::new().assumed_speed_mps(0.0);
+}
+
+#[test]
+#[should_panic(expected = "assumed_speed_mps must be positive and finite")]
+fn test_mock_nan_assumed_speed() {
+    let _ = OsrmEngine::new().assumed_speed_mps(f64::NAN);
+}
+
+#[test]
+fn test_mock_route_via_points() {
+    let engine = OsrmEngine::new();
+    let points = points();
+
+    let route_request = RouteRequestBuilder::new(&points)
+        .waypoints(&[0, 2])
+        .build()
+        .expect("Failed to build route request");
+    let response = engine
+        .route(&route_request)
+        .expect("Failed to route request");
+
+    assert_eq!(response.routes[0].legs.len(), 1);
+    let waypoints = response.waypoints.expect("Waypoints were not returned");
+    assert_eq!(waypoints.len(), 2);
+    assert_eq!(waypoints[1].as_point(), points[2]);
+}
+
+#[test]
+fn test_mock_legs_between_via_points() {
+    let engine = OsrmEngine::new();
+    let points = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+        Point::new(48.942296, 10.510960).expect("Invalid point"),
+        Point::new(48.542296, 10.110960).expect("Invalid point"),
+    ];
+
+    // Point 1 is a via point, so the legs run between waypoints 0, 1 and 2,
+    // ie. input points 0, 2 and 3
+    let route_request = RouteRequestBuilder::new(&points)
+        .waypoints(&[0, 2, 3])
+        .build()
+        .expect("Failed to build route request");
+    let response = engine
+        .route(&route_request)
+        .expect("Failed to route request");
+    assert_eq!(response.routes[0].legs.len(), 2);
+
+    let spans = response
+        .legs_between(&[0, 1, 2])
+        .expect("Waypoint indices should be valid");
+    assert_eq!(spans.len(), 2);
+    assert!(spans.iter().all(|span| span.legs.len() == 1));
+    let whole = response
+        .legs_between(&[0, 2])
+        .expect("Waypoint indices should be valid");
+    assert_eq!(whole[0].legs.len(), 2);
+    assert!(
+        response.legs_between(&[0, 3]).is_none(),
+        "Input point indices past the last waypoint should be rejected"
+    );
+}
@@ -10,8 +10,11 @@ use osrm_interface::{
     r#match::MatchRequestBuilder,
     nearest::NearestRequestBuilder,
     osrm_response_types::Geometry,
-    request_types::{Bearing, CarExclude, Exclude, GeometryType, OverviewZoom},
+    request_types::{
+        Annotations, Bearing, CarExclude, Exclude, GeometryType, OverviewZoom, Snapping,
+    },
     route::RouteRequestBuilder,
+    services::Approach,
     table::TableRequestBuilder,
     trip::TripRequestBuilder,
 };
@@ -120,6 +123,41 @@ fn test_basic_native_trip() {
     );
 }
 
+#[test]
+fn test_native_trip_speed_annotations() {
+    let engine = init_native_engine(".env");
+
+    let points = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+        Point::new(48.942296, 10.510960).expect("Invalid point"),
+    ];
+    let speed_only = Annotations {
+        speed: true,
+        ..Annotations::NONE
+    };
+    let trip_request = TripRequestBuilder::new(&points)
+        .annotations(speed_only)
+        .build()
+        .expect("Failed to build trip request");
+
+    let trip_response = engine.trip(&trip_request).expect("Failed navigate trip");
+
+    assert_eq!(trip_response.code, "Ok", "Response code is not 'Ok'");
+    for leg in trip_response.trips.iter().flat_map(|t| t.legs.iter()) {
+        let annotation = leg
+            .annotation
+            .as_ref()
+            .expect("Trip legs should have annotations");
+        assert!(!annotation.speed.is_empty(), "Speed should be populated");
+        assert!(annotation.duration.is_empty());
+        assert!(annotation.distance.is_empty());
+        assert!(annotation.weight.is_empty());
+        assert!(annotation.nodes.is_empty());
+        assert!(annotation.datasources.is_empty());
+    }
+}
+
 #[test]
 fn test_native_route_steps() {
     let engine = init_native_engine(".env");
@@ -257,6 +295,32 @@ fn test_native_nearest() {
     );
 }
 
+#[test]
+fn test_native_nearest_exclude_snapping() {
+    let engine = init_native_engine(".env");
+
+    let num_points = 3;
+    let point = Point::new(48.040437, 10.316550).expect("Invalid point");
+    let excludes = [Exclude::Car(CarExclude::Motorway)];
+    for snapping in [Snapping::Default, Snapping::Any] {
+        let nearest_request = NearestRequestBuilder::new(&point, num_points)
+            .exclude(&excludes)
+            .snapping(snapping)
+            .build()
+            .expect("Failed to build nearest request");
+        let response = engine
+            .nearest(&nearest_request)
+            .expect("Failed to find nearest");
+
+        assert_eq!(response.code, "Ok", "Response code is not 'Ok'");
+        assert_eq!(
+            response.waypoints.len(),
+            num_points as usize,
+            "Nearest returned the wrong number of points for {snapping:?}"
+        );
+    }
+}
+
 #[test]
 fn test_native_table() {
     let engine = init_native_engine(".env");
@@ -509,3 +573,96 @@ fn test_route_skip_waypoints() {
         "Waypoints were returned despite skip_waypoints=true"
     )
 }
+
+#[test]
+fn test_native_route_approaches() {
+    let engine = init_native_engine(".env");
+
+    // Destination on St. Petersburger Straße, a dual carriageway in Dresden
+    let points = [
+        Point::new(51.0505, 13.7373).expect("Invalid point"),
+        Point::new(51.04456, 13.74155).expect("Invalid point"),
+    ];
+
+    let mut distances = Vec::new();
+    for approach in [Approach::Curb, Approach::Opposite] {
+        let approaches = [Approach::Unrestricted, approach];
+        let route_request = RouteRequestBuilder::new(&points)
+            .approaches(&approaches)
+            .build()
+            .expect("Failed to create route request");
+        let response = engine.route(&route_request).expect("Failed to route");
+        assert_eq!(response.code, "Ok", "Response code is not 'Ok'");
+        distances.push(response.routes[0].distance);
+    }
+
+    assert_ne!(
+        distances[0], distances[1],
+        "Curb and opposite approaches to a divided road produced the same route"
+    );
+
+    // Approaches are read by osrm-backend as an array of bytes, a mixed array
+    // checks each element lands on its own point
+    assert_eq!(std::mem::size_of::<Approach>(), 1);
+    let points = [
+        Point::new(51.0505, 13.7373).expect("Invalid point"),
+        Point::new(51.0480, 13.7390).expect("Invalid point"),
+        Point::new(51.04456, 13.74155).expect("Invalid point"),
+    ];
+    let mut distances = Vec::new();
+    for approach in [Approach::Curb, Approach::Opposite] {
+        let approaches = [Approach::Unrestricted, Approach::Unrestricted, approach];
+        let route_request = RouteRequestBuilder::new(&points)
+            .approaches(&approaches)
+            .build()
+            .expect("Failed to create route request");
+        let response = engine.route(&route_request).expect("Failed to route");
+        assert_eq!(response.code, "Ok", "Response code is not 'Ok'");
+        distances.push(response.routes[0].distance);
+    }
+    assert_ne!(
+        distances[0], distances[1],
+        "The last approach of a mixed array was not applied to the last point"
+    );
+}
+
+#[test]
+fn test_native_simple_route_names() {
+    let engine = init_native_engine(".env");
+
+    let response = engine
+        .simple_route(
+            Point::new(51.08460070137968, 13.693104319460645).expect("Invalid point"),
+            Point::new(51.10033848278219, 13.715837111172739).expect("Invalid point"),
+        )
+        .expect("Failed to route");
+    assert_eq!(response.code, "Ok", "Response code is not 'Ok'");
+    assert!(
+        response.start_name.is_some(),
+        "Start name was not populated"
+    );
+    assert!(response.end_name.is_some(), "End name was not populated");
+}
+
+#[test]
+fn test_native_route_raw() {
+    let engine = init_native_engine(".env");
+
+    let points = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+    ];
+    let route_request = RouteRequestBuilder::new(&points)
+        .build()
+        .expect("No points in request");
+
+    let raw = engine
+        .route_raw(&route_request)
+        .expect("Failed to route request");
+    let parsed =
+        osrm_interface::route::RouteResponse::from_json(&raw).expect("Raw response should parse");
+    let response = engine
+        .route(&route_request)
+        .expect("Failed to route request");
+    assert_eq!(parsed.routes[0].distance, response.routes[0].distance);
+}
@@ -0,0 +1,129 @@
+use osrm_interface::polyline::{PolylineDecodeError, decode_polyline};
+use rand::Rng;
+
+#[test]
+fn test_decode_polyline() {
+    let points = decode_polyline("_p~iF~ps|U_ulLnnqC_mqNvxq`@", 5).expect("Invalid polyline");
+    let expected = [(38.5, -120.2), (40.7, -120.95), (43.252, -126.453)];
+    assert_eq!(points.len(), expected.len());
+    for (point, (latitude, longitude)) in points.iter().zip(expected) {
+        assert!((point.latitude() - latitude).abs() < 1e-9);
+        assert!((point.longitude() - longitude).abs() < 1e-9);
+    }
+
+    assert_eq!(decode_polyline("", 5), Ok(vec![]));
+}
+
+#[test]
+fn test_decode_polyline_malformed() {
+    // Latitude without a longitude
+    assert_eq!(
+        decode_polyline("_p~iF", 5),
+        Err(PolylineDecodeError::Truncated)
+    );
+    // Ends on a continuation byte
+    assert_eq!(
+        decode_polyline("_p~iF~ps|", 5),
+        Err(PolylineDecodeError::Truncated)
+    );
+    assert_eq!(
+        decode_polyline("_p~iF ps|U", 5),
+        Err(PolylineDecodeError::InvalidCharacter(5))
+    );
+    assert_eq!(
+        decode_polyline(&"~".repeat(20), 5),
+        Err(PolylineDecodeError::Overflow(0))
+    );
+}
+
+#[test]
+fn test_decode_polyline_out_of_range() {
+    // (38.5, -120.2) encoded with precision 6
+    assert_eq!(
+        decode_polyline("_izlhA~rlgdF", 6).map(|points| points[0].as_tuple()),
+        Ok((38.5, -120.2))
+    );
+    assert_eq!(
+        decode_polyline("_izlhA~rlgdF", 5),
+        Err(PolylineDecodeError::OutOfRange(0))
+    );
+    // (0, 0) followed by (91, 0)
+    assert_eq!(
+        decode_polyline("??_mljP?", 5),
+        Err(PolylineDecodeError::OutOfRange(2))
+    );
+}
+
+#[test]
+fn test_decode_polyline_random_input() {
+    let mut rng = rand::rng();
+    for _ in 0..10_000 {
+        let len = rng.random_range(0..64);
+        // Mostly polyline characters, with the occasional byte outside the alphabet
+        let encoded = (0..len)
+            .map(|_| {
+                if rng.random_bool(0.95) {
+                    rng.random_range(63u8..=126) as char
+                } else {
+                    rng.random_range(0u8..128) as char
+                }
+            })
+            .collect::<String>();
+        for precision in [5, 6] {
+            if let Ok(points) = decode_polyline(&encoded, precision) {
+                assert!(
+                    points.len() <= encoded.len() / 2,
+                    "Too many points decoded from {encoded:?}"
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_geometry_decode() {
+    use osrm_interface::{
+        osrm_response_types::{GeoJsonLineString, Geometry, GeometryDecodeError},
+        request_types::GeometryType,
+    };
+
+    // Brandenburger Tor to Rosenthaler Platz to Alexanderplatz, Berlin
+    let geojson = Geometry::GeoJson(GeoJsonLineString {
+        r#type: "LineString".to_string(),
+        coordinates: vec![
+            [13.38886, 52.51704],
+            [13.39763, 52.52941],
+            [13.42856, 52.52322],
+        ],
+    });
+    let expected = geojson
+        .decode(GeometryType::GeoJSON)
+        .expect("Failed to decode GeoJSON");
+    assert_eq!(expected[0].as_tuple(), (52.51704, 13.38886));
+
+    for (polyline, geometry_type) in [
+        ("ofp_Ik_vpAilAyu@te@i`E", GeometryType::Polyline),
+        ("_jkdcBwbepXcdWccPzaKcl{@", GeometryType::Polyline6),
+    ] {
+        let points = Geometry::Polyline(polyline.to_string())
+            .decode(geometry_type)
+            .expect("Failed to decode polyline");
+        assert_eq!(points.len(), expected.len());
+        for (point, expected) in points.iter().zip(&expected) {
+            assert!((point.latitude() - expected.latitude()).abs() < 1e-9);
+            assert!((point.longitude() - expected.longitude()).abs() < 1e-9);
+        }
+    }
+
+    let polyline = Geometry::Polyline("ofp_Ik_vpAilAyu@te@i`E".to_string());
+    assert_eq!(
+        polyline.decode(GeometryType::GeoJSON),
+        Err(GeometryDecodeError::UnknownPrecision)
+    );
+    assert_eq!(
+        Geometry::Polyline("_p~iF".to_string()).decode(GeometryType::Polyline),
+        Err(GeometryDecodeError::Polyline(
+            PolylineDecodeError::Truncated
+        ))
+    );
+}
@@ -1,4 +1,20 @@
-use osrm_interface::{Point, route::RouteRequestBuilder};
+mod common;
+#[cfg(feature = "log")]
+use common::capture_log::capture_warnings;
+
+use osrm_interface::{
+    Point, PointParseError,
+    r#match::{MatchGapsBehaviour, MatchRequestBuilder, MatchRequestError},
+    nearest::{NearestRequestBuilder, NearestRequestError},
+    osrm_response_types::Waypoint,
+    request_types::{
+        Annotations, Bearing, BicycleExclude, CarExclude, Exclude, ExcludeError, ExcludeSet,
+    },
+    route::{RouteRequestBuilder, RouteRequestError},
+    services::{Approach, DimensionMismatch, Location, PointSet},
+    table::{TableRequestBuilder, TableRequestError},
+    trip::{TripRequestBuilder, TripRequestError},
+};
 
 #[test]
 fn test_invalid_point() {
@@ -27,6 +43,83 @@ fn test_invalid_point() {
     );
 }
 
+#[test]
+fn test_point_from_str() {
+    let valid = [
+        ("52.5,13.4", (52.5, 13.4)),
+        (" 52.5 , 13.4 ", (52.5, 13.4)),
+        ("-33.86882,151.2093", (-33.86882, 151.2093)),
+        ("90,-180", (90.0, -180.0)),
+        ("0,0", (0.0, 0.0)),
+    ];
+    for (input, expected) in valid {
+        let point: Point = input.parse().expect(input);
+        assert_eq!(point.as_tuple(), expected, "{input}");
+    }
+
+    let malformed = [
+        "",
+        "52.5",
+        "52.5;13.4",
+        "52.5,13.4,0",
+        "lat,lon",
+        "52.5,",
+        ",13.4",
+    ];
+    for input in malformed {
+        assert_eq!(
+            input.parse::<Point>(),
+            Err(PointParseError::Malformed(input.to_string())),
+            "{input:?}"
+        );
+    }
+
+    let out_of_range = [
+        ("91,0", 91.0, 0.0),
+        ("0,180.5", 0.0, 180.5),
+        ("-90.1,10", -90.1, 10.0),
+    ];
+    for (input, latitude, longitude) in out_of_range {
+        assert_eq!(
+            input.parse::<Point>(),
+            Err(PointParseError::OutOfRange {
+                latitude,
+                longitude
+            }),
+            "{input}"
+        );
+    }
+}
+
+#[test]
+fn test_point_display() {
+    let point = Point::new(52.5, 13.4).expect("Invalid point");
+    assert_eq!(point.to_string(), "13.400000,52.500000");
+    let point = Point::new(-33.86882, 151.2093).expect("Invalid point");
+    assert_eq!(format!("{point}"), "151.209300,-33.868820");
+}
+
+#[test]
+fn test_bearing_bounds() {
+    for (value, range) in [(0, 0), (359, 180), (0, 180), (359, 0)] {
+        let bearing = Bearing::new(value, range).expect("Bearing should be valid");
+        assert_eq!((bearing.value(), bearing.range()), (value, range));
+        assert_eq!(bearing.url_form(), format!("{value},{range}"));
+    }
+
+    assert!(
+        Bearing::new(360, 90).is_none(),
+        "A bearing of 360 should be written as 0"
+    );
+    assert!(Bearing::new(-1, 90).is_none());
+    assert!(Bearing::new(90, 181).is_none());
+    assert!(Bearing::new(90, -1).is_none());
+    assert!(
+        Bearing::new(360, 181).is_none(),
+        "Both out of range should be rejected"
+    );
+}
+
 #[test]
 fn test_invalid_route_request() {
     let points = [];
@@ -43,3 +136,1101 @@ fn test_invalid_route_request() {
         "Request should return Error for one point"
     );
 }
+
+#[test]
+fn test_nearest_zero_number() {
+    let point = Point::new(48.040437, 10.316550).expect("Invalid point");
+    let result = NearestRequestBuilder::new(&point, 0).build();
+    assert!(
+        matches!(result, Err(NearestRequestError::ZeroNumber)),
+        "Nearest request should not allow zero candidates"
+    );
+    assert!(NearestRequestBuilder::new(&point, 1).build().is_ok());
+    assert!(
+        NearestRequestBuilder::new(&point, 0)
+            .with_ambiguity_check()
+            .build()
+            .is_ok(),
+        "The ambiguity check raises the number to 2"
+    );
+}
+
+#[test]
+fn test_exclude_set_matches_slice() {
+    let points = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+    ];
+    let exclude_set = ExcludeSet::car().motorway().toll().motorway();
+    assert_eq!(
+        exclude_set.as_slice(),
+        &[
+            Exclude::Car(CarExclude::Motorway),
+            Exclude::Car(CarExclude::Toll)
+        ],
+        "ExcludeSet should preserve insertion order and ignore duplicates"
+    );
+
+    let from_set = RouteRequestBuilder::new(&points)
+        .exclude_set(&exclude_set)
+        .build()
+        .expect("Failed to build route request from ExcludeSet");
+    let from_slice = RouteRequestBuilder::new(&points)
+        .exclude(exclude_set.as_slice())
+        .build()
+        .expect("Failed to build route request from slice");
+    assert_eq!(
+        format!("{:?}", from_set),
+        format!("{:?}", from_slice),
+        "ExcludeSet and slice should produce identical requests"
+    );
+
+    let bicycle_set = ExcludeSet::bicycle().ferry();
+    assert_eq!(
+        bicycle_set.as_slice(),
+        &[Exclude::Bicycle(BicycleExclude::Ferry)]
+    );
+}
+
+#[test]
+fn test_combine_exclude_presets() {
+    let avoid_highways = [Exclude::Car(CarExclude::Motorway)];
+    let truck_restrictions = [
+        Exclude::Car(CarExclude::Ferry),
+        Exclude::Car(CarExclude::Motorway),
+    ];
+    let combined = Exclude::combine([&avoid_highways[..], &truck_restrictions[..]])
+        .expect("Car presets should combine");
+    assert_eq!(
+        combined,
+        [
+            Exclude::Car(CarExclude::Motorway),
+            Exclude::Car(CarExclude::Ferry)
+        ],
+        "Combined presets should keep the first of each exclude in order"
+    );
+    assert_eq!(Exclude::combine([]), Ok(vec![]));
+
+    let bicycle = [Exclude::Bicycle(BicycleExclude::Ferry)];
+    assert_eq!(
+        Exclude::combine([&avoid_highways[..], &bicycle[..]]),
+        Err(ExcludeError::DifferentExcludeTypes)
+    );
+    assert_eq!(
+        Exclude::combine([&[][..], &bicycle[..], &avoid_highways[..]]),
+        Err(ExcludeError::DifferentExcludeTypes)
+    );
+
+    let union = ExcludeSet::car()
+        .motorway()
+        .union(&ExcludeSet::car().ferry().motorway());
+    assert_eq!(union.as_slice(), combined);
+}
+
+#[test]
+fn test_point_tuple_order() {
+    let point = Point::new(48.040437, 10.316550).expect("Invalid point");
+
+    let (lat, lon) = point.as_tuple();
+    assert_eq!(
+        lat,
+        point.latitude(),
+        "as_tuple should return latitude first"
+    );
+    assert_eq!(
+        lon,
+        point.longitude(),
+        "as_tuple should return longitude second"
+    );
+
+    let (lon, lat) = point.as_lon_lat();
+    assert_eq!(
+        lon,
+        point.longitude(),
+        "as_lon_lat should return longitude first"
+    );
+    assert_eq!(
+        lat,
+        point.latitude(),
+        "as_lon_lat should return latitude second"
+    );
+
+    let swapped = point.as_lon_lat();
+    assert_eq!(point.as_tuple(), (swapped.1, swapped.0));
+}
+
+#[test]
+fn test_point_from_lng_lat() {
+    for (a, b) in [(48.040437, 10.316550), (-33.8688, 151.2093), (0.0, -179.99)] {
+        assert_eq!(Point::new(a, b), Point::from_lng_lat(b, a));
+    }
+
+    let (lon, lat) = Point::new(48.040437, 10.316550)
+        .expect("Invalid point")
+        .as_lon_lat();
+    assert_eq!(
+        Point::from_lng_lat(lon, lat).map(|p| p.as_lon_lat()),
+        Some((lon, lat)),
+        "from_lng_lat should round trip as_lon_lat"
+    );
+
+    assert!(
+        Point::from_lng_lat(10.0, 91.0).is_none(),
+        "Latitude should still be range checked"
+    );
+    assert!(
+        Point::from_lng_lat(100.0, 10.0).is_some(),
+        "Longitudes beyond 90 are valid"
+    );
+}
+
+#[test]
+fn test_point_haversine_distance() {
+    let berlin = Point::new(52.517037, 13.388860).expect("Invalid point");
+    let munich = Point::new(48.137154, 11.576124).expect("Invalid point");
+
+    let distance = berlin.haversine_distance(&munich);
+    assert!(
+        (distance - 504_000.0).abs() < 2_000.0,
+        "Berlin to Munich should be roughly 504km, got {distance}"
+    );
+    assert_eq!(distance, munich.haversine_distance(&berlin));
+    assert_eq!(berlin.haversine_distance(&berlin), 0.0);
+}
+
+#[test]
+fn test_empty_route_request_builder() {
+    // Building without points is a compile error, see the `compile_fail`
+    // doctest on `RouteRequestBuilder`
+    let builder = RouteRequestBuilder::empty().steps(true);
+
+    let points = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+    ];
+    let route_request = builder
+        .clone()
+        .points(&points)
+        .build()
+        .expect("Builder should succeed once points are supplied");
+    assert!(
+        format!("{route_request:?}").contains("steps: true"),
+        "Options set before the points should be kept"
+    );
+
+    assert!(
+        matches!(
+            builder.points(&points[..1]).build(),
+            Err(RouteRequestError::InsufficientPoints)
+        ),
+        "Building with a single point should return InsufficientPoints"
+    );
+}
+
+#[test]
+fn test_build_unchecked_matches_build() {
+    let points = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+    ];
+    let radiuses = [Some(10.0), None];
+    let exclude = [Exclude::Car(CarExclude::Toll)];
+    let builder = RouteRequestBuilder::new(&points)
+        .steps(true)
+        .radiuses(&radiuses)
+        .exclude(&exclude);
+
+    let checked = builder.build().expect("Failed to build route request");
+    let unchecked = builder.build_unchecked();
+    assert_eq!(
+        format!("{checked:?}"),
+        format!("{unchecked:?}"),
+        "build_unchecked should produce the same request as build"
+    );
+}
+
+#[test]
+fn test_approach_discriminants() {
+    // Must match osrm::engine::Approach for the native engine
+    assert_eq!(Approach::Curb as i32, 0);
+    assert_eq!(Approach::Unrestricted as i32, 1);
+    assert_eq!(Approach::Opposite as i32, 2);
+    assert_eq!(std::mem::size_of::<Approach>(), 1);
+}
+
+#[test]
+fn test_route_request_from_waypoints() {
+    let waypoints = [
+        Waypoint {
+            hint: Some("hint".to_string()),
+            ..Waypoint::default()
+        },
+        Waypoint {
+            hint: None,
+            ..Waypoint::default()
+        },
+    ];
+    assert!(
+        RouteRequestBuilder::from_waypoints(&waypoints)
+            .build()
+            .is_ok(),
+        "Request from waypoints should build, with or without hints"
+    );
+    assert!(
+        matches!(
+            RouteRequestBuilder::from_waypoints(&waypoints[..1]).build(),
+            Err(RouteRequestError::InsufficientPoints)
+        ),
+        "Request from a single waypoint should not build"
+    );
+}
+
+#[test]
+fn test_request_error_hints() {
+    let dimension_hints = [
+        (DimensionMismatch::Timestamps, "one element per point"),
+        (
+            DimensionMismatch::Bearings,
+            "pass `None` to leave a point unconstrained",
+        ),
+        (
+            DimensionMismatch::Radiuses,
+            "pass `None` for an unlimited radius",
+        ),
+        (
+            DimensionMismatch::Hints,
+            "pass `None` for points without a hint",
+        ),
+        (
+            DimensionMismatch::Approaches,
+            "pass `Approach::Unrestricted`",
+        ),
+    ];
+    for (mismatch, hint) in dimension_hints {
+        assert!(mismatch.hint().contains(hint));
+        for message in [
+            RouteRequestError::DimensionMismatch(mismatch).to_string(),
+            TripRequestError::DimensionMismatch(mismatch).to_string(),
+            MatchRequestError::DimensionMismatch(mismatch).to_string(),
+            TableRequestError::DimensionMismatch(mismatch).to_string(),
+        ] {
+            assert!(message.contains(hint), "Missing hint in {message:?}");
+        }
+    }
+
+    let hints = [
+        (
+            RouteRequestError::InsufficientPoints.to_string(),
+            "pass at least a start and an end point",
+        ),
+        (
+            RouteRequestError::DifferentExcludeTypes.to_string(),
+            "use the excludes of a single profile",
+        ),
+        (
+            RouteRequestError::NegativeRadius.to_string(),
+            "pass `None` for an unlimited radius",
+        ),
+        (
+            RouteRequestError::WaypointsMissingEndpoints.to_string(),
+            "include indices 0 and points.len() - 1",
+        ),
+        (
+            TripRequestError::InsufficientPoints.to_string(),
+            "pass at least a start and an end point",
+        ),
+        (
+            TripRequestError::DifferentExcludeTypes.to_string(),
+            "use the excludes of a single profile",
+        ),
+        (
+            TripRequestError::NegativeRadius.to_string(),
+            "pass `None` for an unlimited radius",
+        ),
+        (
+            MatchRequestError::InsufficientPoints.to_string(),
+            "pass at least a start and an end point",
+        ),
+        (
+            MatchRequestError::TimestampsNotSorted.to_string(),
+            "sort the points and timestamps by time",
+        ),
+        (
+            MatchRequestError::TimestampsRequiredForSplitBehaviour.to_string(),
+            "use `MatchGapsBehaviour::Ignore`",
+        ),
+        (
+            MatchRequestError::EmptyWaypoints.to_string(),
+            "leave waypoints unset",
+        ),
+        (
+            MatchRequestError::WaypointIndexOutOfBounds(5, 3).to_string(),
+            "indices must be less than 3",
+        ),
+        (
+            MatchRequestError::DifferentExcludeTypes.to_string(),
+            "use the excludes of a single profile",
+        ),
+        (
+            MatchRequestError::NegativeRadius.to_string(),
+            "pass `None` for an unlimited radius",
+        ),
+        (
+            TableRequestError::EmptySources.to_string(),
+            "pass at least one source point",
+        ),
+        (
+            TableRequestError::EmptyDestinations.to_string(),
+            "pass at least one destination point",
+        ),
+        (
+            TableRequestError::NonPositiveFallbackSpeed.to_string(),
+            "pass a speed in meters per second",
+        ),
+        (
+            TableRequestError::NonPositiveScaleFactor.to_string(),
+            "use 1.0 to leave durations unscaled",
+        ),
+        (
+            TableRequestError::IncompleteFallbackPair.to_string(),
+            "set both with `fallback`",
+        ),
+        (
+            TableRequestError::ScaleFactorRequiresDuration.to_string(),
+            "annotations must be TableAnnotation::Duration",
+        ),
+        (
+            TableRequestError::DifferentExcludeTypes.to_string(),
+            "use the excludes of a single profile",
+        ),
+        (
+            TableRequestError::NegativeRadius.to_string(),
+            "pass `None` for an unlimited radius",
+        ),
+        (
+            NearestRequestError::DifferentExcludeTypes.to_string(),
+            "use the excludes of a single profile",
+        ),
+        (
+            NearestRequestError::NegativeRadius.to_string(),
+            "pass `None` for an unlimited radius",
+        ),
+        (NearestRequestError::ZeroNumber.to_string(), "at least 1"),
+    ];
+    for (message, hint) in hints {
+        assert!(message.contains(hint), "Missing hint in {message:?}");
+    }
+}
+
+#[test]
+fn test_point_set_builders() {
+    let points = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+        Point::new(48.942296, 10.510960).expect("Invalid point"),
+    ];
+    let destinations = [Point::new(51.248931, 7.594814).expect("Invalid point")];
+    let point_set = PointSet::from(&points[..]);
+    assert_eq!(point_set.points(), &points);
+
+    assert_eq!(
+        format!("{:?}", point_set.route().steps(true).build()),
+        format!(
+            "{:?}",
+            RouteRequestBuilder::new(&points).steps(true).build()
+        ),
+    );
+    assert_eq!(
+        format!("{:?}", point_set.table_to(&destinations).build()),
+        format!(
+            "{:?}",
+            TableRequestBuilder::new(&points, &destinations).build()
+        ),
+    );
+    assert_eq!(
+        format!("{:?}", point_set.trip().build()),
+        format!("{:?}", TripRequestBuilder::new(&points).build()),
+    );
+    assert_eq!(
+        format!("{:?}", point_set.match_trace().build()),
+        format!("{:?}", MatchRequestBuilder::new(&points).build()),
+    );
+}
+
+#[test]
+fn test_strict_unconstrained_approaches() {
+    let points = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+    ];
+    let approaches = [Approach::Curb, Approach::Unrestricted];
+    let radiuses = [Some(10.0), None];
+
+    let builder = RouteRequestBuilder::new(&points).approaches(&approaches);
+    assert!(
+        builder.build().is_ok(),
+        "Unconstrained approaches should be allowed by default"
+    );
+    assert!(matches!(
+        builder.clone().strict(true).build(),
+        Err(RouteRequestError::UnconstrainedApproaches)
+    ));
+    assert!(
+        builder.strict(true).radiuses(&radiuses).build().is_ok(),
+        "Radiuses should constrain the approaches"
+    );
+    assert!(
+        RouteRequestBuilder::new(&points)
+            .approaches(&[Approach::Unrestricted; 2])
+            .strict(true)
+            .build()
+            .is_ok(),
+        "Unrestricted approaches need no constraint"
+    );
+
+    assert!(matches!(
+        TripRequestBuilder::new(&points)
+            .approaches(&approaches)
+            .strict(true)
+            .build(),
+        Err(TripRequestError::UnconstrainedApproaches)
+    ));
+    assert!(matches!(
+        MatchRequestBuilder::new(&points)
+            .gaps(MatchGapsBehaviour::Ignore)
+            .approaches(&approaches)
+            .strict(true)
+            .build(),
+        Err(MatchRequestError::UnconstrainedApproaches)
+    ));
+    assert!(matches!(
+        TableRequestBuilder::new(&points, &points)
+            .destination_approaches(&approaches)
+            .source_radiuses(&radiuses)
+            .strict(true)
+            .build(),
+        Err(TableRequestError::UnconstrainedApproaches)
+    ));
+    assert!(matches!(
+        NearestRequestBuilder::new(&points[0], 1)
+            .approach(Approach::Curb)
+            .strict(true)
+            .build(),
+        Err(NearestRequestError::UnconstrainedApproach)
+    ));
+    assert!(
+        NearestRequestBuilder::new(&points[0], 1)
+            .approach(Approach::Curb)
+            .radius(10.0)
+            .strict(true)
+            .build()
+            .is_ok(),
+        "A radius should constrain the approach"
+    );
+}
+
+#[cfg(feature = "log")]
+#[test]
+fn test_unconstrained_approaches_warning() {
+    let points = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+    ];
+    let approaches = [Approach::Curb, Approach::Unrestricted];
+
+    let (request, warnings) = capture_warnings(|| {
+        RouteRequestBuilder::new(&points)
+            .approaches(&approaches)
+            .build()
+    });
+    assert!(request.is_ok());
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("constrain snapping"));
+
+    let (request, warnings) = capture_warnings(|| {
+        NearestRequestBuilder::new(&points[0], 1)
+            .approach(Approach::Opposite)
+            .build()
+    });
+    assert!(request.is_ok());
+    assert_eq!(warnings.len(), 1);
+
+    let (request, warnings) = capture_warnings(|| {
+        TableRequestBuilder::new(&points, &points)
+            .source_approaches(&approaches)
+            .destination_approaches(&approaches)
+            .build()
+    });
+    assert!(request.is_ok());
+    assert_eq!(warnings.len(), 1, "Table should warn once per request");
+
+    let (request, warnings) = capture_warnings(|| {
+        RouteRequestBuilder::new(&points)
+            .approaches(&approaches)
+            .strict(true)
+            .build()
+    });
+    assert!(request.is_err());
+    assert!(
+        warnings.is_empty(),
+        "Strict mode should error instead of warn"
+    );
+
+    let (_, warnings) = capture_warnings(|| {
+        RouteRequestBuilder::new(&points)
+            .approaches(&[Approach::Unrestricted; 2])
+            .build()
+    });
+    assert!(warnings.is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_points_from_feature_collection() {
+    use osrm_interface::request_types::{GeoJsonParseError, points_from_feature_collection};
+
+    let feature = |geometry: serde_json::Value| serde_json::json!({ "type": "Feature", "geometry": geometry, "properties": {} });
+    let collection = |features: Vec<serde_json::Value>| serde_json::json!({ "type": "FeatureCollection", "features": features });
+
+    let points = points_from_feature_collection(&collection(vec![
+        feature(serde_json::json!({ "type": "Point", "coordinates": [10.31655, 48.040437] })),
+        feature(serde_json::json!({
+            "type": "LineString",
+            "coordinates": [[9.0, 49.0], [9.1, 49.1]]
+        })),
+        feature(serde_json::Value::Null),
+        feature(
+            serde_json::json!({ "type": "Point", "coordinates": [9.052887, 49.006101, 120.0] }),
+        ),
+    ]))
+    .expect("Failed to parse feature collection");
+    assert_eq!(
+        points,
+        [
+            Point::new(48.040437, 10.31655).unwrap(),
+            Point::new(49.006101, 9.052887).unwrap(),
+        ],
+        "Only point features should be extracted, in order"
+    );
+
+    assert_eq!(
+        points_from_feature_collection(&collection(vec![
+            feature(serde_json::json!({ "type": "Point", "coordinates": [10.0, 48.0] })),
+            feature(serde_json::json!({ "type": "Point", "coordinates": [10.0, 91.0] })),
+        ])),
+        Err(GeoJsonParseError::InvalidCoordinates(1))
+    );
+    assert_eq!(
+        points_from_feature_collection(&collection(vec![feature(
+            serde_json::json!({ "type": "Point", "coordinates": ["10.0", 48.0] })
+        )])),
+        Err(GeoJsonParseError::InvalidFeature(0))
+    );
+    assert_eq!(
+        points_from_feature_collection(&feature(serde_json::json!({
+            "type": "Point",
+            "coordinates": [10.0, 48.0]
+        }))),
+        Err(GeoJsonParseError::NotAFeatureCollection)
+    );
+}
+
+#[test]
+fn test_downsample_trace() {
+    use osrm_interface::request_types::downsample;
+
+    // Roughly 1.1m steps northwards, with a backtrack
+    let mut trace: Vec<Point> = (0..100)
+        .map(|i| Point::new(48.0 + i as f64 * 1e-5, 10.0).expect("Invalid point"))
+        .collect();
+    trace.push(trace[0]);
+
+    let kept = downsample(&trace, 15.0);
+    assert_eq!(kept[0], 0, "The first point should always be kept");
+    assert!(
+        kept.windows(2).all(|w| w[0] < w[1]),
+        "Indices should be in order"
+    );
+    for pair in kept.windows(2) {
+        let distance = trace[pair[0]].haversine_distance(&trace[pair[1]]);
+        assert!(
+            distance >= 15.0,
+            "Kept points {pair:?} are only {distance}m apart"
+        );
+    }
+    assert!(
+        kept.len() < trace.len() / 5,
+        "Dense trace should be thinned"
+    );
+    assert_eq!(
+        kept.last(),
+        Some(&(trace.len() - 1)),
+        "Returning to the start is far from the last kept point"
+    );
+
+    assert!(downsample(&[], 15.0).is_empty());
+    assert_eq!(downsample(&trace, 0.0).len(), trace.len());
+}
+
+#[test]
+fn test_convex_hull() {
+    use osrm_interface::request_types::convex_hull;
+
+    let point = |lat: f64, lon: f64| Point::new(lat, lon).expect("Invalid point");
+    let square = [
+        point(48.0, 10.0),
+        point(48.0, 10.1),
+        point(48.1, 10.1),
+        point(48.1, 10.0),
+    ];
+
+    // Interior, duplicate and edge points are dropped
+    let mut points = square.to_vec();
+    points.extend([
+        point(48.05, 10.05),
+        point(48.0, 10.1),
+        point(48.0, 10.05),
+        point(48.1, 10.0),
+    ]);
+    let hull = convex_hull(&points);
+    assert_eq!(
+        hull.len(),
+        4,
+        "Expected the corners of the square, got {hull:?}"
+    );
+    assert!(square.iter().all(|corner| hull.contains(corner)));
+    assert_eq!(hull[0], point(48.0, 10.0), "Hull should start westernmost");
+    assert_eq!(
+        hull[1],
+        point(48.0, 10.1),
+        "Hull should be counter-clockwise"
+    );
+
+    // Exactly representable, so the points are exactly collinear
+    let collinear = [point(48.0, 10.0), point(48.5, 10.5), point(48.25, 10.25)];
+    assert_eq!(
+        convex_hull(&collinear),
+        vec![point(48.0, 10.0), point(48.5, 10.5)],
+        "Collinear points should reduce to the endpoints"
+    );
+
+    assert_eq!(convex_hull(&[square[0]; 3]), vec![square[0]]);
+    assert!(convex_hull(&[]).is_empty());
+}
+
+#[test]
+fn test_cluster_points() {
+    use osrm_interface::request_types::cluster;
+
+    // Three buildings roughly 1km apart, each with a few stops within 3m
+    let buildings = [(48.0, 10.0), (48.01, 10.0), (48.0, 10.015)];
+    let points: Vec<Point> = (0..4)
+        .flat_map(|i| {
+            buildings.iter().map(move |(lat, lon)| {
+                Point::new(lat + i as f64 * 5e-6, lon + i as f64 * 5e-6).expect("Invalid point")
+            })
+        })
+        .collect();
+
+    let (representatives, assignment) = cluster(&points, 5.0);
+    assert_eq!(representatives.len(), buildings.len());
+    assert_eq!(assignment.len(), points.len());
+    for (i, a) in points.iter().enumerate() {
+        let representative = &representatives[assignment[i]];
+        assert!(representative.haversine_distance(a) <= 5.0);
+        for (j, b) in points.iter().enumerate() {
+            if a.haversine_distance(b) <= 5.0 {
+                assert_eq!(
+                    assignment[i], assignment[j],
+                    "Points {i} and {j} are within the radius"
+                );
+            }
+        }
+    }
+
+    let (representatives, assignment) = cluster(&points, 0.0);
+    assert_eq!(
+        representatives, points,
+        "Distinct points should not collapse"
+    );
+    assert_eq!(assignment, (0..points.len()).collect::<Vec<_>>());
+    assert_eq!(cluster(&[], 5.0), (vec![], vec![]));
+}
+
+#[test]
+fn test_request_url_parts() {
+    let points = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+    ];
+    let radiuses = [Some(10.0), None];
+    let route_request = RouteRequestBuilder::new(&points)
+        .radiuses(&radiuses)
+        .build()
+        .expect("Failed to build route request");
+    let parts = route_request.to_url_parts("driving", "v1", Some("json"));
+    assert_eq!(
+        parts.path,
+        "/route/v1/driving/10.316550,48.040437;9.052887,49.006101.json"
+    );
+    assert_eq!(
+        parts.query.first(),
+        Some(&("alternatives".to_string(), "false".to_string()))
+    );
+    assert_eq!(
+        parts.query.last(),
+        Some(&("radiuses".to_string(), "10.000000000000;".to_string()))
+    );
+    let url = parts.to_url("http://localhost:5000");
+    assert!(url.starts_with("http://localhost:5000/route/v1/driving/10.316550,48.040437;"));
+    assert!(url.contains(".json?alternatives=false&steps=false&"));
+    assert!(url.ends_with("&radiuses=10.000000000000;"));
+
+    let radiuses = [Some(5.0)];
+    let table_request = TableRequestBuilder::new(&points, &points[..1])
+        .destination_radiuses(&radiuses)
+        .build()
+        .expect("Failed to build table request");
+    let parts = table_request.to_url_parts("car", "v1", None);
+    assert_eq!(
+        parts.path,
+        "/table/v1/car/10.316550,48.040437;9.052887,49.006101;10.316550,48.040437"
+    );
+    let query = |key: &str| {
+        parts
+            .query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    };
+    assert_eq!(query("sources"), Some("0;1"));
+    assert_eq!(query("destinations"), Some("2"));
+    assert_eq!(
+        query("radiuses"),
+        Some(";;5.000000000000"),
+        "Unset source radiuses should be empty"
+    );
+    assert_eq!(query("bearings"), None);
+
+    let nearest_request = osrm_interface::nearest::NearestRequestBuilder::new(&points[0], 2)
+        .build()
+        .expect("Failed to build nearest request");
+    assert_eq!(
+        nearest_request
+            .to_url_parts("foot", "v1", None)
+            .to_url("http://localhost:5000"),
+        "http://localhost:5000/nearest/v1/foot/10.316550,48.040437?number=2"
+    );
+
+    // The version and format follow a base path in the endpoint
+    assert_eq!(
+        nearest_request
+            .to_url_parts("foot", "v2", Some("json"))
+            .to_url("https://maps.example.com/osrm/"),
+        "https://maps.example.com/osrm/nearest/v2/foot/10.316550,48.040437.json?number=2"
+    );
+}
+
+#[test]
+fn test_table_url_per_point_options() {
+    use osrm_interface::request_types::Bearing;
+
+    let sources = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+        Point::new(48.942296, 10.510960).expect("Invalid point"),
+    ];
+    let destinations = [Point::new(51.248931, 7.594814).expect("Invalid point")];
+    let source_bearings = [
+        Some(Bearing::new_unchecked(90, 20)),
+        None,
+        Some(Bearing::new_unchecked(0, 180)),
+    ];
+    let destination_hints = [Some("abc")];
+    let source_approaches = [Approach::Curb, Approach::Unrestricted, Approach::Opposite];
+    let table_request = TableRequestBuilder::new(&sources, &destinations)
+        .source_bearings(&source_bearings)
+        .destination_hints(&destination_hints)
+        .source_approaches(&source_approaches)
+        .generate_hints(false)
+        .build()
+        .expect("Failed to build table request");
+
+    assert_eq!(
+        table_request
+            .to_url_parts("car", "v1", None)
+            .to_url("http://localhost:5000"),
+        "http://localhost:5000/table/v1/car/\
+         10.316550,48.040437;9.052887,49.006101;10.510960,48.942296;7.594814,51.248931\
+         ?sources=0;1;2&destinations=3&generate_hints=false&annotations=duration\
+         &bearings=90,20;;0,180;&hints=;;;abc&approaches=curb;unrestricted;opposite;"
+    );
+}
+
+#[test]
+fn test_generate_hints_defaults() {
+    use osrm_interface::nearest::NearestRequestBuilder;
+
+    let points = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+    ];
+    let generate_hints = |query: Vec<(String, String)>| {
+        query
+            .into_iter()
+            .find(|(key, _)| key == "generate_hints")
+            .map(|(_, value)| value)
+    };
+
+    let defaults = [
+        (
+            "route",
+            RouteRequestBuilder::new(&points)
+                .build()
+                .expect("Failed to build route request")
+                .to_url_parts("car", "v1", None)
+                .query,
+        ),
+        (
+            "table",
+            TableRequestBuilder::new(&points, &points)
+                .build()
+                .expect("Failed to build table request")
+                .to_url_parts("car", "v1", None)
+                .query,
+        ),
+        (
+            "trip",
+            TripRequestBuilder::new(&points)
+                .build()
+                .expect("Failed to build trip request")
+                .to_url_parts("car", "v1", None)
+                .query,
+        ),
+        (
+            "match",
+            MatchRequestBuilder::new(&points)
+                .gaps(MatchGapsBehaviour::Ignore)
+                .build()
+                .expect("Failed to build match request")
+                .to_url_parts("car", "v1", None)
+                .query,
+        ),
+    ];
+    for (service, query) in defaults {
+        assert_eq!(
+            generate_hints(query).as_deref(),
+            Some("true"),
+            "{service} should generate hints by default"
+        );
+    }
+
+    let disabled = [
+        RouteRequestBuilder::new(&points)
+            .generate_hints(false)
+            .build()
+            .expect("Failed to build route request")
+            .to_url_parts("car", "v1", None)
+            .query,
+        TableRequestBuilder::new(&points, &points)
+            .generate_hints(false)
+            .build()
+            .expect("Failed to build table request")
+            .to_url_parts("car", "v1", None)
+            .query,
+        TripRequestBuilder::new(&points)
+            .generate_hints(false)
+            .build()
+            .expect("Failed to build trip request")
+            .to_url_parts("car", "v1", None)
+            .query,
+        MatchRequestBuilder::new(&points)
+            .gaps(MatchGapsBehaviour::Ignore)
+            .generate_hints(false)
+            .build()
+            .expect("Failed to build match request")
+            .to_url_parts("car", "v1", None)
+            .query,
+    ];
+    for query in disabled {
+        assert_eq!(generate_hints(query).as_deref(), Some("false"));
+    }
+
+    // Nearest leaves the option to the backend default, which generates hints
+    let query = NearestRequestBuilder::new(&points[0], 1)
+        .build()
+        .expect("Failed to build nearest request")
+        .to_url_parts("car", "v1", None)
+        .query;
+    assert_eq!(generate_hints(query), None);
+}
+
+#[test]
+fn test_requests_from_locations() {
+    let start = Point::new(48.040437, 10.316550).expect("Invalid point");
+    let end = Point::new(49.006101, 9.052887).expect("Invalid point");
+    let hinted = [
+        Location::Hint {
+            hint: "start-hint",
+            input: start,
+        },
+        Location::from(end),
+    ];
+    let coordinates = "10.316550,48.040437;9.052887,49.006101";
+
+    let route_url = RouteRequestBuilder::from_locations(&hinted)
+        .build()
+        .expect("Failed to build route request")
+        .to_url_parts("driving", "v1", None)
+        .to_url("");
+    let trip_url = TripRequestBuilder::from_locations(&hinted)
+        .build()
+        .expect("Failed to build trip request")
+        .to_url_parts("driving", "v1", None)
+        .to_url("");
+    let match_url = MatchRequestBuilder::from_locations(&hinted)
+        .gaps(MatchGapsBehaviour::Ignore)
+        .build()
+        .expect("Failed to build match request")
+        .to_url_parts("driving", "v1", None)
+        .to_url("");
+    for url in [route_url, trip_url, match_url] {
+        assert!(url.contains(&format!("/{coordinates}?")), "{url}");
+        assert!(url.contains("&hints=start-hint;"), "{url}");
+    }
+
+    // Without any hints, no hints are sent
+    let points = [Location::Point(start), Location::Point(end)];
+    let url = RouteRequestBuilder::from_locations(&points)
+        .build()
+        .expect("Failed to build route request")
+        .to_url_parts("driving", "v1", None)
+        .to_url("");
+    assert!(!url.contains("&hints="), "{url}");
+    assert!(matches!(
+        TripRequestBuilder::from_locations(&hinted[..1]).build(),
+        Err(TripRequestError::InsufficientPoints)
+    ));
+}
+
+#[test]
+fn test_annotations_url_form() {
+    let points = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+    ];
+    let speed_only = Annotations {
+        speed: true,
+        ..Annotations::NONE
+    };
+    let duration_and_nodes = Annotations {
+        duration: true,
+        nodes: true,
+        ..Annotations::NONE
+    };
+    let cases = [
+        (Annotations::NONE, "false"),
+        (Annotations::from(true), "true"),
+        (speed_only, "speed"),
+        (duration_and_nodes, "duration,nodes"),
+    ];
+    for (annotations, expected) in cases {
+        let queries = [
+            RouteRequestBuilder::new(&points)
+                .annotations(annotations)
+                .build()
+                .expect("Failed to build route request")
+                .to_url_parts("car", "v1", None)
+                .query,
+            TripRequestBuilder::new(&points)
+                .annotations(annotations)
+                .build()
+                .expect("Failed to build trip request")
+                .to_url_parts("car", "v1", None)
+                .query,
+            MatchRequestBuilder::new(&points)
+                .annotations(annotations)
+                .gaps(MatchGapsBehaviour::Ignore)
+                .build()
+                .expect("Failed to build match request")
+                .to_url_parts("car", "v1", None)
+                .query,
+        ];
+        for query in queries {
+            assert!(
+                query.contains(&("annotations".to_string(), expected.to_string())),
+                "Expected annotations={expected} in {query:?}"
+            );
+        }
+    }
+    assert!(!Annotations::from(false).any());
+}
+
+#[test]
+fn test_route_alternatives_count() {
+    let points = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+    ];
+    let alternatives = |builder: RouteRequestBuilder| {
+        builder
+            .build()
+            .expect("Failed to build route request")
+            .to_url_parts("car", "v1", None)
+            .query
+            .into_iter()
+            .find(|(key, _)| key == "alternatives")
+            .map(|(_, value)| value)
+    };
+
+    let builder = RouteRequestBuilder::new(&points);
+    assert_eq!(alternatives(builder.clone()).as_deref(), Some("false"));
+    assert_eq!(
+        alternatives(builder.clone().alternatives(true)).as_deref(),
+        Some("1")
+    );
+    assert_eq!(
+        alternatives(builder.clone().alternatives_count(3)).as_deref(),
+        Some("3")
+    );
+    assert_eq!(
+        alternatives(builder.alternatives_count(3).alternatives(false)).as_deref(),
+        Some("false")
+    );
+}
+
+#[test]
+fn test_route_waypoints() {
+    let points = [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+        Point::new(48.942296, 10.510960).expect("Invalid point"),
+    ];
+    let builder = RouteRequestBuilder::new(&points);
+
+    let request = builder
+        .clone()
+        .waypoints(&[0, 2])
+        .build()
+        .expect("Failed to build route request");
+    let parts = request.to_url_parts("car", "v1", None);
+    assert!(
+        parts
+            .query
+            .iter()
+            .any(|(key, value)| key == "waypoints" && value == "0;2")
+    );
+
+    assert!(matches!(
+        builder.clone().waypoints(&[]).build(),
+        Err(RouteRequestError::EmptyWaypoints)
+    ));
+    assert!(matches!(
+        builder.clone().waypoints(&[0, 3]).build(),
+        Err(RouteRequestError::WaypointIndexOutOfBounds(3, 3))
+    ));
+    assert!(matches!(
+        builder.waypoints(&[0, 1]).build(),
+        Err(RouteRequestError::WaypointsMissingEndpoints)
+    ));
+}
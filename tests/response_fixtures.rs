@@ -0,0 +1,315 @@
+//! Regression tests parsing OSRM responses in `tests/fixtures`.
+//!
+//! The fixtures follow the osrm-routed v5 response format and exercise the
+//! optional fields most likely to break deserialisation. To replace them with
+//! the verbatim output of the backend set in `.env`, run
+//!
+//! ```text
+//! cargo test --features remote --test response_fixtures -- --ignored capture_fixtures
+//! ```
+//!
+//! and update the asserted values below to match the captured data.
+#![cfg(feature = "serde")]
+
+mod common;
+
+use osrm_interface::{
+    errors::OsrmError,
+    r#match::MatchResponse,
+    nearest::NearestResponse,
+    osrm_response_types::{DrivingMode, DrivingSide, Geometry},
+    route::RouteResponse,
+    table::TableResponse,
+    trip::TripResponse,
+};
+
+const ROUTE: &str = include_str!("fixtures/route.json");
+const TABLE: &str = include_str!("fixtures/table.json");
+const MATCH: &str = include_str!("fixtures/match.json");
+const TRIP: &str = include_str!("fixtures/trip.json");
+const NEAREST: &str = include_str!("fixtures/nearest.json");
+const NO_SEGMENT: &str = include_str!("fixtures/no_segment.json");
+const ROUTE_TOLL: &str = include_str!("fixtures/route_toll.json");
+
+#[test]
+fn test_fixture_route() {
+    let response = RouteResponse::from_json(ROUTE).expect("Failed to parse route fixture");
+    assert_eq!(response.code, "Ok");
+    assert_eq!(response.waypoints.as_ref().map(Vec::len), Some(2));
+
+    let route = &response.routes[0];
+    assert_eq!(route.weight_name, "routability");
+    let Some(Geometry::GeoJson(line)) = &route.geometry else {
+        panic!("Expected GeoJSON overview geometry");
+    };
+    assert_eq!(line.coordinates.len(), 4);
+
+    let leg = &route.legs[0];
+    assert_eq!(leg.summary, "Friedrichstraße, Torstraße");
+    let annotation = leg.annotation.as_ref().expect("Missing annotation");
+    assert_eq!(annotation.nodes.len(), annotation.distance.len() + 1);
+    assert_eq!(annotation.datasources, [0, 0, 1]);
+    assert_eq!(
+        annotation.metadata.datasource_names,
+        ["lua profile", "traffic"]
+    );
+
+    let [depart, turn, arrive] = leg.steps.as_slice() else {
+        panic!("Expected three steps, got {}", leg.steps.len());
+    };
+    assert_eq!(depart.maneuver.r#type, "depart");
+    assert!(matches!(depart.mode, DrivingMode::Driving));
+    assert_eq!(depart.driving_side, DrivingSide::Right);
+    let intersection = &depart.intersections[1];
+    assert_eq!(intersection.r#in, Some(1));
+    assert_eq!(
+        intersection.classes.as_deref(),
+        Some(&["restricted".to_string()][..])
+    );
+    let lanes = intersection.lanes.as_ref().expect("Missing lanes");
+    assert!(lanes[0].valid && !lanes[1].valid);
+
+    assert_eq!(turn.maneuver.modifier.as_deref(), Some("right"));
+    assert_eq!(turn.r#ref.as_deref(), Some("B 5"));
+    assert!(matches!(turn.geometry, Geometry::GeoJson(_)));
+    assert_eq!(arrive.maneuver.r#type, "arrive");
+    assert_eq!(arrive.intersections[0].out, None);
+}
+
+#[test]
+fn test_fixture_table() {
+    let response = TableResponse::from_json(TABLE).expect("Failed to parse table fixture");
+    assert_eq!(response.sources.as_ref().map(Vec::len), Some(2));
+    assert_eq!(response.destinations.as_ref().map(Vec::len), Some(2));
+
+    let durations = response.durations.as_ref().expect("Missing durations");
+    assert_eq!(durations[0], [Some(0.0), Some(428.1)]);
+    assert_eq!(durations[1][1], None, "Unroutable cells should be null");
+    let distances = response.distances.as_ref().expect("Missing distances");
+    assert_eq!(distances[1], [Some(1527.9), None]);
+    assert_eq!(response.fallback_speed_cells, Some(vec![vec![0.0, 1.0]]));
+}
+
+#[test]
+fn test_fixture_match() {
+    let response = MatchResponse::from_json(MATCH).expect("Failed to parse match fixture");
+    assert_eq!(response.matchings.len(), 2, "Fixture trace is split in two");
+    assert_eq!(response.tracepoints.len(), 5);
+    assert!(
+        response.tracepoints[1].is_none(),
+        "Unmatched tracepoints should be null"
+    );
+    assert!(response.validate_indices());
+
+    let second = response.tracepoints[3]
+        .as_ref()
+        .expect("Missing tracepoint");
+    assert_eq!(second.matchings_index, 1);
+    assert_eq!(second.waypoint_index, 0);
+    assert!(
+        response
+            .matchings
+            .iter()
+            .all(|m| matches!(m.geometry, Some(Geometry::Polyline(_))))
+    );
+    assert_eq!(response.matchings[1].confidence, 0.62);
+}
+
+#[test]
+fn test_fixture_trip() {
+    let response = TripResponse::from_json(TRIP).expect("Failed to parse trip fixture");
+    assert_eq!(response.trips.len(), 1);
+    assert_eq!(response.trips[0].legs.len(), 3);
+    assert_eq!(response.trips[0].weight_name, "duration");
+
+    let waypoints = response.waypoints.as_ref().expect("Missing waypoints");
+    let order: Vec<usize> = waypoints.iter().map(|w| w.waypoint_index).collect();
+    assert_eq!(order, [0, 2, 1]);
+    assert!(waypoints.iter().all(|w| w.trips_index == 0));
+}
+
+#[test]
+fn test_fixture_nearest() {
+    let response = NearestResponse::from_json(NEAREST).expect("Failed to parse nearest fixture");
+    assert_eq!(response.waypoints.len(), 2);
+    assert!(
+        response.waypoints.iter().all(|w| w.hint.is_some()),
+        "Nearest waypoints should carry hints"
+    );
+}
+
+#[test]
+fn test_fixture_no_segment() {
+    let result = RouteResponse::from_json(NO_SEGMENT);
+    assert!(
+        matches!(
+            &result,
+            Err(OsrmError::Service {
+                code,
+                failed_coordinate_index: Some(3),
+                ..
+            }) if code == "NoSegment"
+        ),
+        "Expected the failed coordinate to be reported, got {result:?}"
+    );
+
+    let result = RouteResponse::from_json(
+        r#"{"code":"NoSegment","message":"Could not find a matching segment for any coordinate"}"#,
+    );
+    assert!(
+        matches!(
+            result,
+            Err(OsrmError::Service {
+                failed_coordinate_index: None,
+                ..
+            })
+        ),
+        "Messages without an index should not report one"
+    );
+}
+
+#[test]
+fn test_fixture_lane_guidance() {
+    let response = RouteResponse::from_json(ROUTE).expect("Failed to parse route fixture");
+    let guidance = response.routes[0].lane_guidance();
+
+    // The depart step only has lanes at a later intersection, and the
+    // arrival has none
+    let [turn] = guidance.as_slice() else {
+        panic!("Expected guidance for the turn only, got {guidance:?}");
+    };
+    assert_eq!((turn.leg_index, turn.step_index), (0, 1));
+    assert_eq!(turn.lanes.len(), 3);
+    assert_eq!(turn.valid_lanes, [1, 2]);
+    let indications: Vec<&[String]> = turn
+        .valid_lanes
+        .iter()
+        .map(|&i| turn.lanes[i].indications.as_slice())
+        .collect();
+    assert_eq!(
+        indications,
+        [["straight".to_string()], ["right".to_string()]]
+    );
+}
+
+#[test]
+fn test_fixture_distance_on_class() {
+    let response = RouteResponse::from_json(ROUTE_TOLL).expect("Failed to parse toll fixture");
+    let route = &response.routes[0];
+
+    // Only the A 99 is tolled, the A 94 is an untolled motorway
+    assert_eq!(route.distance_on_class("toll"), 12650.0);
+    assert_eq!(route.distance_on_class("motorway"), 12650.0 + 2810.2);
+    assert_eq!(route.distance_on_class("ferry"), 0.0);
+    assert_eq!(
+        Some(route.distance_on_class("toll")),
+        route.class_coverage().get("toll").copied()
+    );
+    assert!(route.distance_on_class("toll") / route.distance > 0.75);
+}
+
+/// Overwrite every fixture with the raw response of the `.env` backend to the
+/// request it represents. Requires a car profile with a map covering Berlin
+/// and Munich.
+#[cfg(feature = "remote")]
+#[test]
+#[ignore]
+fn capture_fixtures() {
+    use osrm_interface::{
+        Point,
+        r#match::{MatchGapsBehaviour, MatchRequestBuilder},
+        nearest::NearestRequestBuilder,
+        request_types::{GeometryType, OverviewZoom},
+        route::RouteRequestBuilder,
+        table::{TableAnnotation, TableFallbackCoordinate, TableRequestBuilder},
+        trip::TripRequestBuilder,
+    };
+
+    let engine = common::init_remote_engine(".env");
+    // Keep error bodies, such as NoSegment, rather than failing on them
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .http_status_as_error(false)
+        .build()
+        .into();
+    let capture = |name: &str, url: String| {
+        let body = agent
+            .get(&url)
+            .call()
+            .and_then(|mut response| response.body_mut().read_to_string())
+            .unwrap_or_else(|e| panic!("Failed to capture {name} from {url}: {e}"));
+        let path = format!("{}/tests/fixtures/{name}.json", env!("CARGO_MANIFEST_DIR"));
+        std::fs::write(&path, body).unwrap_or_else(|e| panic!("Failed to write {path}: {e}"));
+    };
+
+    let friedrichstrasse = Point::new(52.517033, 13.388799).expect("Invalid point");
+    let torstrasse = Point::new(52.529432, 13.397631).expect("Invalid point");
+    let islandstrasse = Point::new(52.523239, 13.428554).expect("Invalid point");
+    let holzmarktstrasse = Point::new(52.512143, 13.427712).expect("Invalid point");
+    let tiergarten = Point::new(52.514511, 13.350091).expect("Invalid point");
+    let north_sea = Point::new(54.5, 3.0).expect("Invalid point");
+
+    let points = [friedrichstrasse, torstrasse];
+    let route_request = RouteRequestBuilder::new(&points)
+        .steps(true)
+        .annotations(true)
+        .geometry(GeometryType::GeoJSON)
+        .overview(OverviewZoom::Full)
+        .build()
+        .expect("Failed to build route request");
+    capture("route", engine.route_url(&route_request));
+
+    let points = [
+        Point::new(48.13742, 11.58782).expect("Invalid point"),
+        Point::new(48.15993, 11.65748).expect("Invalid point"),
+    ];
+    let route_request = RouteRequestBuilder::new(&points)
+        .steps(true)
+        .build()
+        .expect("Failed to build route request");
+    capture("route_toll", engine.route_url(&route_request));
+
+    let points = [friedrichstrasse, torstrasse, islandstrasse, north_sea];
+    let route_request = RouteRequestBuilder::new(&points)
+        .build()
+        .expect("Failed to build route request");
+    capture("no_segment", engine.route_url(&route_request));
+
+    let sources = [friedrichstrasse, torstrasse];
+    let destinations = [friedrichstrasse, islandstrasse];
+    let table_request = TableRequestBuilder::new(&sources, &destinations)
+        .annotations(TableAnnotation::All)
+        .fallback(TableFallbackCoordinate::Snapped, 10.0)
+        .build()
+        .expect("Failed to build table request");
+    capture("table", engine.table_url(&table_request));
+
+    // The unmatchable point in the park becomes a null tracepoint, and the
+    // gap in the timestamps splits the trace
+    let points = [
+        friedrichstrasse,
+        tiergarten,
+        torstrasse,
+        islandstrasse,
+        holzmarktstrasse,
+    ];
+    let timestamps = [0, 30, 150, 900, 1050];
+    let radiuses = [None, Some(1.0), None, None, None];
+    let match_request = MatchRequestBuilder::new(&points)
+        .timestamps(&timestamps)
+        .radiuses(&radiuses)
+        .gaps(MatchGapsBehaviour::Split)
+        .build()
+        .expect("Failed to build match request");
+    capture("match", engine.match_url(&match_request));
+
+    let points = [friedrichstrasse, islandstrasse, torstrasse];
+    let trip_request = TripRequestBuilder::new(&points)
+        .build()
+        .expect("Failed to build trip request");
+    capture("trip", engine.trip_url(&trip_request));
+
+    let nearest_request = NearestRequestBuilder::new(&friedrichstrasse, 2)
+        .build()
+        .expect("Failed to build nearest request");
+    capture("nearest", engine.nearest_url(&nearest_request));
+}
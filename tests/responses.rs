@@ -0,0 +1,1289 @@
+use osrm_interface::{
+    Point,
+    r#match::{MatchRequestBuilder, MatchResponse},
+    osrm_response_types::{
+        AggregatedSegment, DrivingSide, GeoJsonLineString, Geometry, Intersection, MatchRoute,
+        MatchWaypoint, NearestWaypoint, ResponseCode, Route, RouteLeg, RouteStep, StepManeuver,
+        TripWaypoint, Waypoint, lon_lat_point,
+    },
+    request_types::GeometryType,
+    route::{AlternativeSummary, RouteResponse},
+};
+
+#[test]
+fn test_route_weight_helpers() {
+    let route = Route::default();
+    assert_eq!(route.weight_profile(), "duration");
+    assert_eq!(route.total_weight(), route.weight);
+    assert!(
+        route.is_time_based(),
+        "Default route should be weighted by duration"
+    );
+
+    let route = Route {
+        weight_name: "routability".to_string(),
+        ..Route::default()
+    };
+    assert_eq!(route.weight_profile(), "routability");
+    assert!(
+        !route.is_time_based(),
+        "Route weighted by routability should not be time based"
+    );
+}
+
+#[test]
+fn test_route_totals() {
+    let route = Route::default();
+    assert_eq!(route.total_distance(), 60.0);
+    assert_eq!(route.total_duration(), 200.0);
+
+    let route = Route {
+        legs: vec![],
+        ..Route::default()
+    };
+    assert_eq!(route.total_distance(), 0.0);
+    assert_eq!(route.total_duration(), 0.0);
+
+    let alternative = Route {
+        distance: 120.0,
+        ..Route::default()
+    };
+    let response = RouteResponse {
+        code: "Ok".to_string(),
+        routes: vec![Route::default(), alternative],
+        waypoints: None,
+    };
+    let best = response.best_route().expect("Missing best route");
+    assert_eq!(best.distance, 90.0);
+    let empty = RouteResponse {
+        routes: vec![],
+        ..response
+    };
+    assert!(empty.best_route().is_none());
+}
+
+#[test]
+fn test_match_tracepoint_pairs() {
+    let points = [
+        Point::new(52.517037, 13.388860).expect("Invalid point"),
+        Point::new(52.529407, 13.397634).expect("Invalid point"),
+        Point::new(52.523219, 13.428555).expect("Invalid point"),
+    ];
+    let response = MatchResponse {
+        code: "Ok".to_string(),
+        tracepoints: vec![
+            Some(MatchWaypoint::default()),
+            None,
+            Some(MatchWaypoint {
+                waypoint_index: 1,
+                ..MatchWaypoint::default()
+            }),
+        ],
+        matchings: vec![MatchRoute::default()],
+    };
+
+    let pairs = response.tracepoint_pairs(&points);
+    assert_eq!(pairs.len(), points.len());
+    for (i, (point, _)) in pairs.iter().enumerate() {
+        assert_eq!(*point, points[i], "Pairs should preserve input order");
+    }
+    assert!(pairs[0].1.is_some());
+    assert!(pairs[1].1.is_none(), "Outlier should be paired with None");
+    assert_eq!(pairs[2].1.map(|w| w.waypoint_index), Some(1));
+}
+
+#[test]
+fn test_match_validate_indices() {
+    let mut response = MatchResponse {
+        code: "Ok".to_string(),
+        tracepoints: vec![
+            Some(MatchWaypoint::default()),
+            None,
+            Some(MatchWaypoint {
+                waypoint_index: 2,
+                ..MatchWaypoint::default()
+            }),
+        ],
+        matchings: vec![MatchRoute::default()],
+    };
+    assert!(response.validate_indices());
+
+    response.tracepoints[2] = Some(MatchWaypoint {
+        waypoint_index: 3,
+        ..MatchWaypoint::default()
+    });
+    assert!(
+        !response.validate_indices(),
+        "A two leg matching has no fourth waypoint"
+    );
+
+    response.tracepoints[2] = Some(MatchWaypoint {
+        matchings_index: 1,
+        ..MatchWaypoint::default()
+    });
+    assert!(!response.validate_indices(), "There is no second matching");
+}
+
+#[test]
+fn test_route_class_coverage() {
+    let unclassified = Intersection {
+        classes: None,
+        ..Intersection::default()
+    };
+    let motorway = Intersection {
+        classes: Some(vec!["motorway".to_string()]),
+        ..Intersection::default()
+    };
+    let route = Route {
+        legs: vec![
+            RouteLeg {
+                steps: vec![
+                    // Default intersections are classed toll and restricted
+                    RouteStep {
+                        distance: 100.0,
+                        ..RouteStep::default()
+                    },
+                    RouteStep {
+                        distance: 50.0,
+                        intersections: vec![unclassified],
+                        ..RouteStep::default()
+                    },
+                ],
+                ..RouteLeg::default()
+            },
+            RouteLeg {
+                steps: vec![
+                    RouteStep {
+                        distance: 25.0,
+                        ..RouteStep::default()
+                    },
+                    RouteStep {
+                        distance: 10.0,
+                        intersections: vec![motorway, Intersection::default()],
+                        ..RouteStep::default()
+                    },
+                ],
+                ..RouteLeg::default()
+            },
+        ],
+        ..Route::default()
+    };
+
+    assert_eq!(route.intersections().count(), 7);
+
+    let coverage = route.class_coverage();
+    assert_eq!(coverage.len(), 3, "Unexpected classes {coverage:?}");
+    assert_eq!(coverage["toll"], 125.0);
+    assert_eq!(coverage["restricted"], 125.0);
+    assert_eq!(
+        coverage["motorway"], 10.0,
+        "Only the maneuver intersection should be attributed"
+    );
+
+    assert!(
+        Route::default().class_coverage().is_empty(),
+        "Routes without steps have no class coverage"
+    );
+}
+
+#[test]
+fn test_route_response_hints() {
+    let response = RouteResponse {
+        code: "Ok".to_string(),
+        routes: vec![Route::default()],
+        waypoints: Some(vec![Waypoint::default(); 4]),
+    };
+    assert!(
+        response
+            .waypoints
+            .iter()
+            .flatten()
+            .all(|waypoint| waypoint.hint.is_some()),
+        "Default waypoints should have hints"
+    );
+
+    let mut kept = response.clone();
+    kept.keep_hints_for(&[0, 2, 10]);
+    let hints = kept
+        .waypoints
+        .iter()
+        .flatten()
+        .map(|waypoint| waypoint.hint.is_some())
+        .collect::<Vec<_>>();
+    assert_eq!(hints, [true, false, true, false]);
+
+    let mut stripped = response;
+    stripped.strip_hints();
+    assert!(
+        stripped
+            .waypoints
+            .iter()
+            .flatten()
+            .all(|waypoint| waypoint.hint.is_none()),
+        "All hints should be stripped"
+    );
+}
+
+#[test]
+fn test_route_maneuver_counts() {
+    let step = |maneuver_type: &str| RouteStep {
+        maneuver: StepManeuver {
+            r#type: maneuver_type.to_string(),
+            ..StepManeuver::default()
+        },
+        ..RouteStep::default()
+    };
+    let route = Route {
+        legs: vec![
+            RouteLeg {
+                steps: vec![
+                    step("depart"),
+                    step("turn"),
+                    step("new name"),
+                    step("arrive"),
+                ],
+                ..RouteLeg::default()
+            },
+            RouteLeg {
+                steps: vec![
+                    step("depart"),
+                    step("continue"),
+                    step("roundabout"),
+                    step("turn"),
+                    step("arrive"),
+                ],
+                ..RouteLeg::default()
+            },
+        ],
+        ..Route::default()
+    };
+
+    assert_eq!(route.turn_count(), 3);
+    let counts = route.maneuver_counts();
+    assert_eq!(counts["depart"], 2);
+    assert_eq!(counts["turn"], 2);
+    assert_eq!(counts["roundabout"], 1);
+    assert_eq!(counts.values().sum::<usize>(), 9);
+
+    assert_eq!(
+        Route::default().turn_count(),
+        0,
+        "Routes without steps have no turns"
+    );
+}
+
+#[test]
+fn test_route_driving_side_changes() {
+    // Dover to Calais, then back onto the ferry
+    let step = |driving_side: DrivingSide, location: [f64; 2]| RouteStep {
+        driving_side,
+        maneuver: StepManeuver {
+            location,
+            ..StepManeuver::default()
+        },
+        ..RouteStep::default()
+    };
+    let route = Route {
+        legs: vec![
+            RouteLeg {
+                steps: vec![
+                    step(DrivingSide::Left, [1.3, 51.12]),
+                    step(DrivingSide::Left, [1.33, 51.12]),
+                    step(DrivingSide::Right, [1.85, 50.96]),
+                ],
+                ..RouteLeg::default()
+            },
+            RouteLeg {
+                steps: vec![
+                    step(DrivingSide::Right, [1.85, 50.96]),
+                    step(DrivingSide::Left, [1.33, 51.12]),
+                ],
+                ..RouteLeg::default()
+            },
+        ],
+        ..Route::default()
+    };
+
+    let changes = route.driving_side_changes();
+    assert_eq!(changes.len(), 2, "Changes should be found across legs");
+    assert_eq!(changes[0].as_lon_lat(), (1.85, 50.96));
+    assert_eq!(changes[1].as_lon_lat(), (1.33, 51.12));
+
+    assert!(
+        Route::default().driving_side_changes().is_empty(),
+        "Routes without steps have no changes"
+    );
+}
+
+#[test]
+fn test_route_anomalous_legs() {
+    let leg = |distance: f64, duration: f64| RouteLeg {
+        distance,
+        duration,
+        ..RouteLeg::default()
+    };
+    let route = Route {
+        legs: vec![
+            leg(1000.0, 100.0),
+            // 1000 km/h
+            leg(10_000.0, 36.0),
+            leg(0.0, 0.0),
+            leg(5.0, 0.0),
+            leg(10.0, 100.0),
+        ],
+        ..Route::default()
+    };
+
+    assert_eq!(route.legs[0].average_speed(), Some(10.0));
+    assert_eq!(
+        route.legs[2].average_speed(),
+        None,
+        "Zero duration legs have no speed"
+    );
+    assert_eq!(route.anomalous_legs(1.0, 40.0), vec![1, 3, 4]);
+    assert_eq!(route.anomalous_legs(0.0, f64::INFINITY), vec![3]);
+}
+
+#[test]
+fn test_route_position_at_time() {
+    let line = |coordinates: Vec<[f64; 2]>| {
+        Geometry::GeoJson(GeoJsonLineString {
+            r#type: "LineString".to_string(),
+            coordinates,
+        })
+    };
+    let step = |duration: f64, coordinates: Vec<[f64; 2]>| RouteStep {
+        duration,
+        geometry: line(coordinates),
+        ..RouteStep::default()
+    };
+    // Northwards for 100s, then eastwards for 50s with a bend
+    let route = Route {
+        duration: 150.0,
+        legs: vec![RouteLeg {
+            steps: vec![
+                step(100.0, vec![[10.0, 48.0], [10.0, 48.01]]),
+                step(50.0, vec![[10.0, 48.01], [10.01, 48.01], [10.02, 48.01]]),
+                step(0.0, vec![[10.02, 48.01], [10.02, 48.01]]),
+            ],
+            ..RouteLeg::default()
+        }],
+        ..Route::default()
+    };
+    let position = |elapsed: f64| {
+        route
+            .position_at_time(elapsed, GeometryType::GeoJSON)
+            .map(|p| p.as_lon_lat())
+            .expect("Missing position")
+    };
+    let close =
+        |(a, b): (f64, f64), (c, d): (f64, f64)| (a - c).abs() < 1e-9 && (b - d).abs() < 1e-9;
+
+    assert!(
+        close(position(0.0), (10.0, 48.0)),
+        "Should start at the origin"
+    );
+    assert!(close(position(50.0), (10.0, 48.005)));
+    assert!(
+        close(position(125.0), (10.01, 48.01)),
+        "Should interpolate across the vertices of the step"
+    );
+    assert!(
+        close(position(150.0), (10.02, 48.01)),
+        "Should end at the destination"
+    );
+    assert!(
+        route
+            .position_at_time(150.1, GeometryType::GeoJSON)
+            .is_none(),
+        "Past the end"
+    );
+    assert!(
+        route
+            .position_at_time(-1.0, GeometryType::GeoJSON)
+            .is_none()
+    );
+
+    // Without steps, constant speed along the overview geometry
+    let route = Route {
+        duration: 100.0,
+        geometry: Some(line(vec![[10.0, 48.0], [10.0, 48.01], [10.0, 48.02]])),
+        ..Route::default()
+    };
+    let midpoint = route
+        .position_at_time(50.0, GeometryType::GeoJSON)
+        .expect("Missing position");
+    assert!(close(midpoint.as_lon_lat(), (10.0, 48.01)));
+
+    // The same overview as a polyline6, which must not be decoded at precision 5
+    let route = Route {
+        duration: 100.0,
+        geometry: Some(Geometry::Polyline("__upzA_gjaR_pR?_pR?".to_string())),
+        ..Route::default()
+    };
+    let midpoint = route
+        .position_at_time(50.0, GeometryType::Polyline6)
+        .expect("Missing position");
+    assert!(close(midpoint.as_lon_lat(), (10.0, 48.01)));
+    assert!(
+        route
+            .position_at_time(50.0, GeometryType::GeoJSON)
+            .is_none(),
+        "The precision of a polyline is unknown for GeoJSON"
+    );
+}
+
+#[test]
+fn test_route_response_legs_between() {
+    let legs = (1..=4)
+        .map(|i| RouteLeg {
+            distance: 100.0 * i as f64,
+            duration: 10.0 * i as f64,
+            ..RouteLeg::default()
+        })
+        .collect();
+    let response = RouteResponse {
+        code: "Ok".to_string(),
+        routes: vec![Route {
+            legs,
+            ..Route::default()
+        }],
+        waypoints: None,
+    };
+
+    let spans = response
+        .legs_between(&[0, 3, 4])
+        .expect("Indices should be valid");
+    assert_eq!(spans.len(), 2);
+    assert_eq!(spans[0].legs.len(), 3);
+    assert_eq!(spans[0].distance, 600.0);
+    assert_eq!(spans[0].duration, 60.0);
+    assert_eq!(spans[1].legs.len(), 1);
+    assert_eq!(spans[1].distance, 400.0);
+
+    assert!(
+        response.legs_between(&[0]).is_some_and(|s| s.is_empty()),
+        "A single index has no journeys"
+    );
+    assert!(
+        response.legs_between(&[2, 1]).is_none(),
+        "Decreasing indices should be rejected"
+    );
+    assert!(
+        response.legs_between(&[0, 5]).is_none(),
+        "Out of bounds indices should be rejected"
+    );
+}
+
+#[test]
+fn test_leg_aggregated_segments() {
+    // Nodes 49772551, 49772552 | 49786799..=49786802 with datasources [1, 0, 0, 0, 1]
+    let leg = RouteLeg::default();
+    let segments = leg.aggregated_segments();
+    assert_eq!(
+        segments,
+        [
+            AggregatedSegment {
+                distance: 5.0,
+                duration: 15.0,
+                node_range: 0..=1,
+            },
+            AggregatedSegment {
+                distance: 20.0,
+                duration: 70.0,
+                node_range: 1..=4,
+            },
+            AggregatedSegment {
+                distance: 5.0,
+                duration: 15.0,
+                node_range: 4..=5,
+            },
+        ]
+    );
+
+    let annotation = leg.annotation.as_ref().unwrap();
+    assert_eq!(
+        segments.iter().map(|s| s.distance).sum::<f64>(),
+        annotation.distance.iter().sum::<f64>(),
+        "Aggregation should preserve the total distance"
+    );
+
+    let leg = RouteLeg {
+        annotation: None,
+        ..RouteLeg::default()
+    };
+    assert!(leg.aggregated_segments().is_empty());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_route_response_ndjson() {
+    use osrm_interface::route::write_all_ndjson;
+
+    let responses = [
+        RouteResponse {
+            code: "Ok".to_string(),
+            routes: vec![Route::default()],
+            waypoints: None,
+        },
+        RouteResponse {
+            code: "Ok".to_string(),
+            routes: vec![Route {
+                geometry: Some(Geometry::Polyline("{lu_IypwpAVrAvAdI".to_string())),
+                ..Route::default()
+            }],
+            waypoints: None,
+        },
+    ];
+
+    let mut buffer = Vec::new();
+    write_all_ndjson(&responses, &mut buffer).expect("Failed to write ndjson");
+    let output = String::from_utf8(buffer).expect("ndjson should be utf8");
+
+    let lines = output.lines().collect::<Vec<_>>();
+    assert_eq!(
+        lines.len(),
+        responses.len(),
+        "Expected one line per response"
+    );
+    for (line, expected) in lines.iter().zip(responses.iter()) {
+        let response: RouteResponse =
+            serde_json::from_str(line).expect("Failed to read back ndjson line");
+        assert_eq!(response.code, expected.code);
+        assert_eq!(response.routes.len(), expected.routes.len());
+        assert_eq!(response.routes[0].distance, expected.routes[0].distance);
+    }
+    assert!(matches!(
+        serde_json::from_str::<RouteResponse>(lines[1])
+            .unwrap()
+            .routes[0]
+            .geometry,
+        Some(Geometry::Polyline(_))
+    ));
+}
+
+#[test]
+fn test_geometry_bounding_box() {
+    let route = Route::default();
+    let bbox = route
+        .bounding_box(GeometryType::GeoJSON)
+        .expect("Route should have a bounding box");
+    let Some(Geometry::GeoJson(line)) = &route.geometry else {
+        panic!("Default route geometry should be GeoJson");
+    };
+    for c in &line.coordinates {
+        let vertex = Point::new(c[1], c[0]).expect("Invalid point");
+        assert!(
+            bbox.contains(&vertex),
+            "Bounding box should contain {vertex:?}"
+        );
+    }
+
+    // Vertices (38.5, -120.2), (40.7, -120.95), (43.252, -126.453)
+    let polyline = Geometry::Polyline("_p~iF~ps|U_ulLnnqC_mqNvxq`@".to_string());
+    let bbox = polyline
+        .bounding_box(GeometryType::Polyline)
+        .expect("Polyline should have a bounding box");
+    assert!((bbox.south_west().latitude() - 38.5).abs() < 1e-9);
+    assert!((bbox.south_west().longitude() - -126.453).abs() < 1e-9);
+    assert!((bbox.north_east().latitude() - 43.252).abs() < 1e-9);
+    assert!((bbox.north_east().longitude() - -120.2).abs() < 1e-9);
+
+    // The same vertices at precision 6
+    let polyline6 = Geometry::Polyline("_izlhA~rlgdF_{geC~ywl@_kwzCn`{nI".to_string());
+    let bbox6 = polyline6
+        .bounding_box(GeometryType::Polyline6)
+        .expect("Polyline6 should have a bounding box");
+    assert!((bbox6.south_west().latitude() - 38.5).abs() < 1e-9);
+    assert!((bbox6.south_west().longitude() - -126.453).abs() < 1e-9);
+    assert!((bbox6.north_east().latitude() - 43.252).abs() < 1e-9);
+    assert!((bbox6.north_east().longitude() - -120.2).abs() < 1e-9);
+    assert!(
+        polyline6.bounding_box(GeometryType::GeoJSON).is_none(),
+        "The precision of a polyline is unknown for GeoJSON"
+    );
+
+    let route = Route {
+        geometry: None,
+        ..Route::default()
+    };
+    assert!(
+        route.bounding_box(GeometryType::GeoJSON).is_none(),
+        "No geometry should have no bounding box"
+    );
+    let empty = Geometry::GeoJson(GeoJsonLineString {
+        coordinates: Vec::new(),
+        ..GeoJsonLineString::default()
+    });
+    assert!(
+        empty.bounding_box(GeometryType::GeoJSON).is_none(),
+        "Empty geometry should have no bounding box"
+    );
+}
+
+#[test]
+fn test_route_empty_legs() {
+    let empty_leg = RouteLeg {
+        distance: 0.0,
+        duration: 0.0,
+        weight: 0.0,
+        ..RouteLeg::default()
+    };
+    let route = Route {
+        legs: vec![RouteLeg::default(), empty_leg, RouteLeg::default()],
+        ..Route::default()
+    };
+    assert_eq!(
+        route.non_empty_legs().count(),
+        2,
+        "Zero length leg should be skipped"
+    );
+
+    let mut response = osrm_interface::route::RouteResponse {
+        code: "Ok".to_string(),
+        routes: vec![route],
+        waypoints: None,
+    };
+    response.without_empty_legs();
+    assert_eq!(response.routes[0].legs.len(), 2);
+    assert!(response.routes[0].legs.iter().all(|l| l.distance > 0.0));
+}
+
+#[test]
+fn test_polyline_antimeridian_sign() {
+    // (-16.5, 179.99), (-16.6, -179.99)
+    let geometry = Geometry::Polyline("~sucBohqia@~oR~qctcA".to_string());
+    let bbox = geometry
+        .bounding_box(GeometryType::Polyline)
+        .expect("Polyline should have a bounding box");
+    assert!((bbox.south_west().latitude() - -16.6).abs() < 1e-9);
+    assert!((bbox.south_west().longitude() - -179.99).abs() < 1e-9);
+    assert!((bbox.north_east().latitude() - -16.5).abs() < 1e-9);
+    assert!((bbox.north_east().longitude() - 179.99).abs() < 1e-9);
+}
+
+#[test]
+fn test_route_arrival_times() {
+    let route = Route::default();
+    let departure = 1_700_000_000;
+
+    let arrivals = route.arrival_times(departure);
+    assert_eq!(
+        arrivals.len(),
+        route.legs.len() + 1,
+        "Expected an arrival time for each waypoint"
+    );
+    assert_eq!(arrivals[0], departure);
+    assert!(arrivals.windows(2).all(|w| w[0] <= w[1]));
+    let total_duration: f64 = route.legs.iter().map(|l| l.duration).sum();
+    assert_eq!(
+        *arrivals.last().unwrap(),
+        departure + total_duration.round() as u64,
+        "Final arrival should be departure plus total duration"
+    );
+}
+
+#[test]
+fn test_match_combined_geometry() {
+    let first = vec![[13.0, 52.0], [13.1, 52.0], [13.2, 52.1]];
+    let second = vec![[13.2, 52.1], [13.3, 52.2]];
+    let third = vec![[13.5, 52.3], [13.6, 52.3]];
+    let response = MatchResponse {
+        code: "Ok".to_string(),
+        tracepoints: Vec::new(),
+        matchings: [&first, &second, &third]
+            .into_iter()
+            .map(|coordinates| MatchRoute {
+                geometry: Some(Geometry::GeoJson(GeoJsonLineString {
+                    coordinates: coordinates.clone(),
+                    ..GeoJsonLineString::default()
+                })),
+                ..MatchRoute::default()
+            })
+            .collect(),
+    };
+
+    let Geometry::GeoJson(line) = response.combined_geometry(GeometryType::GeoJSON) else {
+        panic!("Combined geometry should be GeoJson");
+    };
+    // The first two matchings share an endpoint, the gap to the third does not
+    assert_eq!(
+        line.coordinates.len(),
+        first.len() + second.len() + third.len() - 1
+    );
+    assert_eq!(line.coordinates.first(), first.first());
+    assert_eq!(line.coordinates.last(), third.last());
+
+    // Polyline6 matchings are decoded at the requested precision
+    let response = MatchResponse {
+        matchings: vec![MatchRoute {
+            geometry: Some(Geometry::Polyline("_oydbB_smxW?_ibE".to_string())),
+            ..MatchRoute::default()
+        }],
+        ..response
+    };
+    let Geometry::GeoJson(line) = response.combined_geometry(GeometryType::Polyline6) else {
+        panic!("Combined geometry should be GeoJson");
+    };
+    assert_eq!(line.coordinates, vec![[13.0, 52.0], [13.1, 52.0]]);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_float_encoded_integers() {
+    use osrm_interface::osrm_response_types::{Intersection, StepManeuver};
+
+    let maneuver: StepManeuver = serde_json::from_str(
+        r#"{
+            "location": [13.39677, 52.54366],
+            "bearing_before": 10.0,
+            "bearing_after": 95.0,
+            "type": "roundabout",
+            "modifier": "right",
+            "exit": 2.0
+        }"#,
+    )
+    .expect("Failed to parse float exit");
+    assert_eq!(maneuver.exit, Some(2));
+
+    let maneuver: StepManeuver = serde_json::from_str(
+        r#"{
+            "location": [13.39677, 52.54366],
+            "bearing_before": 10.0,
+            "bearing_after": 95.0,
+            "type": "turn"
+        }"#,
+    )
+    .expect("Failed to parse maneuver without exit");
+    assert_eq!(maneuver.exit, None);
+
+    let intersection: Intersection = serde_json::from_str(
+        r#"{
+            "location": [13.39677, 52.54366],
+            "bearings": [10.0, 190, 275.0],
+            "entry": [true, false, true],
+            "in": 1.0,
+            "out": 2
+        }"#,
+    )
+    .expect("Failed to parse float bearings");
+    assert_eq!(intersection.bearings, vec![10, 190, 275]);
+    assert_eq!(intersection.r#in, Some(1));
+    assert_eq!(intersection.out, Some(2));
+
+    assert!(
+        serde_json::from_str::<Intersection>(
+            r#"{"location": [0.0, 0.0], "bearings": [-10.0], "entry": [true]}"#
+        )
+        .is_err(),
+        "Negative bearings should be rejected"
+    );
+    assert!(
+        serde_json::from_str::<Intersection>(
+            r#"{"location": [0.0, 0.0], "bearings": [10.5], "entry": [true]}"#
+        )
+        .is_err(),
+        "Fractional bearings should be rejected rather than rounded"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_response_from_json() {
+    use osrm_interface::{
+        errors::OsrmError, nearest::NearestResponse, table::TableResponse, trip::TripResponse,
+    };
+
+    const LEG: &str = r#"{
+        "distance": 1200.5,
+        "duration": 180.2,
+        "weight": 180.2,
+        "summary": "Start Street, End Street",
+        "steps": []
+    }"#;
+    let route = format!(
+        r#"{{
+            "distance": 1200.5,
+            "duration": 180.2,
+            "weight": 180.2,
+            "weight_name": "routability",
+            "geometry": "_p~iF~ps|U_ulLnnqC",
+            "legs": [{LEG}]
+        }}"#
+    );
+
+    let response = RouteResponse::from_json(&format!(r#"{{"code":"Ok","routes":[{route}]}}"#))
+        .expect("Failed to parse route response");
+    assert_eq!(response.routes[0].distance, 1200.5);
+    assert!(response.waypoints.is_none());
+
+    let response =
+        TableResponse::from_json(r#"{"code":"Ok","durations":[[0.0,12.5],[13.0,null]]}"#)
+            .expect("Failed to parse table response");
+    assert_eq!(
+        response.durations,
+        Some(vec![vec![Some(0.0), Some(12.5)], vec![Some(13.0), None]])
+    );
+
+    let response = TripResponse::from_json(&format!(
+        r#"{{
+            "code": "Ok",
+            "trips": [{route}],
+            "waypoints": [
+                {{"hint":"a","location":[-120.2,38.5],"name":"A","distance":1.0,"trips_index":0,"waypoint_index":1}},
+                {{"hint":"b","location":[-120.95,40.7],"name":"B","distance":2.0,"trips_index":0,"waypoint_index":0}}
+            ]
+        }}"#
+    ))
+    .expect("Failed to parse trip response");
+    let waypoints = response.waypoints.expect("Missing trip waypoints");
+    assert_eq!(waypoints[0].waypoint_index, 1);
+
+    let response = MatchResponse::from_json(&format!(
+        r#"{{
+            "code": "Ok",
+            "tracepoints": [
+                {{"hint":"a","location":[-120.2,38.5],"name":"A","distance":1.0,"matchings_index":0,"waypoint_index":0,"alternatives_count":0}},
+                null,
+                {{"hint":"b","location":[-120.95,40.7],"name":"B","distance":2.0,"matchings_index":0,"waypoint_index":1,"alternatives_count":1}}
+            ],
+            "matchings": [{{
+                "distance": 1200.5,
+                "duration": 180.2,
+                "weight": 180.2,
+                "weight_name": "routability",
+                "geometry": "_p~iF~ps|U_ulLnnqC",
+                "legs": [{LEG}],
+                "confidence": 0.9
+            }}]
+        }}"#
+    ))
+    .expect("Failed to parse match response");
+    assert!(response.tracepoints[1].is_none());
+    assert!(response.validate_indices());
+
+    let response = NearestResponse::from_json(
+        r#"{"code":"Ok","waypoints":[
+            {"hint":"a","location":[10.31655,48.040437],"name":"A","distance":5.0},
+            {"hint":"b","location":[10.31656,48.040438],"name":"B","distance":5.5}
+        ]}"#,
+    )
+    .expect("Failed to parse nearest response");
+    assert!(response.is_ambiguous());
+
+    let result = RouteResponse::from_json(
+        r#"{"code":"NoRoute","message":"Impossible route between points"}"#,
+    );
+    assert!(
+        matches!(
+            &result,
+            Err(OsrmError::Service { code, message, .. })
+                if code == "NoRoute" && message == "Impossible route between points"
+        ),
+        "Expected a service error, got {result:?}"
+    );
+    let result = TableResponse::from_json(r#"{"code":"NoTable","sources":[],"destinations":[]}"#);
+    assert!(
+        matches!(&result, Err(OsrmError::Service { code, .. }) if code == "NoTable"),
+        "Non-Ok codes should be errors even when the response parses, got {result:?}"
+    );
+    let result = RouteResponse::from_json(r#"{"code":"Ok","routes":"#);
+    assert!(
+        matches!(result, Err(OsrmError::JsonParse(_))),
+        "Expected a json parse error, got {result:?}"
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_table_response_weights() {
+    use osrm_interface::table::TableResponse;
+
+    let response = TableResponse::from_json(
+        r#"{
+            "code": "Ok",
+            "durations": [[0.0, 12.5], [13.0, null]],
+            "weights": [[0.0, 20.1], [19.4, null]]
+        }"#,
+    )
+    .expect("Failed to parse table response");
+    assert_eq!(response.weight(0, 1), Some(20.1));
+    assert_eq!(
+        response.weight(1, 1),
+        None,
+        "Unroutable cells have no weight"
+    );
+    assert_eq!(
+        response.weight(2, 0),
+        None,
+        "Out of bounds cells have no weight"
+    );
+
+    let response = TableResponse::from_json(r#"{"code":"Ok","durations":[[0.0]]}"#)
+        .expect("Failed to parse table response");
+    assert!(response.weights.is_none());
+    assert_eq!(response.weight(0, 0), None);
+}
+
+#[test]
+fn test_route_strip_intersections() {
+    let step = |r#type: &str, name: &str| RouteStep {
+        name: name.to_string(),
+        maneuver: StepManeuver {
+            r#type: r#type.to_string(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut route = Route {
+        legs: vec![
+            RouteLeg {
+                steps: vec![
+                    step("depart", "Lortzingstraße"),
+                    step("turn", "Hauptstraße"),
+                ],
+                ..Default::default()
+            },
+            RouteLeg {
+                steps: vec![step("arrive", "Hauptstraße")],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+    assert_eq!(route.intersections().count(), 6);
+
+    route.strip_intersections();
+    assert_eq!(route.intersections().count(), 0);
+    let steps: Vec<_> = route
+        .legs
+        .iter()
+        .flat_map(|leg| &leg.steps)
+        .map(|step| (step.maneuver.r#type.as_str(), step.name.as_str()))
+        .collect();
+    assert_eq!(
+        steps,
+        [
+            ("depart", "Lortzingstraße"),
+            ("turn", "Hauptstraße"),
+            ("arrive", "Hauptstraße")
+        ],
+        "Maneuvers and names should be kept"
+    );
+    assert_eq!(route.turn_count(), 1);
+}
+
+#[test]
+fn test_trip_roundtrip_order() {
+    use osrm_interface::{osrm_response_types::TripWaypoint, trip::TripResponse};
+
+    // The first input point is visited first, then the third, then the second
+    let waypoints: Vec<TripWaypoint> = [0, 2, 1]
+        .into_iter()
+        .map(|waypoint_index| TripWaypoint {
+            waypoint_index,
+            ..Default::default()
+        })
+        .collect();
+    let trip = |len_legs: usize| TripResponse {
+        code: "Ok".to_string(),
+        trips: vec![Route {
+            legs: vec![RouteLeg::default(); len_legs],
+            ..Default::default()
+        }],
+        waypoints: Some(waypoints.clone()),
+    };
+
+    let roundtrip = trip(3);
+    assert_eq!(
+        roundtrip.roundtrip_order(),
+        Some(vec![0, 2, 1, 0]),
+        "The loop should close at the start"
+    );
+
+    // An open trip visits the same order without returning
+    let open = trip(2);
+    assert_eq!(open.roundtrip_order(), None);
+
+    let mut skipped = trip(3);
+    skipped.waypoints = None;
+    assert_eq!(skipped.roundtrip_order(), None);
+    let mut split = trip(3);
+    split.trips.push(Route::default());
+    assert_eq!(
+        split.roundtrip_order(),
+        None,
+        "Only a single trip is a loop"
+    );
+}
+
+#[test]
+fn test_response_code() {
+    for code in [
+        ResponseCode::Ok,
+        ResponseCode::InvalidQuery,
+        ResponseCode::NoSegment,
+        ResponseCode::NoTrips,
+        ResponseCode::NoMatch,
+    ] {
+        assert_eq!(ResponseCode::from(code.as_str()), code);
+    }
+    let code = ResponseCode::from("TooFarAway");
+    assert_eq!(code, ResponseCode::Other("TooFarAway".to_string()));
+    assert_eq!(code.to_string(), "TooFarAway");
+    assert!(!code.is_ok());
+
+    let response = RouteResponse {
+        code: "NoRoute".to_string(),
+        routes: vec![],
+        waypoints: None,
+    };
+    assert_eq!(response.code_enum(), ResponseCode::NoRoute);
+}
+
+#[test]
+fn test_response_locations_as_points() {
+    let location = [13.388799, 52.517033];
+    let expected = Point::new(52.517033, 13.388799).expect("Invalid point");
+    assert_eq!(lon_lat_point(location), expected);
+
+    let waypoint = Waypoint {
+        location,
+        ..Waypoint::default()
+    };
+    assert_eq!(waypoint.as_point(), expected);
+    let trip_waypoint = TripWaypoint {
+        location,
+        ..TripWaypoint::default()
+    };
+    assert_eq!(trip_waypoint.as_point(), expected);
+    let nearest_waypoint = NearestWaypoint {
+        location,
+        ..NearestWaypoint::default()
+    };
+    assert_eq!(nearest_waypoint.as_point(), expected);
+    let match_waypoint = MatchWaypoint {
+        location,
+        ..MatchWaypoint::default()
+    };
+    assert_eq!(match_waypoint.as_point(), expected);
+    let maneuver = StepManeuver {
+        location,
+        ..StepManeuver::default()
+    };
+    assert_eq!(maneuver.as_point(), expected);
+    let intersection = Intersection {
+        location,
+        ..Intersection::default()
+    };
+    assert_eq!(intersection.as_point(), expected);
+}
+
+#[test]
+fn test_route_alternatives_summary() {
+    let route = |distance: f64, duration: f64, coordinates: Option<Vec<[f64; 2]>>| Route {
+        distance,
+        duration,
+        geometry: coordinates.map(|coordinates| {
+            Geometry::GeoJson(GeoJsonLineString {
+                r#type: "LineString".to_string(),
+                coordinates,
+            })
+        }),
+        ..Route::default()
+    };
+    let response = RouteResponse {
+        code: "Ok".to_string(),
+        routes: vec![
+            route(
+                3000.0,
+                300.0,
+                Some(vec![
+                    [10.0, 48.0],
+                    [10.01, 48.0],
+                    [10.02, 48.0],
+                    [10.03, 48.0],
+                ]),
+            ),
+            // Leaves after the second vertex and rejoins at the third
+            route(
+                3500.0,
+                420.0,
+                Some(vec![
+                    [10.0, 48.0],
+                    [10.01, 48.0],
+                    [10.015, 48.005],
+                    [10.02, 48.0],
+                    [10.03, 48.0],
+                ]),
+            ),
+            route(2800.0, 330.0, None),
+        ],
+        waypoints: None,
+    };
+
+    let summaries = response.alternatives_summary(GeometryType::GeoJSON);
+    assert_eq!(summaries.len(), 2);
+    let AlternativeSummary {
+        distance_delta,
+        duration_delta,
+        divergence_start,
+        rejoin,
+    } = summaries[0];
+    assert_eq!((distance_delta, duration_delta), (500.0, 120.0));
+    assert_eq!(divergence_start, Point::new(48.0, 10.01));
+    assert_eq!(rejoin, Point::new(48.0, 10.02));
+
+    assert_eq!(summaries[1].distance_delta, -200.0);
+    assert_eq!(summaries[1].duration_delta, 30.0);
+    assert!(summaries[1].divergence_start.is_none() && summaries[1].rejoin.is_none());
+
+    // The same overviews as polyline6
+    let polyline6 = |distance: f64, duration: f64, polyline: &str| Route {
+        distance,
+        duration,
+        geometry: Some(Geometry::Polyline(polyline.to_string())),
+        ..Route::default()
+    };
+    let polyline6_response = RouteResponse {
+        code: "Ok".to_string(),
+        routes: vec![
+            polyline6(3000.0, 300.0, "__upzA_gjaR?_pR?_pR?_pR"),
+            polyline6(3500.0, 420.0, "__upzA_gjaR?_pRowHowHnwHowH?_pR"),
+        ],
+        waypoints: None,
+    };
+    let summaries = polyline6_response.alternatives_summary(GeometryType::Polyline6);
+    let close = |point: Option<Point>, expected: Option<Point>| {
+        let (point, expected) = (point.unwrap(), expected.unwrap());
+        point.haversine_distance(&expected) < 0.01
+    };
+    assert!(close(
+        summaries[0].divergence_start,
+        Point::new(48.0, 10.01)
+    ));
+    assert!(close(summaries[0].rejoin, Point::new(48.0, 10.02)));
+
+    let single = RouteResponse {
+        routes: response.routes[..1].to_vec(),
+        ..response
+    };
+    assert!(
+        single
+            .alternatives_summary(GeometryType::GeoJSON)
+            .is_empty()
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_partial_annotation_from_json() {
+    let response = RouteResponse::from_json(
+        r#"{
+            "code": "Ok",
+            "routes": [{
+                "distance": 20.0,
+                "duration": 4.0,
+                "weight": 4.0,
+                "weight_name": "routability",
+                "legs": [{
+                    "distance": 20.0,
+                    "duration": 4.0,
+                    "weight": 4.0,
+                    "summary": "",
+                    "steps": [],
+                    "annotation": {"speed": [5.0, 5.0]}
+                }]
+            }]
+        }"#,
+    )
+    .expect("Failed to parse route response with only speed annotations");
+    let annotation = response.routes[0].legs[0]
+        .annotation
+        .as_ref()
+        .expect("Missing annotation");
+    assert_eq!(annotation.speed, vec![5.0, 5.0]);
+    assert!(annotation.duration.is_empty() && annotation.nodes.is_empty());
+    assert!(annotation.metadata.datasource_names.is_empty());
+}
+
+#[test]
+fn test_route_collapsed_waypoints() {
+    let waypoint = |location: [f64; 2]| Waypoint {
+        hint: None,
+        location,
+        name: String::new(),
+        distance: 0.0,
+    };
+    let mut response = RouteResponse {
+        code: "Ok".to_string(),
+        routes: vec![Route::default()],
+        waypoints: Some(vec![
+            waypoint([10.0, 48.0]),
+            // Two inputs either side of a road snapping to nearly the same location
+            waypoint([10.01, 48.0]),
+            waypoint([10.01001, 48.0]),
+            waypoint([10.02, 48.0]),
+        ]),
+    };
+
+    assert_eq!(response.collapsed_waypoints(1.0), vec![(1, 2)]);
+    assert!(response.collapsed_waypoints(0.1).is_empty());
+    assert_eq!(response.collapsed_waypoints(800.0).len(), 5);
+
+    response.waypoints = None;
+    assert!(response.collapsed_waypoints(1.0).is_empty());
+}
+
+#[test]
+fn test_match_ambiguous_split_points() {
+    let points = [
+        Point::new(52.517037, 13.388860).expect("Invalid point"),
+        Point::new(52.529407, 13.397634).expect("Invalid point"),
+        Point::new(52.523219, 13.428555).expect("Invalid point"),
+        Point::new(52.520000, 13.430000).expect("Invalid point"),
+        Point::new(52.518000, 13.440000).expect("Invalid point"),
+    ];
+    let ambiguous = |alternatives_count| {
+        Some(MatchWaypoint {
+            alternatives_count,
+            ..MatchWaypoint::default()
+        })
+    };
+    let response = MatchResponse {
+        code: "Ok".to_string(),
+        tracepoints: vec![ambiguous(0), ambiguous(2), None, ambiguous(1), ambiguous(0)],
+        matchings: vec![MatchRoute::default()],
+    };
+    let split_points = response.ambiguous_split_points();
+    assert_eq!(split_points, vec![1, 3]);
+
+    let timestamps = [0, 10, 20, 30, 40];
+    let request = MatchRequestBuilder::new(&points)
+        .timestamps(&timestamps)
+        .waypoints(&[0, 4])
+        .build()
+        .expect("Failed to build match request");
+    let sub_traces = request.split_at(&split_points);
+    assert_eq!(sub_traces.len(), 3);
+
+    let query = |parts: &osrm_interface::services::UrlParts, key: &str| {
+        parts
+            .query
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.clone())
+    };
+    let expected_timestamps = ["0;10", "10;20;30", "30;40"];
+    for (sub_trace, expected) in sub_traces.iter().zip(expected_timestamps) {
+        let parts = sub_trace.to_url_parts("car", "v1", None);
+        assert_eq!(query(&parts, "timestamps").as_deref(), Some(expected));
+        assert_eq!(query(&parts, "waypoints"), None);
+    }
+    // The split point is shared by consecutive sub-traces
+    let path = |i: usize| sub_traces[i].to_url_parts("car", "v1", None).path;
+    assert!(path(0).ends_with(&points[1].to_string()));
+    assert!(path(1).contains(&format!("/{};", points[1])));
+
+    assert_eq!(request.split_at(&[0, 4, 9]).len(), 1);
+}
@@ -0,0 +1,174 @@
+#![cfg(feature = "wasm")]
+
+mod common;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use common::fixtures;
+use osrm_interface::{
+    Point,
+    errors::{OsrmError, RemoteOsrmError},
+    nearest::NearestRequestBuilder,
+    osrm_response_types::ResponseCode,
+    route::RouteRequestBuilder,
+    table::TableRequestBuilder,
+    wasm::{Fetch, FetchResponse, OsrmEngine, Profile},
+};
+
+/// Responds to every request with `response`, recording the urls.
+struct MockFetch {
+    response: Result<FetchResponse, String>,
+    urls: RefCell<Vec<String>>,
+}
+
+impl MockFetch {
+    fn new(status: u16, body: &str) -> Self {
+        Self {
+            response: Ok(FetchResponse {
+                status,
+                body: body.to_string(),
+            }),
+            urls: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+impl Fetch for MockFetch {
+    async fn get(&self, url: &str) -> Result<FetchResponse, String> {
+        self.urls.borrow_mut().push(url.to_string());
+        self.response.clone()
+    }
+}
+
+/// Run a future which completes without waiting, as with [`MockFetch`].
+fn block_on<T>(future: impl Future<Output = T>) -> T {
+    match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("MockFetch should complete immediately"),
+    }
+}
+
+fn points() -> [Point; 2] {
+    [
+        Point::new(48.040437, 10.316550).expect("Invalid point"),
+        Point::new(49.006101, 9.052887).expect("Invalid point"),
+    ]
+}
+
+#[test]
+fn test_wasm_urls() {
+    let points = points();
+    let engine = OsrmEngine::new(
+        "https://maps.example.com/osrm/".to_string(),
+        Profile::Foot,
+        MockFetch::new(200, fixtures::ROUTE_RESPONSE),
+    );
+
+    let route_request = RouteRequestBuilder::new(&points)
+        .steps(true)
+        .build()
+        .expect("Failed to build route request");
+    let url = engine.route_url(&route_request);
+    assert!(
+        url.starts_with(
+            "https://maps.example.com/osrm/route/v1/foot/10.316550,48.040437;9.052887,49.006101?"
+        ),
+        "Unexpected url {url}"
+    );
+    assert!(url.contains("steps=true"));
+
+    let table_request = TableRequestBuilder::new(&points, &points)
+        .build()
+        .expect("Failed to build table request");
+    assert!(
+        engine
+            .table_url(&table_request)
+            .starts_with("https://maps.example.com/osrm/table/v1/foot/")
+    );
+
+    let nearest_request = NearestRequestBuilder::new(&points[0], 3)
+        .build()
+        .expect("Failed to build nearest request");
+    assert!(engine.nearest_url(&nearest_request).contains("number=3"));
+
+    block_on(engine.route(&route_request)).expect("Failed to route");
+    assert_eq!(*engine.fetch().urls.borrow(), [url]);
+}
+
+#[test]
+fn test_wasm_route() {
+    let points = points();
+    let engine = OsrmEngine::new(
+        "http://localhost:5000".to_string(),
+        Profile::Car,
+        MockFetch::new(200, fixtures::ROUTE_RESPONSE),
+    );
+    let route_request = RouteRequestBuilder::new(&points)
+        .build()
+        .expect("Failed to build route request");
+
+    let response = block_on(engine.route(&route_request)).expect("Failed to route");
+    assert_eq!(response.routes[0].distance, 1200.5);
+}
+
+#[test]
+fn test_wasm_errors() {
+    let points = points();
+    let route_request = RouteRequestBuilder::new(&points)
+        .build()
+        .expect("Failed to build route request");
+
+    let engine = OsrmEngine::new(
+        "http://localhost:5000".to_string(),
+        Profile::Car,
+        MockFetch::new(
+            400,
+            r#"{"code":"NoSegment","message":"Could not find a matching segment for coordinate 1"}"#,
+        ),
+    );
+    let error = block_on(engine.route(&route_request)).expect_err("NoSegment should be an error");
+    assert_eq!(error.response_code(), Some(ResponseCode::NoSegment));
+
+    let engine = OsrmEngine::new(
+        "http://localhost:5000".to_string(),
+        Profile::Car,
+        MockFetch::new(502, "<html>Bad gateway</html>"),
+    );
+    let error = block_on(engine.route(&route_request)).expect_err("502 should be an error");
+    assert!(matches!(
+        error,
+        OsrmError::Remote(RemoteOsrmError::EndpointError(_))
+    ));
+
+    let engine = OsrmEngine::new(
+        "http://localhost:5000".to_string(),
+        Profile::Car,
+        MockFetch::new(200, "<html></html>"),
+    );
+    let error = block_on(engine.route(&route_request)).expect_err("HTML should not parse");
+    assert!(matches!(
+        error,
+        OsrmError::Remote(RemoteOsrmError::JsonParse(_))
+    ));
+
+    let engine = OsrmEngine::new(
+        "http://localhost:5000".to_string(),
+        Profile::Car,
+        MockFetch::new(200, ""),
+    );
+    let error = block_on(engine.route(&route_request)).expect_err("Empty body should be an error");
+    assert!(matches!(error, OsrmError::EmptyResponse(_)));
+
+    let engine = OsrmEngine::new(
+        "http://localhost:5000".to_string(),
+        Profile::Car,
+        MockFetch {
+            response: Err("Blocked by CORS policy".to_string()),
+            urls: RefCell::new(Vec::new()),
+        },
+    );
+    let error = block_on(engine.route(&route_request)).expect_err("Fetch failure is an error");
+    assert!(error.to_string().contains("CORS"));
+}